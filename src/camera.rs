@@ -32,7 +32,7 @@ pub fn render_camera(ecs: &World, ctx: &mut BTerm) {
     let mut data = (&positions, &renderables, !&hidden)
         .join()
         .collect::<Vec<_>>();
-    data.sort_by(|&a, &b| b.1.render_order.cmp(&a.1.render_order));
+    data.sort_by_key(|a| std::cmp::Reverse(a.1.render_order));
     for (pos, render, _) in data.iter() {
         if !map.tiles[map.point2d_to_index(pos.into_point())].visible {
             continue;
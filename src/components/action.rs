@@ -1,40 +1,78 @@
 use bracket_lib::terminal::Point;
-use specs::prelude::*;
+use serde::{Deserialize, Serialize};
+use specs::{
+    prelude::*,
+    saveload::{ConvertSaveload, Marker},
+};
 use specs_derive::*;
+use std::convert::Infallible as NoError;
 
-#[derive(Component)]
+#[derive(Component, ConvertSaveload, Clone)]
 pub struct WantsToMelee {
     pub target: Entity,
 }
 
-#[derive(Component)]
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct WantsToShoot {
+    pub target: Entity,
+}
+
+#[derive(Component, ConvertSaveload, Clone)]
 pub struct WantsToPickupItem {
     pub collected_by: Entity,
     pub item: Entity,
 }
 
-#[derive(Component)]
+#[derive(Component, ConvertSaveload, Clone)]
 pub struct WantsToUseItem {
     pub item: Entity,
     pub target: Option<Point>,
 }
 
-#[derive(Component)]
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct WantsToCastSpell {
+    pub spell: Entity,
+    pub target: Option<Point>,
+}
+
+#[derive(Component, ConvertSaveload, Clone)]
 pub struct WantsToDropItem {
     pub item: Entity,
+    /// How many to drop off a stack; the whole of it when `None`.
+    pub quantity: Option<i32>,
 }
 
-#[derive(Component)]
+#[derive(Component, ConvertSaveload, Clone)]
 pub struct WantsToRemoveItem {
     pub item: Entity,
 }
 
-#[derive(Component)]
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct WantsToOpenDoor {
+    pub door: Entity,
+}
+
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct WantsToCloseDoor {
+    pub door: Entity,
+}
+
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct WantsToBashDoor {
+    pub door: Entity,
+}
+
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct WantsToOpenContainer {
+    pub container: Entity,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct WantsToApproach {
     pub idx: i32,
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct WantsToFlee {
     pub indices: Vec<usize>,
 }
@@ -1,30 +1,266 @@
-use specs::prelude::*;
+use super::unit::AttributeType;
+use serde::{Deserialize, Serialize};
+use specs::{
+    prelude::*,
+    saveload::{ConvertSaveload, Marker},
+};
 use specs_derive::*;
+use std::{collections::HashMap, convert::Infallible as NoError};
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct SpellTemplate {
+    pub mana_cost: i32,
+    pub difficulty: i32,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Ranged {
     pub range: i32,
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct ProvidesHealing {
     pub heal_amount: i32,
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct ProvidesFood {}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Identifies {}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct RemovesCurse {}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct InflictsDamage {
     pub damage: i32,
+    pub damage_type: DamageType,
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum DamageType {
+    Slashing,
+    Piercing,
+    Bludgeoning,
+    Fire,
+    Cold,
+    Poison,
+    Acid,
+    Arcane,
+}
+
+impl DamageType {
+    pub fn parse(name: &str) -> Option<DamageType> {
+        match name.to_lowercase().as_str() {
+            "slashing" => Some(DamageType::Slashing),
+            "piercing" => Some(DamageType::Piercing),
+            "bludgeoning" => Some(DamageType::Bludgeoning),
+            "fire" => Some(DamageType::Fire),
+            "cold" => Some(DamageType::Cold),
+            "poison" => Some(DamageType::Poison),
+            "acid" => Some(DamageType::Acid),
+            "arcane" => Some(DamageType::Arcane),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DamageType::Slashing => "slashing",
+            DamageType::Piercing => "piercing",
+            DamageType::Bludgeoning => "bludgeoning",
+            DamageType::Fire => "fire",
+            DamageType::Cold => "cold",
+            DamageType::Poison => "poison",
+            DamageType::Acid => "acid",
+            DamageType::Arcane => "arcane",
+        }
+    }
 }
 
-#[derive(Component)]
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum Resistance {
+    Normal,
+    Resistant,
+    Immune,
+    Vulnerable,
+}
+
+impl Resistance {
+    pub fn parse(name: &str) -> Option<Resistance> {
+        match name.to_lowercase().as_str() {
+            "resistant" => Some(Resistance::Resistant),
+            "immune" => Some(Resistance::Immune),
+            "vulnerable" => Some(Resistance::Vulnerable),
+            _ => None,
+        }
+    }
+
+    /// Folds another source in: immunity wins outright, otherwise a
+    /// resistance and a vulnerability cancel each other out.
+    pub fn combine(self, other: Resistance) -> Resistance {
+        match (self, other) {
+            (Resistance::Immune, _) | (_, Resistance::Immune) => Resistance::Immune,
+            (Resistance::Normal, r) | (r, Resistance::Normal) => r,
+            (Resistance::Resistant, Resistance::Vulnerable)
+            | (Resistance::Vulnerable, Resistance::Resistant) => Resistance::Normal,
+            (r, _) => r,
+        }
+    }
+
+    pub fn apply(self, damage: i32) -> i32 {
+        match self {
+            Resistance::Normal => damage,
+            Resistance::Resistant => damage / 2,
+            Resistance::Immune => 0,
+            Resistance::Vulnerable => damage * 2,
+        }
+    }
+
+    /// The note shown after a damage line, if any.
+    pub fn note(self) -> Option<&'static str> {
+        match self {
+            Resistance::Normal => None,
+            Resistance::Resistant => Some("(resisted)"),
+            Resistance::Immune => Some("(immune)"),
+            Resistance::Vulnerable => Some("(vulnerable)"),
+        }
+    }
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Default)]
+pub struct Resistances {
+    pub table: HashMap<DamageType, Resistance>,
+}
+
+impl Resistances {
+    pub fn get(&self, damage_type: DamageType) -> Resistance {
+        self.table
+            .get(&damage_type)
+            .copied()
+            .unwrap_or(Resistance::Normal)
+    }
+}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct AreaOfEffect {
     pub radius: i32,
 }
 
-#[derive(Component)]
-pub struct Confusion {
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum StatusKind {
+    Confusion,
+    Stun,
+    Poison {
+        damage: i32,
+    },
+    Bleed {
+        damage: i32,
+    },
+    Regeneration {
+        heal: i32,
+    },
+    Slow {
+        initiative_penalty: i32,
+    },
+    Haste {
+        initiative_bonus: i32,
+    },
+    AttributeModifier {
+        attribute: AttributeType,
+        amount: i32,
+    },
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum Stacking {
+    Refresh,
+    Stack,
+}
+
+impl StatusKind {
+    pub fn stacking(self) -> Stacking {
+        match self {
+            StatusKind::Poison { .. } | StatusKind::Bleed { .. } => Stacking::Stack,
+            _ => Stacking::Refresh,
+        }
+    }
+
+    pub fn same_kind(self, other: StatusKind) -> bool {
+        match (self, other) {
+            (
+                StatusKind::AttributeModifier { attribute: a, .. },
+                StatusKind::AttributeModifier { attribute: b, .. },
+            ) => a == b,
+            _ => std::mem::discriminant(&self) == std::mem::discriminant(&other),
+        }
+    }
+
+    pub fn is_harmful(self) -> bool {
+        match self {
+            StatusKind::Regeneration { .. } | StatusKind::Haste { .. } => false,
+            StatusKind::AttributeModifier { amount, .. } => amount < 0,
+            _ => true,
+        }
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            StatusKind::Confusion => "Confusion".to_string(),
+            StatusKind::Stun => "Stun".to_string(),
+            StatusKind::Poison { .. } => "Poison".to_string(),
+            StatusKind::Bleed { .. } => "Bleeding".to_string(),
+            StatusKind::Regeneration { .. } => "Regeneration".to_string(),
+            StatusKind::Slow { .. } => "Slow".to_string(),
+            StatusKind::Haste { .. } => "Haste".to_string(),
+            StatusKind::AttributeModifier { attribute, amount } => {
+                format!("{} {:+}", attribute.name(), amount)
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StatusTemplate {
+    pub kind: StatusKind,
     pub duration: i32,
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct GrantsStatus {
+    pub effects: Vec<StatusTemplate>,
+}
+
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct StatusEffect {
+    pub target: Entity,
+    pub kind: StatusKind,
+    pub duration: i32,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct EntryTrigger {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resistances_scale_damage() {
+        assert_eq!(Resistance::Normal.apply(7), 7);
+        assert_eq!(Resistance::Resistant.apply(7), 3);
+        assert_eq!(Resistance::Immune.apply(7), 0);
+        assert_eq!(Resistance::Vulnerable.apply(7), 14);
+    }
+
+    #[test]
+    fn immunity_wins_and_opposites_cancel() {
+        use Resistance::*;
+        assert!(Resistant.combine(Vulnerable) == Normal);
+        assert!(Vulnerable.combine(Resistant) == Normal);
+        assert!(Vulnerable.combine(Immune) == Immune);
+        assert!(Normal.combine(Resistant) == Resistant);
+        assert!(Resistant.combine(Resistant) == Resistant);
+    }
+}
@@ -1,24 +1,55 @@
-use specs::prelude::*;
+use super::{effect::DamageType, unit::AttributeType};
+use serde::{Deserialize, Serialize};
+use specs::{
+    prelude::*,
+    saveload::{ConvertSaveload, Marker},
+};
 use specs_derive::*;
+use std::{
+    collections::{HashMap, HashSet},
+    convert::Infallible as NoError,
+};
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Item {
     pub initiative_penalty: f32,
     pub weight: f32,
     pub base_value: i32,
 }
 
-#[derive(Component)]
+/// Several identical items sharing one entity.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Stackable {
+    pub quantity: i32,
+}
+
+/// How an item reads in menus and the log, with its count when there is more
+/// than one.
+pub fn item_label(name: String, stack: Option<&Stackable>) -> String {
+    match stack {
+        Some(stack) if stack.quantity > 1 => format!("{} (x{})", name, stack.quantity),
+        _ => name,
+    }
+}
+
+#[derive(Component, ConvertSaveload, Clone)]
 pub struct InBackpack {
     pub owner: Entity,
 }
 
-#[derive(Component)]
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct InContainer {
+    pub owner: Entity,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Consumable {}
 
-#[derive(PartialEq, Copy, Clone)]
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
 pub enum EquipmentSlot {
     Melee,
+    Ranged,
+    /// The off hand, holding either a shield or a second weapon.
     Shield,
     Hands,
     Head,
@@ -27,30 +58,33 @@ pub enum EquipmentSlot {
     Torso,
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Equippable {
     pub slot: EquipmentSlot,
 }
 
-#[derive(Component, PartialEq)]
+#[derive(Component, ConvertSaveload, Clone, PartialEq)]
 pub struct Equipped {
     pub owner: Entity,
     pub slot: EquipmentSlot,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub enum WeaponAttribute {
     Might,
     Quickness,
 }
 
-#[derive(Component, Clone, Copy)]
+#[derive(Component, Serialize, Deserialize, Clone, Copy)]
 pub struct MeleeWeapon {
     pub attribute: WeaponAttribute,
     pub damage_n_dice: i32,
     pub damage_die_type: i32,
     pub damage_bonus: i32,
     pub hit_bonus: i32,
+    pub damage_type: DamageType,
+    pub two_handed: bool,
+    pub off_hand: bool,
 }
 
 impl MeleeWeapon {
@@ -61,15 +95,97 @@ impl MeleeWeapon {
             damage_die_type: 4,
             damage_bonus: 0,
             hit_bonus: 0,
+            damage_type: DamageType::Bludgeoning,
+            two_handed: false,
+            off_hand: false,
         }
     }
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct RangedWeapon {
+    pub range: i32,
+    pub attribute: WeaponAttribute,
+    pub damage_n_dice: i32,
+    pub damage_die_type: i32,
+    pub damage_bonus: i32,
+    pub hit_bonus: i32,
+    pub damage_type: DamageType,
+    pub ammo: Option<String>,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Ammunition {
+    pub kind: String,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Wearable {
     pub armor_class: i32,
     pub slot: EquipmentSlot,
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Shield {
+    pub block_chance: i32,
+}
+
+/// Attribute modifiers granted to whoever has the item equipped.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct AttributeBonus {
+    pub modifiers: Vec<(AttributeType, i32)>,
+}
+
+/// Extra damage of its own type a weapon adds to every hit.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct DamageBonus {
+    pub damage_type: DamageType,
+    pub n_dice: i32,
+    pub die_type: i32,
+    pub bonus: i32,
+}
+
+/// Can't be taken off once equipped, until the curse is lifted.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Cursed {}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct EquipmentChanged {}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Disguise {
+    pub name: String,
+    pub color: String,
+}
+
+/// The names and colors unidentified item types wear this run, and which of
+/// them the player has already seen through.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Identification {
+    pub disguises: HashMap<String, Disguise>,
+    pub known: HashSet<String>,
+}
+
+impl Identification {
+    pub fn is_known(&self, name: &str) -> bool {
+        !self.disguises.contains_key(name) || self.known.contains(name)
+    }
+
+    pub fn display_name(&self, name: &str) -> String {
+        match self.disguises.get(name) {
+            Some(disguise) if !self.known.contains(name) => disguise.name.clone(),
+            _ => name.to_string(),
+        }
+    }
+
+    /// Learns what `name` is, returning the disguise it wore until now.
+    pub fn identify(&mut self, name: &str) -> Option<String> {
+        if self.is_known(name) {
+            return None;
+        }
+        self.known.insert(name.to_string());
+        self.disguises
+            .get(name)
+            .map(|disguise| disguise.name.clone())
+    }
+}
@@ -1,5 +1,6 @@
 use bracket_lib::terminal::Point;
 use bracket_terminal::{prelude::RGB, FontCharType};
+use serde::{Deserialize, Serialize};
 use specs::prelude::*;
 use specs_derive::*;
 pub mod action;
@@ -8,7 +9,7 @@ pub mod item;
 pub mod props;
 pub mod unit;
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Renderable {
     pub glyph: FontCharType,
     pub fg: RGB,
@@ -16,7 +17,7 @@ pub struct Renderable {
     pub render_order: i32,
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Name {
     pub name: String,
 }
@@ -29,21 +30,23 @@ impl Name {
     }
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
+#[storage(FlaggedStorage)]
 pub struct BlocksTile {}
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct ParticleLifetime {
     pub lifetime_ms: f32,
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Hidden {}
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct BlocksVisibility {}
 
-#[derive(Component, Clone, Copy)]
+#[derive(Component, Serialize, Deserialize, Clone, Copy)]
+#[storage(FlaggedStorage)]
 pub struct Position {
     pub x: i32,
     pub y: i32,
@@ -69,9 +72,28 @@ impl Position {
     }
 }
 
-#[derive(Component, Clone, Copy)]
+#[derive(Component, Serialize, Deserialize, Clone, Copy)]
 pub struct OtherLevelPosition {
     pub x: i32,
     pub y: i32,
     pub depth: i32,
 }
+
+pub struct SerializeMe;
+
+/// Ticks elapsed since the run started, advanced by the initiative system.
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+pub struct TurnCounter {
+    pub turn: i32,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct SerializationHelper {
+    pub map: super::Map,
+    pub master: super::map::master::MasterMap,
+    pub rng: super::rng::RandomGen,
+    pub turns: TurnCounter,
+    pub reputation: unit::Reputation,
+    pub identification: item::Identification,
+    pub log: Vec<Vec<super::logger::LogFragment>>,
+}
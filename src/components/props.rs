@@ -1,17 +1,53 @@
 use bracket_lib::terminal::RGB;
+use bracket_terminal::FontCharType;
+use serde::{Deserialize, Serialize};
 use specs::prelude::*;
 use specs_derive::*;
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct SingleActivation {}
 
-#[derive(Component)]
+/// A door keeps both of its glyphs so it can swap them as it opens and
+/// closes. A locked door opens for whoever carries `key`, or to a pick that
+/// beats `lock`; anyone may try to break it down against its `strength`.
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Door {
     pub open: bool,
+    pub locked: bool,
+    pub key: Option<String>,
+    pub lock: i32,
+    pub strength: i32,
+    pub open_glyph: FontCharType,
+    pub closed_glyph: FontCharType,
 }
 
-#[derive(Component)]
+/// Mobs that can work a door handle on their way.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct OpensDoors {}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct LightSource {
     pub color: RGB,
     pub range: i32,
 }
+
+/// A prop holding items, each of which carries an `InContainer` pointing back
+/// at it. Like a door, a locked container opens for whoever carries `key`, or
+/// to a pick that beats `lock`.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Container {
+    pub locked: bool,
+    pub key: Option<String>,
+    pub lock: i32,
+}
+
+/// A container whose trap, held as its `InflictsDamage`, springs on whoever
+/// first opens it.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Trapped {}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum ContainerMode {
+    Take,
+    Put,
+}
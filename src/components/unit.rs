@@ -1,11 +1,18 @@
+use super::super::raws::factions::Reaction;
+use super::effect::DamageType;
 use bracket_lib::terminal::Point;
-use specs::prelude::*;
+use serde::{Deserialize, Serialize};
+use specs::{
+    prelude::*,
+    saveload::{ConvertSaveload, Marker},
+};
 use specs_derive::*;
+use std::{collections::HashMap, convert::Infallible as NoError};
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Player {}
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Viewshed {
     pub visible_tiles: Vec<Point>,
     pub range: i32,
@@ -22,12 +29,12 @@ impl Viewshed {
     }
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Quips {
     pub available: Vec<String>,
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct SufferDamage {
     pub amount: Vec<(i32, bool)>, // is the player doing the damage ?
 }
@@ -52,10 +59,10 @@ impl SufferDamage {
     }
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct EntityMoved {}
 
-#[derive(Component, Clone, Copy)]
+#[derive(Component, Serialize, Deserialize, Clone, Copy)]
 pub struct Attribute {
     pub base: i32,
     pub modifiers: i32,
@@ -84,7 +91,26 @@ impl Default for Attribute {
     }
 }
 
-#[derive(Component, Clone, Copy)]
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum AttributeType {
+    Might,
+    Fitness,
+    Quickness,
+    Intelligence,
+}
+
+impl AttributeType {
+    pub fn name(self) -> &'static str {
+        match self {
+            AttributeType::Might => "Might",
+            AttributeType::Fitness => "Fitness",
+            AttributeType::Quickness => "Quickness",
+            AttributeType::Intelligence => "Intelligence",
+        }
+    }
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Copy)]
 pub struct Attributes {
     pub might: Attribute,
     pub fitness: Attribute,
@@ -114,6 +140,23 @@ impl Attributes {
             total_initiative_penalty: 0.0,
         }
     }
+    pub fn attribute(&self, attribute: AttributeType) -> Attribute {
+        match attribute {
+            AttributeType::Might => self.might,
+            AttributeType::Fitness => self.fitness,
+            AttributeType::Quickness => self.quickness,
+            AttributeType::Intelligence => self.intelligence,
+        }
+    }
+    pub fn attribute_mut(&mut self, attribute: AttributeType) -> &mut Attribute {
+        match attribute {
+            AttributeType::Might => &mut self.might,
+            AttributeType::Fitness => &mut self.fitness,
+            AttributeType::Quickness => &mut self.quickness,
+            AttributeType::Intelligence => &mut self.intelligence,
+        }
+    }
+
     pub fn player_max_hp(self) -> i32 {
         10 + (10 + self.fitness.bonus()) * self.level
     }
@@ -130,7 +173,7 @@ impl Attributes {
         self.might.total() as f32 * 2.5
     }
 
-    pub fn initiative_bonus(self) -> i32 {
+    pub fn initiative_penalty(self) -> i32 {
         f32::floor(self.total_initiative_penalty) as i32 - self.quickness.bonus()
     }
 }
@@ -149,24 +192,37 @@ impl Default for Attributes {
     }
 }
 
-#[derive(Component, Default)]
+#[derive(Component, Serialize, Deserialize, Clone, Default)]
 pub struct Skills {
     pub melee: i32,
+    pub ranged: i32,
     pub magic: i32,
     pub defense: i32,
 }
 
 impl Skills {
-    pub fn new(melee: i32, magic: i32, defense: i32) -> Self {
+    pub fn new(melee: i32, ranged: i32, magic: i32, defense: i32) -> Self {
         Self {
             melee,
+            ranged,
             magic,
             defense,
         }
     }
 }
 
-#[derive(Component)]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct KnownSpell {
+    pub display_name: String,
+    pub mana_cost: i32,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Default)]
+pub struct KnownSpells {
+    pub spells: Vec<KnownSpell>,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Pool {
     pub max: i32,
     pub current: i32,
@@ -181,7 +237,7 @@ impl Pool {
     }
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Pools {
     pub hit_points: Pool,
     pub mana: Pool,
@@ -212,63 +268,294 @@ impl Pools {
     }
 }
 
-#[derive(Component)]
+/// Points earned on level up that the player has not spent yet.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct LevelUpPoints {
+    pub attribute_points: i32,
+    pub skill_points: i32,
+}
+
+pub const HUNGER_STATE_DURATION: i32 = 200;
+
+#[derive(PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum HungerState {
+    WellFed,
+    Normal,
+    Hungry,
+    Starving,
+}
+
+impl HungerState {
+    pub fn next(self) -> Self {
+        match self {
+            HungerState::WellFed => HungerState::Normal,
+            HungerState::Normal => HungerState::Hungry,
+            HungerState::Hungry | HungerState::Starving => HungerState::Starving,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            HungerState::WellFed => "Well Fed",
+            HungerState::Normal => "Normal",
+            HungerState::Hungry => "Hungry",
+            HungerState::Starving => "Starving",
+        }
+    }
+
+    pub fn initiative_penalty(self) -> i32 {
+        match self {
+            HungerState::WellFed | HungerState::Normal => 0,
+            HungerState::Hungry => 1,
+            HungerState::Starving => 2,
+        }
+    }
+}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct HungerClock {
+    pub state: HungerState,
+    pub duration: i32,
+}
+
+impl HungerClock {
+    pub fn new() -> Self {
+        Self {
+            state: HungerState::WellFed,
+            duration: HUNGER_STATE_DURATION,
+        }
+    }
+
+    /// Advances the clock by one turn, returning the new state when it changes.
+    pub fn tick(&mut self) -> Option<HungerState> {
+        if self.state == HungerState::Starving {
+            return None;
+        }
+        self.duration -= 1;
+        if self.duration > 0 {
+            return None;
+        }
+        self.state = self.state.next();
+        self.duration = HUNGER_STATE_DURATION;
+        Some(self.state)
+    }
+
+    pub fn eat(&mut self) {
+        self.state = HungerState::WellFed;
+        self.duration = HUNGER_STATE_DURATION;
+    }
+}
+
+impl Default for HungerClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct NaturalAttack {
     pub name: String,
     pub damage_n_dice: i32,
     pub damage_die_type: i32,
     pub damage_bonus: i32,
     pub hit_bonus: i32,
+    pub damage_type: DamageType,
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct NaturalProperty {
     pub armor_class: Option<i32>,
     pub attacks: Vec<NaturalAttack>,
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct LootTable {
     pub table: String,
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Initiative {
     pub current: i32,
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct MyTurn;
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Faction {
     pub name: String,
+    pub standing: HashMap<String, Reaction>, // overrides how other factions react to us
+}
+
+impl Faction {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            standing: HashMap::new(),
+        }
+    }
+}
+
+const REPUTATION_LIMIT: i32 = 25;
+const HOSTILE_BELOW: i32 = -10;
+const FRIENDLY_ABOVE: i32 = 10;
+/// What vendors pay, as a percentage of the value, at a neutral standing.
+const SELL_PERCENT: i32 = 50;
+
+/// The player's standing with each faction, built up by what they do and fading
+/// back to zero over time. It only matters once it leaves the neutral band.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Reputation {
+    pub scores: HashMap<String, i32>,
+    pub last_decay: i32,
+    pub dirty: bool,
+}
+
+impl Reputation {
+    pub fn score(&self, faction: &str) -> i32 {
+        self.scores.get(faction).copied().unwrap_or(0)
+    }
+
+    pub fn shift(&mut self, faction: &str, amount: i32) {
+        let score = self.scores.entry(faction.to_string()).or_insert(0);
+        *score = (*score + amount).clamp(-REPUTATION_LIMIT, REPUTATION_LIMIT);
+        self.dirty = true;
+    }
+
+    /// How `faction` ends up reacting to the player, given its usual `base` reaction.
+    pub fn reaction(&self, faction: &str, base: Reaction) -> Reaction {
+        match self.score(faction) {
+            score if score < HOSTILE_BELOW => Reaction::Attack,
+            score if score > FRIENDLY_ABOVE && base == Reaction::Attack => Reaction::Ignore,
+            _ => base,
+        }
+    }
+
+    /// What a vendor of `faction` charges for an item worth `value`, or pays
+    /// for it when the player is `selling`. Buying ranges from 125% down to
+    /// 75% of the value and selling from 30% up to 70%, so even at the best
+    /// standing nothing can be bought and sold back at a profit.
+    pub fn price(&self, faction: &str, value: i32, selling: bool) -> i32 {
+        let score = self.score(faction);
+        let percent = if selling {
+            SELL_PERCENT + score * 4 / 5
+        } else {
+            100 - score
+        };
+        i32::max(1, value * percent / 100)
+    }
+}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct CharacterClass {
+    pub name: String,
 }
 
-#[derive(Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub enum Movement {
     Static,
     Random,
     Waypoint { path: Option<Vec<usize>> },
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct MovementMode {
     pub mode: Movement,
 }
 
-#[derive(Component)]
+pub const CHASE_SEARCH_TURNS: i32 = 5;
+
+/// Where and when a hostile was last seen, so pursuers can follow it out of sight.
+#[derive(Component, ConvertSaveload, Clone)]
 pub struct Chasing {
     pub target: Entity,
+    pub last_seen: Point,
+    pub seen_turn: i32,
+    pub search_turns: i32,
+}
+
+impl Chasing {
+    pub fn new(target: Entity, last_seen: Point, seen_turn: i32) -> Self {
+        Self {
+            target,
+            last_seen,
+            seen_turn,
+            search_turns: CHASE_SEARCH_TURNS,
+        }
+    }
+}
+
+/// The raws behavior profile a mob's utility AI weighs its options with.
+/// `home` is where a guard stays, taken from wherever it first acts.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Behavior {
+    pub profile: String,
+    pub home: Option<Point>,
+    pub called_turn: Option<i32>,
+}
+
+/// How much punishment a mob takes before it runs. `broken` mobs flee from
+/// everything they would otherwise attack.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Morale {
+    pub courage: i32,
+    pub broken: bool,
+}
+
+/// Gathers the nearby mobs named in `followers` into its squad once spawned.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct SquadLeader {
+    pub followers: Vec<String>,
+    pub radius: i32,
+    pub formed: bool,
 }
 
-#[derive(Component)]
+/// Follows `leader`, holding `slot` (an offset from the leader) while idle.
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct SquadMember {
+    pub leader: Entity,
+    pub slot: Point,
+}
+
+/// A follower whose leader died; it runs from its enemies until `until_turn`.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Scattering {
+    pub until_turn: i32,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Vendor {
     pub categories: Vec<String>,
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub enum VendorMode {
     Buy,
     Sell,
+    Craft,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selling_never_pays_more_than_buying_costs() {
+        let mut reputation = Reputation::default();
+        for score in -REPUTATION_LIMIT..=REPUTATION_LIMIT {
+            reputation.scores.insert("Townsfolk".to_string(), score);
+            for value in [1, 2, 7, 10, 33, 100, 250, 1000] {
+                let sell = reputation.price("Townsfolk", value, true);
+                let buy = reputation.price("Townsfolk", value, false);
+                assert!(
+                    sell <= buy,
+                    "score {} value {}: {} > {}",
+                    score,
+                    value,
+                    sell,
+                    buy
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,259 @@
+use bracket_lib::terminal::{letter_to_option, BTerm, VirtualKeyCode};
+
+use super::super::{
+    colors::*,
+    raws::RAWS,
+    rex_assets::RexAssets,
+    rng::RandomGen,
+    spawner::PlayerBuild,
+    state::State,
+    unit::{Attribute, AttributeType, Attributes},
+};
+
+const POINT_BUY_BASE: i32 = 8;
+const POINT_BUY_MAX: i32 = 16;
+const POINT_BUY_POOL: i32 = 12;
+const ROLL_DICE: &str = "3d6";
+const MAX_NAME_LENGTH: usize = 16;
+const CREATION_SALT: i32 = -1; // keeps rolls apart from the per-level seeds
+const ATTRIBUTES: [AttributeType; 4] = [
+    AttributeType::Might,
+    AttributeType::Fitness,
+    AttributeType::Quickness,
+    AttributeType::Intelligence,
+];
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum CreationStep {
+    Name,
+    Class,
+    Attributes,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum AttributeMethod {
+    PointBuy,
+    Rolled,
+}
+
+pub enum CreationResult {
+    NoResponse,
+    Cancel,
+    Done { build: PlayerBuild },
+}
+
+pub struct CharacterCreation {
+    pub step: CreationStep,
+    pub name: String,
+    pub class: usize,
+    pub method: AttributeMethod,
+    pub attributes: [i32; 4],
+    pub selection: usize,
+    rng: RandomGen,
+}
+
+impl CharacterCreation {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            step: CreationStep::Name,
+            name: String::new(),
+            class: 0,
+            method: AttributeMethod::PointBuy,
+            attributes: [POINT_BUY_BASE; 4],
+            selection: 0,
+            rng: RandomGen::from_run_seed(seed, CREATION_SALT),
+        }
+    }
+
+    pub fn points_left(&self) -> i32 {
+        POINT_BUY_POOL
+            - self
+                .attributes
+                .iter()
+                .map(|a| a - POINT_BUY_BASE)
+                .sum::<i32>()
+    }
+
+    pub fn reroll(&mut self) {
+        for attribute in self.attributes.iter_mut() {
+            *attribute = self.rng.roll_str(ROLL_DICE);
+        }
+    }
+
+    fn set_method(&mut self, method: AttributeMethod) {
+        self.method = method;
+        match method {
+            AttributeMethod::PointBuy => self.attributes = [POINT_BUY_BASE; 4],
+            AttributeMethod::Rolled => self.reroll(),
+        }
+    }
+
+    pub fn build(&self) -> PlayerBuild {
+        let raws = RAWS.lock().unwrap();
+        PlayerBuild {
+            name: match self.name.trim() {
+                "" => PlayerBuild::default().name,
+                name => name.to_string(),
+            },
+            class: raws.raws.classes[self.class].name.clone(),
+            attributes: self.attributes,
+        }
+    }
+}
+
+pub fn show_character_creation(gs: &mut State, ctx: &mut BTerm) -> CreationResult {
+    let assets = gs.ecs.fetch::<RexAssets>();
+    ctx.render_xp_sprite(&assets.menu, 0, 0);
+    ctx.draw_box_double(10, 12, 59, 32, c(GRAY5), c(GRAY1));
+    ctx.print_color_centered(14, c(YELLOW1), c(BLACK), "Create your character");
+
+    let creation = &mut gs.creation;
+    match creation.step {
+        CreationStep::Name => name_step(creation, ctx),
+        CreationStep::Class => class_step(creation, ctx),
+        CreationStep::Attributes => attributes_step(creation, ctx),
+    }
+}
+
+fn name_step(creation: &mut CharacterCreation, ctx: &mut BTerm) -> CreationResult {
+    ctx.print_color_centered(18, c(WHITE), c(BLACK), "What is your name?");
+    ctx.print_color_centered(20, c(GREEN5), c(BLACK), format!("{}_", creation.name));
+    ctx.print_color_centered(40, c(GRAY2), c(BLACK), "Type a name, then press Enter");
+
+    match ctx.key {
+        None => {}
+        Some(VirtualKeyCode::Escape) => return CreationResult::Cancel,
+        Some(VirtualKeyCode::Return) => creation.step = CreationStep::Class,
+        Some(VirtualKeyCode::Back) => {
+            creation.name.pop();
+        }
+        Some(VirtualKeyCode::Space) if !creation.name.is_empty() => creation.name.push(' '),
+        Some(key) => {
+            let letter = letter_to_option(key);
+            if letter >= 0 && creation.name.len() < MAX_NAME_LENGTH {
+                let letter = (b'a' + letter as u8) as char;
+                if creation.name.is_empty() || ctx.shift {
+                    creation.name.push(letter.to_ascii_uppercase());
+                } else {
+                    creation.name.push(letter);
+                }
+            }
+        }
+    }
+
+    CreationResult::NoResponse
+}
+
+fn class_step(creation: &mut CharacterCreation, ctx: &mut BTerm) -> CreationResult {
+    let raws = RAWS.lock().unwrap();
+    ctx.print_color_centered(18, c(WHITE), c(BLACK), "Choose your path");
+
+    for (i, class) in raws.raws.classes.iter().enumerate() {
+        let y = 21 + i as i32 * 3;
+        ctx.print_color(
+            14,
+            y,
+            c(YELLOW1),
+            c(BLACK),
+            format!("({})", (b'a' + i as u8) as char),
+        );
+        ctx.print_color(18, y, c(WHITE), c(BLACK), &class.name);
+        ctx.print_color(18, y + 1, c(GRAY6), c(BLACK), &class.description);
+    }
+    ctx.print_color_centered(40, c(GRAY2), c(BLACK), "ESCAPE to go back");
+
+    match ctx.key {
+        None => {}
+        Some(VirtualKeyCode::Escape) => creation.step = CreationStep::Name,
+        Some(key) => {
+            let selection = letter_to_option(key);
+            if selection >= 0 && (selection as usize) < raws.raws.classes.len() {
+                creation.class = selection as usize;
+                creation.step = CreationStep::Attributes;
+            }
+        }
+    }
+
+    CreationResult::NoResponse
+}
+
+fn attributes_step(creation: &mut CharacterCreation, ctx: &mut BTerm) -> CreationResult {
+    let method = match creation.method {
+        AttributeMethod::PointBuy => format!("Point buy ({} points left)", creation.points_left()),
+        AttributeMethod::Rolled => format!("Rolled ({})", ROLL_DICE),
+    };
+    ctx.print_color_centered(18, c(WHITE), c(BLACK), method);
+
+    for (i, (attribute, value)) in ATTRIBUTES.iter().zip(creation.attributes).enumerate() {
+        let y = 21 + i as i32;
+        let color = if i == creation.selection {
+            c(GREEN6)
+        } else {
+            c(WHITE)
+        };
+        if i == creation.selection {
+            ctx.print_color(18, y, color, c(BLACK), ">");
+        }
+        ctx.print_color(20, y, color, c(BLACK), attribute.name());
+        ctx.print_color(36, y, color, c(BLACK), value.to_string());
+    }
+
+    let [might, fitness, quickness, intelligence] = creation.attributes.map(Attribute::new);
+    let attributes = Attributes::new(might, fitness, quickness, intelligence, 1);
+    let derived = [
+        format!("Max HP: {}", attributes.player_max_hp()),
+        format!("Max Mana: {}", attributes.max_mana()),
+        format!("Max Weight: {:.1} kg", attributes.max_weight()),
+        format!("Initiative Penalty: {}", attributes.initiative_penalty()),
+    ];
+    for (i, line) in derived.iter().enumerate() {
+        ctx.print_color(20, 27 + i as i32, c(SHALLOWWATERS1), c(BLACK), line);
+    }
+
+    let help = match creation.method {
+        AttributeMethod::PointBuy => "UP/DOWN select, LEFT/RIGHT spend",
+        AttributeMethod::Rolled => "R to reroll",
+    };
+    ctx.print_color_centered(38, c(GRAY2), c(BLACK), help);
+    ctx.print_color_centered(39, c(GRAY2), c(BLACK), "TAB to switch method");
+    ctx.print_color_centered(40, c(GRAY2), c(BLACK), "ENTER to begin, ESCAPE to go back");
+
+    let selection = creation.selection;
+    match (ctx.key, creation.method) {
+        (None, _) => {}
+        (Some(VirtualKeyCode::Escape), _) => creation.step = CreationStep::Class,
+        (Some(VirtualKeyCode::Return), _) => {
+            return CreationResult::Done {
+                build: creation.build(),
+            }
+        }
+        (Some(VirtualKeyCode::Tab), AttributeMethod::PointBuy) => {
+            creation.set_method(AttributeMethod::Rolled)
+        }
+        (Some(VirtualKeyCode::Tab), AttributeMethod::Rolled) => {
+            creation.set_method(AttributeMethod::PointBuy)
+        }
+        (Some(VirtualKeyCode::R), AttributeMethod::Rolled) => creation.reroll(),
+        (Some(VirtualKeyCode::Up), _) | (Some(VirtualKeyCode::Numpad8), _) => {
+            creation.selection = (selection + ATTRIBUTES.len() - 1) % ATTRIBUTES.len();
+        }
+        (Some(VirtualKeyCode::Down), _) | (Some(VirtualKeyCode::Numpad2), _) => {
+            creation.selection = (selection + 1) % ATTRIBUTES.len();
+        }
+        (Some(VirtualKeyCode::Right), AttributeMethod::PointBuy)
+        | (Some(VirtualKeyCode::Numpad6), AttributeMethod::PointBuy)
+            if creation.points_left() > 0 && creation.attributes[selection] < POINT_BUY_MAX =>
+        {
+            creation.attributes[selection] += 1;
+        }
+        (Some(VirtualKeyCode::Left), AttributeMethod::PointBuy)
+        | (Some(VirtualKeyCode::Numpad4), AttributeMethod::PointBuy)
+            if creation.attributes[selection] > POINT_BUY_BASE =>
+        {
+            creation.attributes[selection] -= 1;
+        }
+        _ => {}
+    }
+
+    CreationResult::NoResponse
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::explicit_counter_loop)]
+
 use bracket_lib::terminal::{letter_to_option, to_cp437, BTerm, VirtualKeyCode};
 use bracket_terminal::FontCharType;
 use specs::prelude::*;
@@ -5,13 +7,15 @@ use specs::prelude::*;
 use super::{
     super::{
         colors::*,
-        item::{Equipped, InBackpack, Item},
-        raws::items::get_vendor_items,
+        item::{item_label, Equipped, Identification, InBackpack, InContainer, Item, Stackable},
+        props::ContainerMode,
+        raws::{items::get_vendor_items, recipes::get_recipes, spells::find_spell_entity},
         state::State,
-        unit::{Vendor, VendorMode},
+        systems::inventory::count_carried,
+        unit::{Faction, KnownSpells, Reputation, Vendor, VendorMode},
         Name,
     },
-    menu::ItemMenuResult,
+    menu::{key_to_digit, ItemMenuResult},
 };
 
 pub enum ItemMenuType {
@@ -19,8 +23,15 @@ pub enum ItemMenuType {
     Drop,
 }
 
+pub enum QuantityResult {
+    NoResponse { quantity: Option<i32> },
+    Cancel,
+    Selected { quantity: Option<i32> },
+}
+
 const MENU_X: usize = 12;
 const MENU_WIDTH: usize = 50;
+const UNIDENTIFIED_PRICE_DIVISOR: i32 = 3;
 
 pub fn draw_menu(ctx: &mut BTerm, count: usize, menu_text: &str, y: usize) {
     ctx.draw_box(
@@ -62,6 +73,8 @@ pub fn show_item_menu(
     let player_entity = gs.ecs.fetch::<Entity>();
     let names = gs.ecs.read_storage::<Name>();
     let backpack = gs.ecs.read_storage::<InBackpack>();
+    let identification = gs.ecs.fetch::<Identification>();
+    let stacks = gs.ecs.read_storage::<Stackable>();
 
     let count = (&backpack, &names)
         .join()
@@ -83,7 +96,8 @@ pub fn show_item_menu(
         .filter(|item| item.1.owner == *player_entity)
         .enumerate()
     {
-        draw_menu_item(ctx, j, y, &name.name);
+        let label = item_label(identification.display_name(&name.name), stacks.get(entity));
+        draw_menu_item(ctx, j, y, &label);
         equippable.push(entity);
         y += 1;
     }
@@ -91,6 +105,59 @@ pub fn show_item_menu(
     match_key(ctx.key, equippable, count)
 }
 
+/// Asks how many to drop off a stack; pressing Enter right away drops all of it.
+pub fn drop_quantity_menu(
+    gs: &mut State,
+    ctx: &mut BTerm,
+    item: Entity,
+    quantity: Option<i32>,
+) -> QuantityResult {
+    let max = gs
+        .ecs
+        .read_storage::<Stackable>()
+        .get(item)
+        .map_or(1, |stack| stack.quantity);
+    let name = gs
+        .ecs
+        .fetch::<Identification>()
+        .display_name(&gs.ecs.read_storage::<Name>().get(item).unwrap().name);
+
+    let y = 24;
+    draw_menu(ctx, 2, &format!("Drop how many {}?", name), y);
+    let text = match quantity {
+        None => format!("all {}", max),
+        Some(quantity) => quantity.to_string(),
+    };
+    ctx.print_color(MENU_X + 3, y, c(GREEN5), c(BLACK), &text);
+    ctx.print_color(
+        MENU_X + 3,
+        y + 1,
+        c(GRAY2),
+        c(BLACK),
+        "Type a number, then press Enter",
+    );
+
+    match ctx.key {
+        None => QuantityResult::NoResponse { quantity },
+        Some(key) => match key {
+            VirtualKeyCode::Escape => QuantityResult::Cancel,
+            VirtualKeyCode::Return => match quantity {
+                Some(0) => QuantityResult::NoResponse { quantity },
+                _ => QuantityResult::Selected { quantity },
+            },
+            VirtualKeyCode::Back => QuantityResult::NoResponse {
+                quantity: quantity.map(|q| q / 10).filter(|q| *q > 0),
+            },
+            _ => match key_to_digit(key) {
+                None => QuantityResult::NoResponse { quantity },
+                Some(digit) => QuantityResult::NoResponse {
+                    quantity: Some(i32::min(max, quantity.unwrap_or(0) * 10 + digit as i32)),
+                },
+            },
+        },
+    }
+}
+
 pub fn remove_item_menu(gs: &mut State, ctx: &mut BTerm) -> (ItemMenuResult, Option<Entity>) {
     let player_entity = gs.ecs.fetch::<Entity>();
     let names = gs.ecs.read_storage::<Name>();
@@ -118,6 +185,35 @@ pub fn remove_item_menu(gs: &mut State, ctx: &mut BTerm) -> (ItemMenuResult, Opt
     match_key(ctx.key, equippable, count)
 }
 
+pub fn show_spell_menu(gs: &mut State, ctx: &mut BTerm) -> (ItemMenuResult, Option<Entity>) {
+    let player_entity = gs.ecs.fetch::<Entity>();
+    let known_spells = gs.ecs.read_storage::<KnownSpells>();
+    let spells = match known_spells.get(*player_entity) {
+        Some(known) => known.spells.clone(),
+        None => Vec::new(),
+    };
+    let count = spells.len();
+
+    let y = 25 - (count / 2);
+    draw_menu(ctx, count, "Cast Which Spell?", y);
+
+    let mut castable = Vec::<Entity>::new();
+    for (j, spell) in spells.iter().enumerate() {
+        draw_menu_item(
+            ctx,
+            j,
+            y + j,
+            &format!("{} ({} mana)", spell.display_name, spell.mana_cost),
+        );
+        if let Some(entity) = find_spell_entity(&gs.ecs, &spell.display_name) {
+            castable.push(entity);
+        }
+    }
+
+    let count = castable.len();
+    match_key(ctx.key, castable, count)
+}
+
 fn match_key(
     key: Option<VirtualKeyCode>,
     equippable: Vec<Entity>,
@@ -142,18 +238,33 @@ pub enum VendorResult {
     Sell,
     BuyMode,
     SellMode,
+    CraftMode,
     Buy,
+    Craft,
+}
+
+/// Prices move with the player's reputation among the vendor's faction.
+fn vendor_price(ecs: &World, vendor: Entity, value: i32, selling: bool) -> i32 {
+    match ecs.read_storage::<Faction>().get(vendor) {
+        Some(faction) => ecs
+            .fetch::<Reputation>()
+            .price(&faction.name, value, selling),
+        None => value,
+    }
 }
 
 fn vendor_sell_menu(
     gs: &mut State,
     ctx: &mut BTerm,
+    vendor: Entity,
 ) -> (VendorResult, Option<Entity>, Option<String>, Option<i32>) {
     let player_entity = gs.ecs.fetch::<Entity>();
     let names = gs.ecs.read_storage::<Name>();
     let backpack = gs.ecs.read_storage::<InBackpack>();
     let items = gs.ecs.read_storage::<Item>();
     let entities = gs.ecs.entities();
+    let identification = gs.ecs.fetch::<Identification>();
+    let stacks = gs.ecs.read_storage::<Stackable>();
 
     let inventory = (&backpack, &names)
         .join()
@@ -168,15 +279,24 @@ fn vendor_sell_menu(
         y,
     );
 
-    let mut equippable = Vec::<Entity>::new();
+    let mut equippable = Vec::<(Entity, i32)>::new();
     for (j, (entity, _, name, item)) in (&entities, &backpack, &names, &items)
         .join()
         .filter(|item| item.1.owner == *player_entity)
         .enumerate()
     {
-        draw_menu_item(ctx, j, y, &name.name);
-        draw_price_item(ctx, item.base_value, y);
-        equippable.push(entity);
+        // Vendors won't pay full price for something nobody can vouch for.
+        let value = if identification.is_known(&name.name) {
+            item.base_value
+        } else {
+            item.base_value / UNIDENTIFIED_PRICE_DIVISOR
+        };
+        let quantity = stacks.get(entity).map_or(1, |stack| stack.quantity);
+        let price = vendor_price(&gs.ecs, vendor, value * quantity, true);
+        let label = item_label(identification.display_name(&name.name), stacks.get(entity));
+        draw_menu_item(ctx, j, y, &label);
+        draw_price_item(ctx, price, y);
+        equippable.push((entity, price));
         y += 1;
     }
 
@@ -188,12 +308,8 @@ fn vendor_sell_menu(
             _ => {
                 let selection = letter_to_option(key);
                 if selection > -1 && selection < count as i32 {
-                    return (
-                        VendorResult::Sell,
-                        Some(equippable[selection as usize]),
-                        None,
-                        None,
-                    );
+                    let (entity, price) = equippable[selection as usize];
+                    return (VendorResult::Sell, Some(entity), None, Some(price));
                 }
                 (VendorResult::NoResponse, None, None, None)
             }
@@ -209,20 +325,24 @@ fn vendor_buy_menu(
     use crate::raws::*;
 
     let vendors = gs.ecs.read_storage::<Vendor>();
+    let categories = &vendors.get(vendor).unwrap().categories;
+    let raws = RAWS.lock().unwrap();
 
-    let inventory = get_vendor_items(
-        &vendors.get(vendor).unwrap().categories,
-        &RAWS.lock().unwrap(),
-    );
+    let inventory: Vec<(String, i32)> = get_vendor_items(categories, &raws)
+        .into_iter()
+        .map(|(name, value)| (name, vendor_price(&gs.ecs, vendor, value, false)))
+        .collect();
     let count = inventory.len();
+    // Vendors running a workshop craft as well.
+    let crafts = !get_recipes(categories, &raws).is_empty();
 
     let mut y = 25 - count / 2;
-    draw_menu(
-        ctx,
-        count,
-        "Buy Which Item? (space to switch to sell mode)",
-        y,
-    );
+    let text = if crafts {
+        "Buy Which Item? (space to switch to craft mode)"
+    } else {
+        "Buy Which Item? (space to switch to sell mode)"
+    };
+    draw_menu(ctx, count, text, y);
 
     for (j, sale) in inventory.iter().enumerate() {
         draw_menu_item(ctx, j, y, &sale.0);
@@ -233,6 +353,7 @@ fn vendor_buy_menu(
     match ctx.key {
         None => (VendorResult::NoResponse, None, None, None),
         Some(key) => match key {
+            VirtualKeyCode::Space if crafts => (VendorResult::CraftMode, None, None, None),
             VirtualKeyCode::Space => (VendorResult::SellMode, None, None, None),
             VirtualKeyCode::Escape => (VendorResult::Cancel, None, None, None),
             _ => {
@@ -259,6 +380,146 @@ pub fn show_vendor_menu(
 ) -> (VendorResult, Option<Entity>, Option<String>, Option<i32>) {
     match mode {
         VendorMode::Buy => vendor_buy_menu(gs, ctx, vendor),
-        VendorMode::Sell => vendor_sell_menu(gs, ctx),
+        VendorMode::Sell => vendor_sell_menu(gs, ctx, vendor),
+        VendorMode::Craft => vendor_craft_menu(gs, ctx, vendor),
+    }
+}
+
+/// Lists what the vendor's workshop can make, along with the ingredients the
+/// player has to bring, in green when they are all in the pack.
+fn vendor_craft_menu(
+    gs: &mut State,
+    ctx: &mut BTerm,
+    vendor: Entity,
+) -> (VendorResult, Option<Entity>, Option<String>, Option<i32>) {
+    use crate::raws::*;
+
+    let player_entity = *gs.ecs.fetch::<Entity>();
+    let vendors = gs.ecs.read_storage::<Vendor>();
+    let raws = RAWS.lock().unwrap();
+    let recipes = get_recipes(&vendors.get(vendor).unwrap().categories, &raws);
+    let count = recipes.len();
+
+    let y = 25 - count / 2;
+    draw_menu(
+        ctx,
+        count,
+        "Craft Which Item? (space to switch to sell mode)",
+        y,
+    );
+
+    for (j, recipe) in recipes.iter().enumerate() {
+        let label = match recipe.quantity {
+            Some(quantity) if quantity > 1 => format!("{} (x{})", recipe.output, quantity),
+            _ => recipe.output.clone(),
+        };
+        draw_menu_item(ctx, j, y + j, &label);
+
+        let ready = recipe.ingredients.iter().all(|ingredient| {
+            count_carried(&gs.ecs, player_entity, &ingredient.name) >= ingredient.quantity
+        });
+        let text = recipe.ingredients_text();
+        let color = if ready { c(GREEN6) } else { c(GREY) };
+        // Right-aligned in the box, but never over the recipe's own label.
+        let text_x = usize::max(
+            (MENU_X + MENU_WIDTH - 1).saturating_sub(text.len()),
+            MENU_X + 7 + label.len(),
+        );
+        ctx.print_color(text_x, y + j, color, c(BLACK), &text);
+    }
+
+    match ctx.key {
+        None => (VendorResult::NoResponse, None, None, None),
+        Some(key) => match key {
+            VirtualKeyCode::Space => (VendorResult::SellMode, None, None, None),
+            VirtualKeyCode::Escape => (VendorResult::Cancel, None, None, None),
+            _ => {
+                let selection = letter_to_option(key);
+                if selection > -1 && selection < count as i32 {
+                    return (
+                        VendorResult::Craft,
+                        None,
+                        Some(recipes[selection as usize].output.clone()),
+                        None,
+                    );
+                }
+                (VendorResult::NoResponse, None, None, None)
+            }
+        },
+    }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum ContainerResult {
+    NoResponse,
+    Cancel,
+    Take,
+    Put,
+    TakeMode,
+    PutMode,
+}
+
+pub fn show_container_menu(
+    gs: &mut State,
+    ctx: &mut BTerm,
+    container: Entity,
+    mode: ContainerMode,
+) -> (ContainerResult, Option<Entity>) {
+    let player_entity = gs.ecs.fetch::<Entity>();
+    let names = gs.ecs.read_storage::<Name>();
+    let backpack = gs.ecs.read_storage::<InBackpack>();
+    let held = gs.ecs.read_storage::<InContainer>();
+    let identification = gs.ecs.fetch::<Identification>();
+    let stacks = gs.ecs.read_storage::<Stackable>();
+
+    let items: Vec<Entity> = match mode {
+        ContainerMode::Take => (&gs.ecs.entities(), &held)
+            .join()
+            .filter(|(_, held)| held.owner == container)
+            .map(|(entity, _)| entity)
+            .collect(),
+        ContainerMode::Put => (&gs.ecs.entities(), &backpack)
+            .join()
+            .filter(|(_, pack)| pack.owner == *player_entity)
+            .map(|(entity, _)| entity)
+            .collect(),
+    };
+    let count = items.len();
+
+    let container_name = names.get(container).map_or("", |n| n.name.as_str());
+    let text = match mode {
+        ContainerMode::Take => format!("Take from the {}? (space to put items)", container_name),
+        ContainerMode::Put => format!("Put in the {}? (space to take items)", container_name),
+    };
+    let y = 25 - (count / 2);
+    draw_menu(ctx, count, &text, y);
+
+    for (j, entity) in items.iter().enumerate() {
+        if let Some(name) = names.get(*entity) {
+            let label = item_label(identification.display_name(&name.name), stacks.get(*entity));
+            draw_menu_item(ctx, j, y + j, &label);
+        }
+    }
+
+    match ctx.key {
+        None => (ContainerResult::NoResponse, None),
+        Some(key) => match key {
+            VirtualKeyCode::Space => match mode {
+                ContainerMode::Take => (ContainerResult::PutMode, None),
+                ContainerMode::Put => (ContainerResult::TakeMode, None),
+            },
+            VirtualKeyCode::Escape => (ContainerResult::Cancel, None),
+            _ => {
+                let selection = letter_to_option(key);
+                if selection > -1 && selection < count as i32 {
+                    let result = match mode {
+                        ContainerMode::Take => ContainerResult::Take,
+                        ContainerMode::Put => ContainerResult::Put,
+                    };
+                    return (result, Some(items[selection as usize]));
+                }
+                (ContainerResult::NoResponse, None)
+            }
+        },
     }
 }
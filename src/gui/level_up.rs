@@ -0,0 +1,199 @@
+use bracket_lib::terminal::{BTerm, VirtualKeyCode};
+use specs::prelude::*;
+
+use super::super::{
+    colors::*,
+    state::State,
+    unit::{AttributeType, Attributes, LevelUpPoints, Skills},
+};
+
+pub const LEVEL_UP_CHOICES: usize = 8;
+const ATTRIBUTE_CHOICES: usize = 4;
+const MENU_X: i32 = 12;
+const MENU_Y: i32 = 12;
+const MENU_WIDTH: i32 = 50;
+const MENU_HEIGHT: i32 = 24;
+
+pub enum LevelUpResult {
+    NoResponse {
+        selection: usize,
+        allocated: [i32; LEVEL_UP_CHOICES],
+    },
+    Confirm {
+        allocated: [i32; LEVEL_UP_CHOICES],
+    },
+}
+
+fn attribute_for_choice(choice: usize) -> AttributeType {
+    match choice {
+        0 => AttributeType::Might,
+        1 => AttributeType::Fitness,
+        2 => AttributeType::Quickness,
+        _ => AttributeType::Intelligence,
+    }
+}
+
+fn skill_for_choice(skills: &mut Skills, choice: usize) -> &mut i32 {
+    match choice {
+        4 => &mut skills.melee,
+        5 => &mut skills.ranged,
+        6 => &mut skills.magic,
+        _ => &mut skills.defense,
+    }
+}
+
+fn choice_name(choice: usize) -> &'static str {
+    match choice {
+        0..=3 => attribute_for_choice(choice).name(),
+        4 => "Melee",
+        5 => "Ranged",
+        6 => "Magic",
+        _ => "Defense",
+    }
+}
+
+/// Applies the allocated points on top of the current attributes and skills.
+pub fn allocate_points(
+    attributes: &mut Attributes,
+    skills: &mut Skills,
+    allocated: &[i32; LEVEL_UP_CHOICES],
+) {
+    for (choice, points) in allocated.iter().enumerate() {
+        if choice < ATTRIBUTE_CHOICES {
+            attributes.attribute_mut(attribute_for_choice(choice)).base += points;
+        } else {
+            *skill_for_choice(skills, choice) += points;
+        }
+    }
+}
+
+pub fn show_level_up(
+    gs: &mut State,
+    ctx: &mut BTerm,
+    selection: usize,
+    mut allocated: [i32; LEVEL_UP_CHOICES],
+) -> LevelUpResult {
+    let player_entity = *gs.ecs.fetch::<Entity>();
+    let points = gs.ecs.read_storage::<LevelUpPoints>();
+    let (attribute_points, skill_points) = match points.get(player_entity) {
+        Some(points) => (points.attribute_points, points.skill_points),
+        None => (0, 0),
+    };
+    let mut attributes = *gs
+        .ecs
+        .read_storage::<Attributes>()
+        .get(player_entity)
+        .unwrap();
+    let mut skills = gs
+        .ecs
+        .read_storage::<Skills>()
+        .get(player_entity)
+        .unwrap()
+        .clone();
+
+    let attributes_left = attribute_points - allocated[..ATTRIBUTE_CHOICES].iter().sum::<i32>();
+    let skills_left = skill_points - allocated[ATTRIBUTE_CHOICES..].iter().sum::<i32>();
+
+    ctx.draw_box(MENU_X, MENU_Y, MENU_WIDTH, MENU_HEIGHT, c(WHITE), c(BLACK));
+    ctx.print_color(
+        MENU_X + 3,
+        MENU_Y,
+        c(YELLOW1),
+        c(BLACK),
+        format!("Level Up! You are now level {}", attributes.level),
+    );
+    ctx.print_color(
+        MENU_X + 3,
+        MENU_Y + 2,
+        c(WHITE),
+        c(BLACK),
+        format!(
+            "Attribute points: {}   Skill points: {}",
+            attributes_left, skills_left
+        ),
+    );
+
+    allocate_points(&mut attributes, &mut skills, &allocated);
+
+    for (choice, points) in allocated.iter().enumerate() {
+        let y = MENU_Y + 4 + choice as i32 + (choice >= ATTRIBUTE_CHOICES) as i32;
+        let value = if choice < ATTRIBUTE_CHOICES {
+            attributes.attribute_mut(attribute_for_choice(choice)).base
+        } else {
+            *skill_for_choice(&mut skills, choice)
+        };
+        let color = if choice == selection {
+            c(GREEN6)
+        } else {
+            c(WHITE)
+        };
+        if choice == selection {
+            ctx.print_color(MENU_X + 2, y, color, c(BLACK), ">");
+        }
+        ctx.print_color(MENU_X + 4, y, color, c(BLACK), choice_name(choice));
+        ctx.print_color(MENU_X + 20, y, color, c(BLACK), value.to_string());
+        if *points > 0 {
+            ctx.print_color(
+                MENU_X + 24,
+                y,
+                c(YELLOW1),
+                c(BLACK),
+                format!("(+{})", points),
+            );
+        }
+    }
+
+    let derived = [
+        format!("Max HP: {}", attributes.player_max_hp()),
+        format!("Max Mana: {}", attributes.max_mana()),
+        format!("Max Weight: {:.1} kg", attributes.max_weight()),
+        format!("Initiative Penalty: {}", attributes.initiative_penalty()),
+    ];
+    for (i, line) in derived.iter().enumerate() {
+        ctx.print_color(
+            MENU_X + 4,
+            MENU_Y + 14 + i as i32,
+            c(SHALLOWWATERS1),
+            c(BLACK),
+            line,
+        );
+    }
+
+    ctx.print_color(
+        MENU_X + 3,
+        MENU_Y + MENU_HEIGHT - 2,
+        c(YELLOW1),
+        c(BLACK),
+        "UP/DOWN select, LEFT/RIGHT spend, ENTER confirm",
+    );
+
+    let remaining = if selection < ATTRIBUTE_CHOICES {
+        attributes_left
+    } else {
+        skills_left
+    };
+    let mut selection = selection;
+    match ctx.key {
+        Some(VirtualKeyCode::Up) | Some(VirtualKeyCode::Numpad8) => {
+            selection = (selection + LEVEL_UP_CHOICES - 1) % LEVEL_UP_CHOICES;
+        }
+        Some(VirtualKeyCode::Down) | Some(VirtualKeyCode::Numpad2) => {
+            selection = (selection + 1) % LEVEL_UP_CHOICES;
+        }
+        Some(VirtualKeyCode::Right) | Some(VirtualKeyCode::Numpad6) if remaining > 0 => {
+            allocated[selection] += 1;
+        }
+        Some(VirtualKeyCode::Left) | Some(VirtualKeyCode::Numpad4) if allocated[selection] > 0 => {
+            allocated[selection] -= 1;
+        }
+        Some(VirtualKeyCode::Return) if attributes_left <= 0 && skills_left <= 0 => {
+            return LevelUpResult::Confirm { allocated };
+        }
+        _ => {}
+    }
+
+    LevelUpResult::NoResponse {
+        selection,
+        allocated,
+    }
+}
@@ -3,6 +3,8 @@ use bracket_lib::terminal::{BTerm, VirtualKeyCode};
 use super::super::{
     colors::*,
     rex_assets::RexAssets,
+    rng::get_seed,
+    saveload::does_save_exist,
     state::{RunState, State},
     First,
 };
@@ -10,7 +12,9 @@ use super::super::{
 #[derive(PartialEq, Copy, Clone)]
 pub enum MainMenuSelection {
     NewGame,
+    LoadGame,
     Continue,
+    SaveAndQuit,
     Quit,
 }
 
@@ -19,6 +23,12 @@ pub enum MainMenuResult {
     Selected { selected: MainMenuSelection },
 }
 
+pub enum SeedEntryResult {
+    NoResponse { seed: Option<u64> },
+    Cancel,
+    Selected { seed: u64 },
+}
+
 pub enum ItemMenuResult {
     Cancel,
     NoResponse,
@@ -36,32 +46,32 @@ pub fn main_menu(gs: &mut State, ctx: &mut BTerm) -> MainMenuResult {
     let first = gs.ecs.fetch::<First>();
     ctx.render_xp_sprite(&assets.menu, 0, 0);
 
-    ctx.draw_box_double(24, 18, 31, 10, c(GRAY5), c(GRAY1));
+    ctx.draw_box_double(24, 18, 31, 12, c(GRAY5), c(GRAY1));
     ctx.print_color_centered(20, c(YELLOW1), c(BLACK), "Rusty Roguelike");
 
-    let mut y = 24;
+    let mut entries = vec![(MainMenuSelection::NewGame, "Begin New Game")];
+    if does_save_exist() {
+        entries.push((MainMenuSelection::LoadGame, "Load Game"));
+    }
+    if !first.run {
+        entries.push((MainMenuSelection::Continue, "Continue"));
+        entries.push((MainMenuSelection::SaveAndQuit, "Save & Quit"));
+    }
+    entries.push((MainMenuSelection::Quit, "Quit"));
+
     if let RunState::MainMenu {
         menu_selection: selection,
     } = *runstate
     {
-        if selection == MainMenuSelection::NewGame {
-            ctx.print_color_centered(y, c(GREEN5), c(BLACK), "> Begin New Game <");
-        } else {
-            ctx.print_color_centered(y, c(GRAY2), c(BLACK), "Begin New Game");
-        }
-        y += 1;
-        if !first.run {
-            if selection == MainMenuSelection::Continue {
-                ctx.print_color_centered(y, c(GREEN5), c(BLACK), "> Continue <");
+        let mut current = 0;
+        for (i, (entry, label)) in entries.iter().enumerate() {
+            let y = 24 + i as i32;
+            if *entry == selection {
+                current = i;
+                ctx.print_color_centered(y, c(GREEN5), c(BLACK), format!("> {} <", label));
             } else {
-                ctx.print_color_centered(y, c(GRAY2), c(BLACK), "Continue");
+                ctx.print_color_centered(y, c(GRAY2), c(BLACK), label);
             }
-            y += 1;
-        }
-        if selection == MainMenuSelection::Quit {
-            ctx.print_color_centered(y, c(GREEN5), c(BLACK), "> Quit <");
-        } else {
-            ctx.print_color_centered(y, c(GRAY2), c(BLACK), "Quit");
         }
 
         match ctx.key {
@@ -78,41 +88,17 @@ pub fn main_menu(gs: &mut State, ctx: &mut BTerm) -> MainMenuResult {
                 }
                 VirtualKeyCode::Up => {
                     return MainMenuResult::NoSelection {
-                        selected: {
-                            let mut new = match selection {
-                                MainMenuSelection::NewGame => MainMenuSelection::Quit,
-                                MainMenuSelection::Continue => MainMenuSelection::NewGame,
-                                MainMenuSelection::Quit => MainMenuSelection::Continue,
-                            };
-
-                            if new == MainMenuSelection::Continue && first.run {
-                                new = MainMenuSelection::NewGame
-                            }
-
-                            new
-                        },
+                        selected: entries[(current + entries.len() - 1) % entries.len()].0,
                     };
                 }
                 VirtualKeyCode::Down => {
                     return MainMenuResult::NoSelection {
-                        selected: {
-                            let mut new = match selection {
-                                MainMenuSelection::NewGame => MainMenuSelection::Continue,
-                                MainMenuSelection::Continue => MainMenuSelection::Quit,
-                                MainMenuSelection::Quit => MainMenuSelection::NewGame,
-                            };
-
-                            if new == MainMenuSelection::Continue && first.run {
-                                new = MainMenuSelection::Quit
-                            }
-
-                            new
-                        },
+                        selected: entries[(current + 1) % entries.len()].0,
                     };
                 }
                 VirtualKeyCode::Return => {
                     return MainMenuResult::Selected {
-                        selected: selection,
+                        selected: entries[current].0,
                     }
                 }
                 _ => {
@@ -129,6 +115,60 @@ pub fn main_menu(gs: &mut State, ctx: &mut BTerm) -> MainMenuResult {
     }
 }
 
+pub fn seed_entry(gs: &mut State, ctx: &mut BTerm, seed: Option<u64>) -> SeedEntryResult {
+    let assets = gs.ecs.fetch::<RexAssets>();
+    ctx.render_xp_sprite(&assets.menu, 0, 0);
+
+    ctx.draw_box_double(24, 18, 31, 10, c(GRAY5), c(GRAY1));
+    ctx.print_color_centered(20, c(YELLOW1), c(BLACK), "Run Seed");
+
+    match seed {
+        None => ctx.print_color_centered(23, c(GRAY2), c(BLACK), "(random)"),
+        Some(seed) => ctx.print_color_centered(23, c(GREEN5), c(BLACK), seed.to_string()),
+    };
+    ctx.print_color_centered(25, c(GRAY2), c(BLACK), "Type a number or leave");
+    ctx.print_color_centered(26, c(GRAY2), c(BLACK), "empty, then press Enter");
+
+    match ctx.key {
+        None => SeedEntryResult::NoResponse { seed },
+        Some(key) => match key {
+            VirtualKeyCode::Escape => SeedEntryResult::Cancel,
+            VirtualKeyCode::Return => SeedEntryResult::Selected {
+                seed: seed.unwrap_or_else(get_seed),
+            },
+            VirtualKeyCode::Back => SeedEntryResult::NoResponse {
+                seed: seed.map(|s| s / 10).filter(|s| *s > 0),
+            },
+            _ => match key_to_digit(key) {
+                None => SeedEntryResult::NoResponse { seed },
+                Some(digit) => SeedEntryResult::NoResponse {
+                    seed: seed
+                        .unwrap_or(0)
+                        .checked_mul(10)
+                        .and_then(|s| s.checked_add(digit))
+                        .or(seed),
+                },
+            },
+        },
+    }
+}
+
+pub fn key_to_digit(key: VirtualKeyCode) -> Option<u64> {
+    match key {
+        VirtualKeyCode::Key0 | VirtualKeyCode::Numpad0 => Some(0),
+        VirtualKeyCode::Key1 | VirtualKeyCode::Numpad1 => Some(1),
+        VirtualKeyCode::Key2 | VirtualKeyCode::Numpad2 => Some(2),
+        VirtualKeyCode::Key3 | VirtualKeyCode::Numpad3 => Some(3),
+        VirtualKeyCode::Key4 | VirtualKeyCode::Numpad4 => Some(4),
+        VirtualKeyCode::Key5 | VirtualKeyCode::Numpad5 => Some(5),
+        VirtualKeyCode::Key6 | VirtualKeyCode::Numpad6 => Some(6),
+        VirtualKeyCode::Key7 | VirtualKeyCode::Numpad7 => Some(7),
+        VirtualKeyCode::Key8 | VirtualKeyCode::Numpad8 => Some(8),
+        VirtualKeyCode::Key9 | VirtualKeyCode::Numpad9 => Some(9),
+        _ => None,
+    }
+}
+
 pub fn game_over(ctx: &mut BTerm) -> GameOverResult {
     ctx.print_color_centered(15, c(YELLOW1), c(BLACK), "Your journey has ended!");
     ctx.print_color_centered(
@@ -1,7 +1,9 @@
 use bracket_lib::terminal::{to_cp437, BTerm, RGB};
 
 pub mod cheat;
+pub mod creation;
 pub mod inventory;
+pub mod level_up;
 pub mod menu;
 pub mod target;
 pub mod tooltips;
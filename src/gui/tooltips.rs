@@ -1,5 +1,9 @@
 use super::super::{
-    camera::get_screen_bounds, colors::*, unit::Attributes, Hidden, Map, Name, Position,
+    camera::get_screen_bounds,
+    colors::*,
+    item::{item_label, Identification, Stackable},
+    unit::Attributes,
+    Hidden, Map, Name, Position,
 };
 use bracket_lib::prelude::Algorithm2D;
 use bracket_lib::terminal::{to_cp437, BTerm, Point};
@@ -56,6 +60,8 @@ pub fn draw_tooltips(ecs: &World, ctx: &mut BTerm) {
     let positions = ecs.read_storage::<Position>();
     let hidden = ecs.read_storage::<Hidden>();
     let attributes = ecs.read_storage::<Attributes>();
+    let identification = ecs.fetch::<Identification>();
+    let stacks = ecs.read_storage::<Stackable>();
     let entities = ecs.entities();
     let (min_x, _, min_y, _) = get_screen_bounds(*player_pos);
 
@@ -72,7 +78,10 @@ pub fn draw_tooltips(ecs: &World, ctx: &mut BTerm) {
     for (entity, source, position, _) in (&entities, &names, &positions, !&hidden).join() {
         if position.x == mouse_pos.x && position.y == mouse_pos.y {
             let mut tip = Tooltip::new();
-            tip.add(&source.name);
+            tip.add(item_label(
+                identification.display_name(&source.name),
+                stacks.get(entity),
+            ));
 
             if let Some(attribute) = attributes.get(entity) {
                 let mut s = "".to_string();
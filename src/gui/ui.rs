@@ -1,11 +1,15 @@
+#![allow(clippy::needless_borrows_for_generic_args)]
+
 use std::cmp::Ordering;
 
 use super::{
     super::{
         colors::*,
-        item::{Consumable, Equipped, InBackpack},
+        effect::StatusEffect,
+        item::{item_label, Consumable, Equipped, Identification, InBackpack, Stackable},
         logger::log_display,
-        unit::{Attribute, Attributes, Pools},
+        raws::{progression::xp_to_next_level, RAWS},
+        unit::{Attribute, Attributes, CharacterClass, HungerClock, HungerState, Pools},
         Entity, Map, Name, CONSOLE_HEIGHT, CONSOLE_WIDTH,
     },
     draw_bar_horizontal, draw_hollow_box,
@@ -106,6 +110,18 @@ pub fn draw_ui(ecs: &World, ctx: &mut BTerm) {
     );
     let xp = format!("Level:  {} ", attribute.level);
 
+    //character name and class
+    let names = ecs.read_storage::<Name>();
+    let mut title = names.get(*player_entity).unwrap().name.clone();
+    if let Some(class) = ecs.read_storage::<CharacterClass>().get(*player_entity) {
+        title = format!("{} the {}", title, class.name);
+    }
+    let title_len = title.len() as i32;
+    let x = V_BAR_Y + ATTR_BOX_WIDTH / 2 - title_len / 2;
+    ctx.set(x, 0, fg, bg, to_cp437('┤'));
+    ctx.set(x + title_len + 1, 0, fg, bg, to_cp437('├'));
+    ctx.print_color(x + 1, 0, c(YELLOW1), bg, &title);
+
     ctx.print_color(V_BAR_Y + 2, 1, c(WHITE), bg, &health);
     ctx.print_color(V_BAR_Y + 2, 2, c(WHITE), bg, &mana);
     ctx.print_color(V_BAR_Y + 2, 3, c(WHITE), bg, &xp);
@@ -137,7 +153,7 @@ pub fn draw_ui(ecs: &World, ctx: &mut BTerm) {
         3,
         14,
         player_pools.xp,
-        attribute.level * 1000,
+        xp_to_next_level(&RAWS.lock().unwrap(), attribute.level),
         c(YELLOW3),
         bg,
     );
@@ -169,6 +185,15 @@ pub fn draw_ui(ecs: &World, ctx: &mut BTerm) {
             attribute.total_initiative_penalty
         ),
     );
+    if let Some(clock) = ecs.read_storage::<HungerClock>().get(*player_entity) {
+        let color = match clock.state {
+            HungerState::WellFed => c(GREEN5),
+            HungerState::Normal => c(WHITE),
+            HungerState::Hungry => c(YELLOW1),
+            HungerState::Starving => c(RED1),
+        };
+        ctx.print_color(V_BAR_Y + 2, ATTR_BOX_HEIGHT + 3, color, bg, clock.state.name());
+    }
     ctx.print_color(
         V_BAR_Y + 2,
         ATTR_BOX_HEIGHT + 4,
@@ -196,15 +221,41 @@ pub fn draw_ui(ecs: &World, ctx: &mut BTerm) {
     y += 1;
     let consumables = ecs.read_storage::<Consumable>();
     let backpack = ecs.read_storage::<InBackpack>();
+    let identification = ecs.fetch::<Identification>();
+    let stacks = ecs.read_storage::<Stackable>();
     let mut index = 1;
-    for (carried_by, _, item_name) in (&backpack, &consumables, &name).join() {
+    for (item, carried_by, _, item_name) in
+        (&ecs.entities(), &backpack, &consumables, &name).join()
+    {
         if carried_by.owner == *player_entity && index < 10 {
             ctx.print_color(V_BAR_Y + 2, y, c(YELLOW1), bg, &format!("↑{}", index));
-            ctx.print_color(V_BAR_Y + 5, y, c(GREEN5), bg, &item_name.name);
+            ctx.print_color(
+                V_BAR_Y + 5,
+                y,
+                c(GREEN5),
+                bg,
+                item_label(identification.display_name(&item_name.name), stacks.get(item)),
+            );
             y += 1;
             index += 1;
         }
     }
+    // Status effects
+    y += 1;
+    let statuses = ecs.read_storage::<StatusEffect>();
+    for status in statuses.join() {
+        if status.target == *player_entity {
+            let color = if status.kind.is_harmful() { c(RED1) } else { c(GREEN5) };
+            ctx.print_color(
+                V_BAR_Y + 2,
+                y,
+                color,
+                bg,
+                format!("{} ({})", status.kind.name(), status.duration),
+            );
+            y += 1;
+        }
+    }
     //Bottom
     let mut block = TextBlock::new(1, H_BAR_2_X + 1, C_WIDTH - 2, LOG_BOX_HEIGHT);
     block.print(&log_display()).unwrap();
@@ -0,0 +1,307 @@
+use super::{
+    arg_value,
+    map::{spatial::SpatialIndex, tiles::Surface, Map},
+    new_state,
+    player_action::key_input,
+    raws::{
+        factions::{reaction_to, Reaction},
+        RAWS,
+    },
+    rng::{get_seed, RandomGen},
+    spawner::PlayerBuild,
+    state::RunState,
+    systems::{damage::delete_the_deads, particle::cull_dead_particles},
+    unit::{Faction, Pools},
+    Position,
+};
+use bracket_lib::{
+    prelude::{a_star_search, Algorithm2D},
+    terminal::{Point, VirtualKeyCode},
+};
+use specs::prelude::*;
+use std::{collections::VecDeque, fmt, fs};
+
+const DEFAULT_MAX_TURNS: u32 = 5000;
+const MAX_IDLE_INPUTS: u32 = 1000;
+
+pub trait InputSource {
+    fn next_key(&mut self, ecs: &World) -> Option<VirtualKeyCode>;
+}
+
+pub struct ScriptedInput {
+    keys: VecDeque<VirtualKeyCode>,
+}
+
+impl ScriptedInput {
+    pub fn from_script(script: &str) -> Result<ScriptedInput, String> {
+        let mut keys = VecDeque::new();
+        for token in script.split_whitespace() {
+            keys.push_back(match token.to_lowercase().as_str() {
+                "n" => VirtualKeyCode::Numpad8,
+                "s" => VirtualKeyCode::Numpad2,
+                "e" => VirtualKeyCode::Numpad6,
+                "w" => VirtualKeyCode::Numpad4,
+                "ne" => VirtualKeyCode::Numpad9,
+                "nw" => VirtualKeyCode::Numpad7,
+                "se" => VirtualKeyCode::Numpad3,
+                "sw" => VirtualKeyCode::Numpad1,
+                "g" | "get" => VirtualKeyCode::G,
+                "x" | "close" => VirtualKeyCode::X,
+                "b" | "bash" => VirtualKeyCode::B,
+                _ => return Err(format!("Unknown script token: {}", token)),
+            });
+        }
+
+        Ok(ScriptedInput { keys })
+    }
+}
+
+impl InputSource for ScriptedInput {
+    fn next_key(&mut self, _ecs: &World) -> Option<VirtualKeyCode> {
+        self.keys.pop_front()
+    }
+}
+
+/// Heads for the down stairs, fighting whatever hostile stands next to it.
+/// Its random moves come from its own generator, so the bot never draws from
+/// the game's stream.
+pub struct StairDiver {
+    last_position: Option<Point>,
+    rng: RandomGen,
+}
+
+impl StairDiver {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            last_position: None,
+            rng: RandomGen::new(seed),
+        }
+    }
+}
+
+impl InputSource for StairDiver {
+    fn next_key(&mut self, ecs: &World) -> Option<VirtualKeyCode> {
+        let map = ecs.fetch::<Map>();
+        let ppos = *ecs.fetch::<Point>();
+        let stuck = self.last_position == Some(ppos);
+        self.last_position = Some(ppos);
+        let factions = ecs.read_storage::<Faction>();
+        let positions = ecs.read_storage::<Position>();
+        let player_faction = factions.get(*ecs.fetch::<Entity>()).unwrap();
+
+        for (faction, pos) in (&factions, &positions).join() {
+            let (dx, dy) = (pos.x - ppos.x, pos.y - ppos.y);
+            if dx.abs() > 1 || dy.abs() > 1 || (dx == 0 && dy == 0) {
+                continue;
+            }
+            if reaction_to(&faction.name, player_faction, &RAWS.lock().unwrap()) == Reaction::Attack
+            {
+                return direction_key(dx, dy);
+            }
+        }
+
+        if let (false, Some(stairs)) = (
+            stuck,
+            map.tiles
+                .iter()
+                .position(|t| t.surface == Surface::DownStairs),
+        ) {
+            let mut terrain = Map::clone(&map);
+            terrain.index = SpatialIndex::default();
+            let path = a_star_search(terrain.point2d_to_index(ppos), stairs, &terrain);
+            if path.success && path.steps.len() > 1 {
+                let next = map.index_to_point2d(path.steps[1]);
+                return direction_key(next.x - ppos.x, next.y - ppos.y);
+            }
+        }
+
+        direction_key(self.rng.range(-1, 2), self.rng.range(-1, 2))
+    }
+}
+
+fn direction_key(dx: i32, dy: i32) -> Option<VirtualKeyCode> {
+    Some(match (dx.signum(), dy.signum()) {
+        (-1, -1) => VirtualKeyCode::Numpad7,
+        (0, -1) => VirtualKeyCode::Numpad8,
+        (1, -1) => VirtualKeyCode::Numpad9,
+        (-1, 0) => VirtualKeyCode::Numpad4,
+        (1, 0) => VirtualKeyCode::Numpad6,
+        (-1, 1) => VirtualKeyCode::Numpad1,
+        (0, 1) => VirtualKeyCode::Numpad2,
+        _ => VirtualKeyCode::Numpad3,
+    })
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Outcome {
+    Died,
+    InputExhausted,
+    Stalled,
+    TurnLimit,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct HeadlessReport {
+    pub seed: u64,
+    pub outcome: Outcome,
+    pub depth: i32,
+    pub max_depth: i32,
+    pub turns: u32,
+    pub position: Point,
+    pub hit_points: i32,
+    pub xp: i32,
+}
+
+impl fmt::Display for HeadlessReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "seed: {} outcome: {:?} depth: {} max depth: {} turns: {} position: {},{} hp: {} xp: {}",
+            self.seed,
+            self.outcome,
+            self.depth,
+            self.max_depth,
+            self.turns,
+            self.position.x,
+            self.position.y,
+            self.hit_points,
+            self.xp
+        )
+    }
+}
+
+pub fn simulate(seed: u64, input: &mut dyn InputSource, max_turns: u32) -> HeadlessReport {
+    let mut gs = new_state(Some(seed));
+    gs.start_new_game(seed, &PlayerBuild::default());
+    *gs.ecs.write_resource::<RunState>() = RunState::PreRun;
+
+    let mut turns = 0;
+    let mut idle_inputs = 0;
+    let mut max_depth = 0;
+
+    let outcome = loop {
+        let runstate = *gs.ecs.fetch::<RunState>();
+        let newrunstate = match runstate {
+            RunState::PreRun => {
+                gs.run_systems();
+                RunState::AwaitingInput
+            }
+            RunState::AwaitingInput => {
+                if turns >= max_turns {
+                    break Outcome::TurnLimit;
+                }
+                if idle_inputs >= MAX_IDLE_INPUTS {
+                    break Outcome::Stalled;
+                }
+                match input.next_key(&gs.ecs) {
+                    None => break Outcome::InputExhausted,
+                    Some(key) => match key_input(&mut gs, Some(key), false) {
+                        RunState::Ticking => {
+                            turns += 1;
+                            idle_inputs = 0;
+                            RunState::Ticking
+                        }
+                        RunState::NextLevel => RunState::NextLevel,
+                        RunState::PreviousLevel => RunState::PreviousLevel,
+                        _ => {
+                            idle_inputs += 1;
+                            RunState::AwaitingInput
+                        }
+                    },
+                }
+            }
+            RunState::Ticking => {
+                gs.run_systems();
+                match *gs.ecs.fetch::<RunState>() {
+                    RunState::AwaitingInput => RunState::AwaitingInput,
+                    _ => RunState::Ticking,
+                }
+            }
+            RunState::NextLevel => {
+                gs.goto_level(1);
+                RunState::PreRun
+            }
+            RunState::PreviousLevel => {
+                gs.goto_level(-1);
+                RunState::PreRun
+            }
+            RunState::MapGeneration => gs.gen.next_state.unwrap_or(RunState::PreRun),
+            RunState::GameOver => break Outcome::Died,
+            _ => RunState::AwaitingInput,
+        };
+
+        *gs.ecs.write_resource::<RunState>() = newrunstate;
+        delete_the_deads(&mut gs.ecs);
+        cull_dead_particles(&mut gs.ecs, f32::MAX);
+        max_depth = i32::max(max_depth, gs.ecs.fetch::<Map>().depth);
+    };
+
+    let depth = gs.ecs.fetch::<Map>().depth;
+    let position = *gs.ecs.fetch::<Point>();
+    let (hit_points, xp) = gs
+        .ecs
+        .read_storage::<Pools>()
+        .get(*gs.ecs.fetch::<Entity>())
+        .map_or((0, 0), |pools| (pools.hit_points.current, pools.xp));
+    HeadlessReport {
+        seed,
+        outcome,
+        depth,
+        max_depth,
+        turns,
+        position,
+        hit_points,
+        xp,
+    }
+}
+
+pub fn run_from_args(seed: Option<u64>) {
+    let seed = seed.unwrap_or_else(get_seed);
+    let max_turns = arg_value("--turns")
+        .and_then(|turns| turns.parse::<u32>().ok())
+        .unwrap_or(DEFAULT_MAX_TURNS);
+
+    let report = match arg_value("--script") {
+        Some(path) => {
+            let script = fs::read_to_string(&path).expect("Unable to read script file");
+            let mut input = ScriptedInput::from_script(&script).expect("Unable to parse script");
+            simulate(seed, &mut input, max_turns)
+        }
+        None => simulate(seed, &mut StairDiver::new(seed), max_turns),
+    };
+
+    println!("{}", report);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_TURNS: u32 = 100;
+
+    #[test]
+    fn stair_diver_runs_repeat_for_a_seed() {
+        for seed in [1, 2, 3, 7, 42] {
+            let first = simulate(seed, &mut StairDiver::new(seed), TEST_TURNS);
+            let second = simulate(seed, &mut StairDiver::new(seed), TEST_TURNS);
+            assert_eq!(first, second, "seed {} diverged", seed);
+        }
+    }
+
+    #[test]
+    fn scripted_runs_repeat_for_a_seed() {
+        let script = "e e e e s s s s w w w w n n n n ne ne sw sw g x b";
+        let run = || {
+            let mut input = ScriptedInput::from_script(script).unwrap();
+            simulate(3, &mut input, TEST_TURNS)
+        };
+        let first = run();
+        assert_eq!(first.outcome, Outcome::InputExhausted);
+        assert_eq!(first, run());
+    }
+
+    #[test]
+    fn scripts_reject_unknown_tokens() {
+        assert!(ScriptedInput::from_script("n e jump").is_err());
+    }
+}
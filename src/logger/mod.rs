@@ -1,9 +1,11 @@
 use bracket_lib::terminal::{TextBuilder, RGB};
 use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
 
 pub mod builder;
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct LogFragment {
     pub color: RGB,
     pub text: String,
@@ -34,3 +36,12 @@ pub fn log_display() -> TextBuilder {
 
     buffer
 }
+
+pub fn clone_log() -> Vec<Vec<LogFragment>> {
+    LOG.lock().unwrap().clone()
+}
+
+pub fn restore_log(log: &mut Vec<Vec<LogFragment>>) {
+    LOG.lock().unwrap().clear();
+    LOG.lock().unwrap().append(log);
+}
@@ -1,10 +1,14 @@
 use bracket_lib::prelude::*;
-use specs::prelude::*;
+use specs::{
+    prelude::*,
+    saveload::{SimpleMarker, SimpleMarkerAllocator},
+};
 
 pub mod camera;
 pub mod colors;
 pub mod components;
 pub mod gui;
+pub mod headless;
 pub mod logger;
 pub mod map;
 pub mod map_builders;
@@ -13,12 +17,13 @@ pub mod player_action;
 pub mod raws;
 pub mod rex_assets;
 pub mod rng;
+pub mod saveload;
 pub mod spawner;
 pub mod state;
 pub mod systems;
 
 use components::*;
-use gui::menu::MainMenuSelection;
+use gui::{creation::CharacterCreation, menu::MainMenuSelection};
 use logger::builder::Log;
 use map::Map;
 use state::{new_dispatcher, MapGen, RunState, State};
@@ -38,6 +43,13 @@ pub struct First {
 }
 
 fn main() {
+    let seed = arg_value("--seed").and_then(|seed| seed.parse::<u64>().ok());
+
+    if std::env::args().any(|arg| arg == "--headless") {
+        headless::run_from_args(seed);
+        return;
+    }
+
     let ctx = BTermBuilder::new()
         .with_tile_dimensions(FONT_SIZE, FONT_SIZE)
         .with_dimensions(CONSOLE_WIDTH, CONSOLE_HEIGHT)
@@ -49,6 +61,14 @@ fn main() {
         .build()
         .expect("Context not build.");
 
+    main_loop(ctx, new_state(seed)).expect("Main loop issue");
+}
+
+pub fn arg_value(name: &str) -> Option<String> {
+    std::env::args().skip_while(|arg| arg != name).nth(1)
+}
+
+pub fn new_state(seed: Option<u64>) -> State {
     let mut gs = State {
         ecs: World::new(),
         gen: MapGen {
@@ -57,7 +77,9 @@ fn main() {
             history: Vec::new(),
             next_state: Some(RunState::PreRun),
         },
+        creation: CharacterCreation::new(seed.unwrap_or_default()),
         dispatcher: new_dispatcher(),
+        seed,
     };
 
     gs.ecs.register::<Renderable>();
@@ -76,6 +98,9 @@ fn main() {
     gs.ecs.register::<unit::Attribute>();
     gs.ecs.register::<unit::Attributes>();
     gs.ecs.register::<unit::Skills>();
+    gs.ecs.register::<unit::KnownSpells>();
+    gs.ecs.register::<unit::HungerClock>();
+    gs.ecs.register::<unit::LevelUpPoints>();
     gs.ecs.register::<unit::Pool>();
     gs.ecs.register::<unit::Pools>();
     gs.ecs.register::<unit::NaturalAttack>();
@@ -84,41 +109,85 @@ fn main() {
     gs.ecs.register::<unit::Initiative>();
     gs.ecs.register::<unit::MyTurn>();
     gs.ecs.register::<unit::Faction>();
+    gs.ecs.register::<unit::CharacterClass>();
     gs.ecs.register::<unit::MovementMode>();
     gs.ecs.register::<unit::Chasing>();
+    gs.ecs.register::<unit::Morale>();
+    gs.ecs.register::<unit::Behavior>();
+    gs.ecs.register::<unit::SquadLeader>();
+    gs.ecs.register::<unit::SquadMember>();
+    gs.ecs.register::<unit::Scattering>();
     gs.ecs.register::<unit::Vendor>();
     gs.ecs.register::<action::WantsToMelee>();
+    gs.ecs.register::<action::WantsToShoot>();
     gs.ecs.register::<action::WantsToPickupItem>();
     gs.ecs.register::<action::WantsToUseItem>();
+    gs.ecs.register::<action::WantsToCastSpell>();
     gs.ecs.register::<action::WantsToDropItem>();
     gs.ecs.register::<action::WantsToRemoveItem>();
     gs.ecs.register::<action::WantsToApproach>();
     gs.ecs.register::<action::WantsToFlee>();
+    gs.ecs.register::<action::WantsToOpenDoor>();
+    gs.ecs.register::<action::WantsToCloseDoor>();
+    gs.ecs.register::<action::WantsToBashDoor>();
+    gs.ecs.register::<action::WantsToOpenContainer>();
     gs.ecs.register::<item::Item>();
     gs.ecs.register::<item::InBackpack>();
+    gs.ecs.register::<item::InContainer>();
     gs.ecs.register::<item::Consumable>();
     gs.ecs.register::<item::Equippable>();
     gs.ecs.register::<item::Equipped>();
     gs.ecs.register::<item::MeleeWeapon>();
+    gs.ecs.register::<item::RangedWeapon>();
+    gs.ecs.register::<item::Ammunition>();
     gs.ecs.register::<item::Wearable>();
+    gs.ecs.register::<item::Shield>();
+    gs.ecs.register::<item::AttributeBonus>();
+    gs.ecs.register::<item::DamageBonus>();
+    gs.ecs.register::<item::Cursed>();
+    gs.ecs.register::<item::Stackable>();
     gs.ecs.register::<item::EquipmentChanged>();
     gs.ecs.register::<effect::ProvidesHealing>();
+    gs.ecs.register::<effect::ProvidesFood>();
+    gs.ecs.register::<effect::Identifies>();
+    gs.ecs.register::<effect::RemovesCurse>();
+    gs.ecs.register::<effect::SpellTemplate>();
     gs.ecs.register::<effect::Ranged>();
     gs.ecs.register::<effect::EntryTrigger>();
     gs.ecs.register::<effect::InflictsDamage>();
     gs.ecs.register::<effect::AreaOfEffect>();
-    gs.ecs.register::<effect::Confusion>();
+    gs.ecs.register::<effect::GrantsStatus>();
+    gs.ecs.register::<effect::StatusEffect>();
+    gs.ecs.register::<effect::Resistances>();
     gs.ecs.register::<props::SingleActivation>();
     gs.ecs.register::<props::Door>();
+    gs.ecs.register::<props::OpensDoors>();
     gs.ecs.register::<props::LightSource>();
+    gs.ecs.register::<props::Container>();
+    gs.ecs.register::<props::Trapped>();
+    gs.ecs.register::<SimpleMarker<SerializeMe>>();
+    gs.ecs.register::<SerializationHelper>();
+
+    gs.ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
 
     raws::load_raws();
 
-    let player_entity = spawner::build_player_entity(&mut gs.ecs, 0, 0);
+    let seed = gs.seed.unwrap_or_else(rng::get_seed);
+    gs.ecs.insert(rng::RandomGen::new(seed));
+    gs.ecs.insert(TurnCounter::default());
+    gs.ecs.insert(unit::Reputation::default());
+    gs.ecs.insert(raws::items::disguise_items(
+        &raws::RAWS.lock().unwrap(),
+        seed,
+    ));
+    gs.ecs.insert(pathfinding::flow_map::FlowMaps::new());
+
+    let player_entity =
+        spawner::build_player_entity(&mut gs.ecs, 0, 0, &spawner::PlayerBuild::default());
 
     gs.ecs.insert(rex_assets::RexAssets::new());
     gs.ecs.insert(systems::particle::ParticleBuilder::new());
-    gs.ecs.insert(map::master::MasterMap::new());
+    gs.ecs.insert(map::master::MasterMap::new(seed));
     gs.ecs.insert(Map::new(1, 0, 0, ""));
     gs.ecs.insert(Point::new(0, 0));
     gs.ecs.insert(player_entity);
@@ -134,5 +203,5 @@ fn main() {
         .append("to Rusty Roguelike")
         .build();
 
-    main_loop(ctx, gs).expect("Main loop issue");
+    gs
 }
@@ -1,23 +1,30 @@
 use super::{
-    super::{map_builders::level_builder, unit::Viewshed},
+    super::{map_builders::level_builder, rng::RandomGen, unit::Viewshed},
     Map, OtherLevelPosition, Position, Surface,
 };
 use bracket_lib::{prelude::Algorithm2D, terminal::Point};
+use serde::{Deserialize, Serialize};
 use specs::prelude::*;
 use std::collections::HashMap;
 
-#[derive(Default)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct MasterMap {
     maps: HashMap<i32, Map>,
+    pub seed: u64,
 }
 
 impl MasterMap {
-    pub fn new() -> Self {
+    pub fn new(seed: u64) -> Self {
         Self {
             maps: HashMap::new(),
+            seed,
         }
     }
 
+    pub fn level_seed(&self, depth: i32) -> u64 {
+        RandomGen::from_run_seed(self.seed, depth).next_u64()
+    }
+
     pub fn store_map(&mut self, map: &Map) {
         self.maps.insert(map.depth, map.clone());
     }
@@ -94,7 +101,8 @@ pub fn level_transition(ecs: &mut World, depth: i32, offset: i32) -> Option<Vec<
 }
 
 pub fn transition_to_new_map(ecs: &mut World, depth: i32) -> Vec<Map> {
-    let mut builder = level_builder(depth, 40, 40);
+    let seed = ecs.read_resource::<MasterMap>().level_seed(depth);
+    let mut builder = level_builder(depth, seed);
     builder.build_map();
 
     if depth > 0 {
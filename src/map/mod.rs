@@ -1,7 +1,10 @@
 pub mod master;
+pub mod spatial;
 pub mod themes;
 pub mod tiles;
 
+use spatial::SpatialIndex;
+use themes::MapTheme;
 use tiles::{is_tile_opaque, is_tile_walkable, Surface};
 
 use super::{
@@ -14,9 +17,10 @@ use bracket_lib::{
     terminal::{DistanceAlg, Point, Rect, RGB},
 };
 
+use serde::{Deserialize, Serialize};
 use specs::prelude::*;
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Map {
     pub width: i32,
     pub height: i32,
@@ -25,9 +29,12 @@ pub struct Map {
     pub rooms: Vec<Rect>,
     pub name: String,
     pub outdoors: bool,
+    pub theme: MapTheme,
+    #[serde(skip)]
+    pub index: SpatialIndex,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Tile {
     pub surface: Surface,
     pub revealed: bool,
@@ -35,8 +42,6 @@ pub struct Tile {
     pub block_visibility: bool,
     pub bloodstains: bool,
     pub light: RGB,
-    pub block_movement: bool,
-    pub content: Vec<Entity>,
 }
 
 impl Tile {
@@ -48,8 +53,6 @@ impl Tile {
             block_visibility: false,
             bloodstains: false,
             light: c(BLACK),
-            block_movement: true,
-            content: Vec::new(),
         }
     }
 }
@@ -70,6 +73,8 @@ impl Map {
             rooms: Vec::new(),
             name: name.to_string(),
             outdoors: true,
+            theme: MapTheme::Default,
+            index: SpatialIndex::default(),
             tiles: vec![Tile::default(); tiles_count],
         }
     }
@@ -77,33 +82,48 @@ impl Map {
         (y * self.width + x) as usize
     }
 
-    fn is_exit_valid(&self, idx: usize) -> bool {
-        self.in_bounds(self.index_to_point2d(idx)) && !self.tiles[idx].block_movement
+    pub fn is_blocked(&self, idx: usize) -> bool {
+        !self.is_passable(idx, false)
     }
 
-    pub fn populate_blocked(&mut self) {
-        for tile in self.tiles.iter_mut() {
-            tile.block_movement = !is_tile_walkable(tile.surface);
-        }
+    /// Whether a walker can step onto `idx`; closed doors only stop those
+    /// that cannot open them.
+    pub fn is_passable(&self, idx: usize, opens_doors: bool) -> bool {
+        is_tile_walkable(self.tiles[idx].surface) && !self.index.blocks_walker(idx, opens_doors)
     }
 
-    pub fn blocks_movement(&mut self, idx: usize, blocks_movement: bool) {
-        self.tiles[idx].block_movement = blocks_movement;
-    }
+    fn exits(&self, idx: usize, opens_doors: bool) -> SmallVec<[(usize, f32); 10]> {
+        let mut exits = SmallVec::new();
+        let w = self.width as usize;
 
-    pub fn is_blocked(&self, idx: usize) -> bool {
-        self.tiles[idx].block_movement
-    }
+        if !self.in_bounds(self.index_to_point2d(idx)) {
+            return exits;
+        }
+
+        let valid =
+            |t: usize| self.in_bounds(self.index_to_point2d(t)) && self.is_passable(t, opens_doors);
 
-    pub fn move_entity(&mut self, entity: Entity, moving_from: usize, moving_to: usize) {
-        for (i, e) in self.tiles[moving_from].content.clone().iter().enumerate() {
-            if *e == entity {
-                self.tiles[moving_from].content.remove(i);
-                self.tiles[moving_from].block_movement = false;
+        let mut to_try = [idx - 1, idx + 1, idx - w, idx + w];
+
+        for t in to_try.iter() {
+            if valid(*t) {
+                exits.push((*t, 1.0));
             }
         }
-        self.tiles[moving_to].content.push(entity);
-        self.tiles[moving_to].block_movement = true;
+
+        to_try = [idx - 1 - w, idx + 1 - w, idx - w + 1, idx + w - 1];
+
+        for t in to_try.iter() {
+            if valid(*t) {
+                exits.push((*t, 2.0));
+            }
+        }
+
+        exits
+    }
+
+    pub fn move_entity(&mut self, entity: Entity, moving_to: usize) {
+        self.index.move_entity(entity, moving_to);
     }
 }
 
@@ -128,29 +148,33 @@ impl BaseMap for Map {
     }
 
     fn get_available_exits(&self, idx: usize) -> SmallVec<[(usize, f32); 10]> {
-        let mut exits = SmallVec::new();
-        let w = self.width as usize;
-
-        if !self.in_bounds(self.index_to_point2d(idx)) {
-            return exits;
-        }
+        self.exits(idx, false)
+    }
+}
 
-        let mut to_try = [idx - 1, idx + 1, idx - w, idx + w];
+/// The map as a mob finds its way around it, with closed doors counted as a
+/// way through for those that can open them.
+pub struct Walker<'a> {
+    pub map: &'a Map,
+    pub opens_doors: bool,
+}
 
-        for t in to_try.iter() {
-            if self.is_exit_valid(*t) {
-                exits.push((*t, 1.0));
-            }
-        }
+impl<'a> Walker<'a> {
+    pub fn new(map: &'a Map, opens_doors: bool) -> Self {
+        Self { map, opens_doors }
+    }
+}
 
-        to_try = [idx - 1 - w, idx + 1 - w, idx - w + 1, idx + w - 1];
+impl BaseMap for Walker<'_> {
+    fn get_pathing_distance(&self, idx1: usize, idx2: usize) -> f32 {
+        self.map.get_pathing_distance(idx1, idx2)
+    }
 
-        for t in to_try.iter() {
-            if self.is_exit_valid(*t) {
-                exits.push((*t, 2.0));
-            }
-        }
+    fn is_opaque(&self, idx: usize) -> bool {
+        self.map.is_opaque(idx)
+    }
 
-        exits
+    fn get_available_exits(&self, idx: usize) -> SmallVec<[(usize, f32); 10]> {
+        self.map.exits(idx, self.opens_doors)
     }
 }
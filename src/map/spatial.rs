@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use bracket_lib::terminal::{DistanceAlg, Point};
+use specs::prelude::*;
+
+#[derive(Clone, Copy)]
+struct Occupant {
+    entity: Entity,
+    blocks: bool,
+    /// A closed door that opens for anyone who can work a handle.
+    door: bool,
+}
+
+/// Entities on the current map, by tile. Kept up to date by the
+/// `MapIndexingSystem` as positions and blockers change, rather than being
+/// rebuilt every tick.
+#[derive(Clone, Default)]
+pub struct SpatialIndex {
+    width: i32,
+    height: i32,
+    tiles: Vec<Vec<Occupant>>,
+    locations: HashMap<u32, usize>,
+    built: bool,
+}
+
+impl SpatialIndex {
+    pub fn is_built(&self) -> bool {
+        self.built
+    }
+
+    /// Empties the index and sizes it for a `width` x `height` map.
+    pub fn reset(&mut self, width: i32, height: i32) {
+        self.width = width;
+        self.height = height;
+        self.tiles = vec![Vec::new(); (width * height).max(0) as usize];
+        self.locations.clear();
+        self.built = true;
+    }
+
+    /// Places `entity` on `idx`, moving it if it is already indexed elsewhere.
+    pub fn insert(&mut self, entity: Entity, idx: usize, blocks: bool, door: bool) {
+        self.remove(entity.id());
+        if idx >= self.tiles.len() {
+            return;
+        }
+        self.tiles[idx].push(Occupant {
+            entity,
+            blocks,
+            door,
+        });
+        self.locations.insert(entity.id(), idx);
+    }
+
+    /// Moves an indexed entity to `idx`, keeping its blocking state.
+    pub fn move_entity(&mut self, entity: Entity, idx: usize) {
+        let occupant = self
+            .locations
+            .get(&entity.id())
+            .and_then(|from| self.tiles[*from].iter().find(|o| o.entity == entity))
+            .copied();
+        let (blocks, door) = occupant.map_or((false, false), |o| (o.blocks, o.door));
+        self.insert(entity, idx, blocks, door);
+    }
+
+    pub fn remove(&mut self, id: u32) {
+        if let Some(idx) = self.locations.remove(&id) {
+            self.tiles[idx].retain(|o| o.entity.id() != id);
+        }
+    }
+
+    /// Updates a door in place as it opens, closes or unlocks.
+    pub fn set_door(&mut self, entity: Entity, blocks: bool, door: bool) {
+        if let Some(idx) = self.locations.get(&entity.id()) {
+            for occupant in self.tiles[*idx].iter_mut() {
+                if occupant.entity == entity {
+                    occupant.blocks = blocks;
+                    occupant.door = door;
+                }
+            }
+        }
+    }
+
+    pub fn entities_at(&self, idx: usize) -> impl Iterator<Item = Entity> + '_ {
+        self.tiles
+            .get(idx)
+            .into_iter()
+            .flat_map(|content| content.iter().map(|o| o.entity))
+    }
+
+    pub fn first_blocker(&self, idx: usize) -> Option<Entity> {
+        self.tiles
+            .get(idx)?
+            .iter()
+            .find(|o| o.blocks)
+            .map(|o| o.entity)
+    }
+
+    /// The closed, unlocked door standing on `idx`, if any. Locked doors are
+    /// never returned, even to a mob carrying their key: NPCs don't plan
+    /// routes through locks, and only the player works them.
+    pub fn closed_door(&self, idx: usize) -> Option<Entity> {
+        self.tiles
+            .get(idx)?
+            .iter()
+            .find(|o| o.door)
+            .map(|o| o.entity)
+    }
+
+    /// Whether something on `idx` stops a walker, counting closed doors as
+    /// open for those who can open them.
+    pub fn blocks_walker(&self, idx: usize, opens_doors: bool) -> bool {
+        self.tiles
+            .get(idx)
+            .is_some_and(|content| content.iter().any(|o| o.blocks && !(opens_doors && o.door)))
+    }
+
+    pub fn entities_in_radius(&self, center: Point, radius: f32) -> Vec<Entity> {
+        let reach = radius.ceil() as i32;
+        let mut found = Vec::new();
+        for y in (center.y - reach).max(0)..=(center.y + reach).min(self.height - 1) {
+            for x in (center.x - reach).max(0)..=(center.x + reach).min(self.width - 1) {
+                let pt = Point::new(x, y);
+                if DistanceAlg::Pythagoras.distance2d(center, pt) <= radius {
+                    found.extend(self.entities_at((y * self.width + x) as usize));
+                }
+            }
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOOR_IDX: usize = 12;
+
+    fn door_index(unlocked: bool) -> (SpatialIndex, Entity) {
+        let door = World::new().create_entity().build();
+        let mut index = SpatialIndex::default();
+        index.reset(5, 5);
+        index.insert(door, DOOR_IDX, true, unlocked);
+        (index, door)
+    }
+
+    #[test]
+    fn unlocked_doors_open_for_door_openers() {
+        let (index, door) = door_index(true);
+        assert_eq!(index.closed_door(DOOR_IDX), Some(door));
+        assert!(!index.blocks_walker(DOOR_IDX, true));
+        assert!(index.blocks_walker(DOOR_IDX, false));
+    }
+
+    #[test]
+    fn doors_update_in_place_as_they_open_and_close() {
+        let (mut index, door) = door_index(true);
+
+        index.set_door(door, false, false);
+        assert_eq!(index.closed_door(DOOR_IDX), None);
+        assert!(!index.blocks_walker(DOOR_IDX, false));
+        assert_eq!(index.first_blocker(DOOR_IDX), None);
+
+        index.set_door(door, true, true);
+        assert_eq!(index.closed_door(DOOR_IDX), Some(door));
+        assert_eq!(index.first_blocker(DOOR_IDX), Some(door));
+        assert_eq!(index.entities_at(DOOR_IDX).count(), 1);
+    }
+
+    #[test]
+    fn moved_entities_keep_their_state() {
+        let (mut index, door) = door_index(true);
+
+        index.move_entity(door, DOOR_IDX + 1);
+        assert_eq!(index.entities_at(DOOR_IDX).count(), 0);
+        assert_eq!(index.closed_door(DOOR_IDX + 1), Some(door));
+
+        index.remove(door.id());
+        assert_eq!(index.entities_at(DOOR_IDX + 1).count(), 0);
+    }
+
+    #[test]
+    fn locked_doors_keep_npcs_out() {
+        let (index, _) = door_index(false);
+        assert_eq!(index.closed_door(DOOR_IDX), None);
+        assert!(index.blocks_walker(DOOR_IDX, true));
+    }
+}
@@ -1,20 +1,39 @@
 use bracket_lib::terminal::{to_cp437, RGB};
 use bracket_terminal::FontCharType;
+use serde::{Deserialize, Serialize};
 
 use super::{super::colors::*, tiles::Surface, Map};
 
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
+pub enum MapTheme {
+    Default,
+    Forest,
+    Cavern,
+    CavernTransition,
+}
+
+pub fn parse_theme(name: &str) -> Option<MapTheme> {
+    match name {
+        "default" => Some(MapTheme::Default),
+        "forest" => Some(MapTheme::Forest),
+        "cavern" => Some(MapTheme::Cavern),
+        "cavern_transition" => Some(MapTheme::CavernTransition),
+        _ => None,
+    }
+}
+
 pub fn get_tile_glyph(idx: usize, map: &Map) -> (FontCharType, RGB, RGB) {
-    let (glyph, mut fg, theme_bg) = match map.depth {
-        4 => {
+    let (glyph, mut fg, theme_bg) = match map.theme {
+        MapTheme::CavernTransition => {
             if idx as i32 % map.width < map.width /2 {
                 get_cavern_glyph(idx, map)
             }else {
                 get_default_glyph(idx, map)
             }
         }
-        2|3 => get_cavern_glyph(idx, map),
-        1 => get_forest_glyph(idx, map),
-        _ => get_default_glyph(idx, map),
+        MapTheme::Cavern => get_cavern_glyph(idx, map),
+        MapTheme::Forest => get_forest_glyph(idx, map),
+        MapTheme::Default => get_default_glyph(idx, map),
     };
 
     let mut bg = if map.tiles[idx].bloodstains && map.tiles[idx].visible {
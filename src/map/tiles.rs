@@ -1,4 +1,6 @@
-#[derive(Clone, Copy, PartialEq, Default)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
 pub enum Surface {
     #[default]
     Wall,
@@ -1,20 +1,4 @@
-use super::{
-    AreaStartingPosition, BuilderChain, CullUnreachable, DistantExit, DrunkardsWalkBuilder, Map,
-    RandomGen, Surface, VoronoiSpawner, X, Y,
-};
-
-pub fn cavern_builder(depth: i32, width: i32, height: i32) -> BuilderChain {
-    let mut builder = BuilderChain::new(depth, width, height, "The Ominous Cavern");
-    builder
-        .start_with(DrunkardsWalkBuilder::winding_passages())
-        .with(AreaStartingPosition::new((X::Center, Y::Center)))
-        .with(CullUnreachable::new())
-        .with(AreaStartingPosition::new((X::Left, Y::Center)))
-        .with(CavernDecorator::new())
-        .with(VoronoiSpawner::new())
-        .with(DistantExit::new());
-    builder
-}
+use super::{Map, Surface};
 
 pub struct CavernDecorator {}
 
@@ -27,18 +11,17 @@ impl CavernDecorator {
 
 impl super::MetaMapBuilder for CavernDecorator {
     fn build_map(&mut self, data: &mut crate::map_builders::BuilderMap) {
-        let mut rng = RandomGen::default();
         let map = data.map.clone();
 
         for (idx, tile) in data.map.tiles.iter_mut().enumerate() {
             tile.surface = match tile.surface {
-                Surface::Floor => match rng.range(0, 100) {
+                Surface::Floor => match data.rng.range(0, 100) {
                     0..=60 => Surface::Gravel,
                     61..=66 => Surface::ShallowWater,
                     _ => Surface::Floor,
                 },
                 Surface::Wall => match count_neighbors(idx, &map) {
-                    1 => match rng.range(0, 4) {
+                    1 => match data.rng.range(0, 4) {
                         0 => Surface::Stalactite,
                         1 => Surface::Stalagmite,
                         _ => Surface::Wall,
@@ -50,7 +33,6 @@ impl super::MetaMapBuilder for CavernDecorator {
             }
         }
         data.take_snapshot();
-        data.map.outdoors = false;
     }
 }
 
@@ -1,25 +1,9 @@
-use super::{
-    random_valid_points_finder, AreaStartingPosition, BuilderChain, CellularAutomataBuilder,
-    CullUnreachable, RandomGen, Surface, VoronoiSpawner, X, Y,
-};
+use super::{random_valid_points_finder, Surface, X, Y};
 use bracket_lib::{
     prelude::{a_star_search, Algorithm2D},
     terminal::Point,
 };
 
-pub fn forest_builder(depth: i32, width: i32, height: i32) -> BuilderChain {
-    let mut builder = BuilderChain::new(depth, width, height, "The Deep Dark Forest");
-    builder
-        .start_with(CellularAutomataBuilder::new())
-        .with(AreaStartingPosition::new((X::Center, Y::Center)))
-        .with(CullUnreachable::new())
-        .with(AreaStartingPosition::new((X::Left, Y::Center)))
-        .with(VoronoiSpawner::new())
-        .with(ForestRoad::new());
-
-    builder
-}
-
 pub struct ForestRoad {}
 
 impl ForestRoad {
@@ -34,7 +18,6 @@ impl super::MetaMapBuilder for ForestRoad {
         let start_idx = data.map.point2d_to_index(data.starting_point.unwrap());
         let end_of_road = random_valid_points_finder(&X::Right, &Y::Center, data);
 
-        data.map.populate_blocked();
         let path = a_star_search(start_idx, end_of_road, &data.map);
 
         for idx in path.steps.iter() {
@@ -47,8 +30,6 @@ impl super::MetaMapBuilder for ForestRoad {
         }
         data.take_snapshot();
 
-        let mut rng = RandomGen::default();
-
         let stream_idx = if !path.steps.is_empty() {
             path.steps[path.steps.len() * 4 / 5]
         } else {
@@ -57,7 +38,7 @@ impl super::MetaMapBuilder for ForestRoad {
 
         let stair = random_valid_points_finder(
             &X::Right,
-            &match rng.range(0, 2) {
+            &match data.rng.range(0, 2) {
                 0 => Y::Top,
                 _ => Y::Bottom,
             },
@@ -0,0 +1,142 @@
+use super::{
+    super::super::{
+        map::themes::parse_theme,
+        raws::levels::{BuilderRaw, LevelLayoutRaw},
+    },
+    cavern::CavernDecorator,
+    forest::ForestRoad,
+    prefabs,
+    random::random_builder,
+    town::TownBuilder,
+    transition_cavern::CavernTransitionBuilder,
+    AreaEndingPosition, AreaStartingPosition, BSPCorridors, BspDungeonBuilder, BspInteriorBuilder,
+    BuilderChain, CellularAutomataBuilder, CorridorSpawner, CullUnreachable, DLABuilder,
+    DistantExit, DoglegCorridors, DoorPlacement, DrunkardsWalkBuilder, InitialMapBuilder,
+    MazeBuilder, MetaMapBuilder, NearestCorridors, Nothing, PrefabSectionBuilder, RoomBasedSpawner,
+    RoomBasedStairs, RoomBasedStartingPosition, RoomCornerRounder, RoomDrawer, RoomExploder,
+    RoomSorter, SimpleMapBuilder, Sorter, StraightLineCorridors, VoronoiCellBuilder,
+    VoronoiSpawner, X, Y,
+};
+
+/// Assembles the builder chain described by a level layout from the raws.
+pub fn build_chain(level: &LevelLayoutRaw, depth: i32, seed: u64) -> Result<BuilderChain, String> {
+    let mut builder = BuilderChain::new(depth, level.width, level.height, &level.name, seed);
+    builder.data.map.outdoors = level.outdoors;
+    builder.data.map.theme =
+        parse_theme(&level.theme).ok_or(format!("unknown theme '{}'", level.theme))?;
+
+    match level.start.name.as_str() {
+        "random" => random_builder(&mut builder),
+        _ => {
+            builder.start_with(initial_builder(&level.start)?);
+        }
+    }
+    for meta in level.meta.iter() {
+        builder.with(meta_builder(meta)?);
+    }
+
+    Ok(builder)
+}
+
+fn initial_builder(raw: &BuilderRaw) -> Result<Box<dyn InitialMapBuilder>, String> {
+    let variant = raw.variant.as_deref().unwrap_or("");
+    Ok(match (raw.name.as_str(), variant) {
+        ("town", "") => TownBuilder::new(),
+        ("simple_map", "") => SimpleMapBuilder::new(),
+        ("bsp_dungeon", "") => BspDungeonBuilder::new(),
+        ("bsp_interior", "") => BspInteriorBuilder::new(),
+        ("cellular_automata", "") => CellularAutomataBuilder::new(),
+        ("maze", "") => MazeBuilder::new(),
+        ("drunkard", "open_area") => DrunkardsWalkBuilder::open_area(),
+        ("drunkard", "open_halls") => DrunkardsWalkBuilder::open_halls(),
+        ("drunkard", "winding_passages") => DrunkardsWalkBuilder::winding_passages(),
+        ("drunkard", "fat_passages") => DrunkardsWalkBuilder::fat_passages(),
+        ("drunkard", "fearful_symmetry") => DrunkardsWalkBuilder::fearful_symmetry(),
+        ("dla", "walk_inwards") => DLABuilder::walk_inwards(),
+        ("dla", "walk_outwards") => DLABuilder::walk_outwards(),
+        ("dla", "central_attractor") => DLABuilder::central_attractor(),
+        ("dla", "insectoid") => DLABuilder::insectoid(),
+        ("dla", "heavy_erosion") => DLABuilder::heavy_erosion(),
+        ("voronoi", "pythagoras") => VoronoiCellBuilder::pythagoras(),
+        ("voronoi", "manhattan") => VoronoiCellBuilder::manhattan(),
+        ("voronoi", "chebyshev") => VoronoiCellBuilder::chebyshev(),
+        _ => {
+            return Err(format!(
+                "unknown initial builder '{}' (variant '{}')",
+                raw.name, variant
+            ))
+        }
+    })
+}
+
+fn meta_builder(raw: &BuilderRaw) -> Result<Box<dyn MetaMapBuilder>, String> {
+    Ok(match raw.name.as_str() {
+        "area_starting_position" => AreaStartingPosition::new(parse_position(raw)?),
+        "area_ending_position" => AreaEndingPosition::new(parse_position(raw)?),
+        "cull_unreachable" => CullUnreachable::new(),
+        "distant_exit" => DistantExit::new(),
+        "door_placement" => DoorPlacement::new(),
+        "nothing" => Nothing::new(),
+        "voronoi_spawner" => VoronoiSpawner::new(),
+        "room_based_spawner" => RoomBasedSpawner::new(),
+        "room_based_stairs" => RoomBasedStairs::new(),
+        "room_based_starting_position" => RoomBasedStartingPosition::new(),
+        "room_corner_rounding" => RoomCornerRounder::new(),
+        "room_draw" => RoomDrawer::new(),
+        "room_exploder" => RoomExploder::new(),
+        "room_sorter" => RoomSorter::new(parse_sorter(raw)?),
+        "corridor_spawner" => CorridorSpawner::new(),
+        "corridors_bsp" => BSPCorridors::new(),
+        "corridors_dogleg" => DoglegCorridors::new(),
+        "corridors_lines" => StraightLineCorridors::new(),
+        "corridors_nearest" => NearestCorridors::new(),
+        "cavern_decorator" => CavernDecorator::new(),
+        "cavern_transition" => CavernTransitionBuilder::new(),
+        "forest_road" => ForestRoad::new(),
+        "prefab_section" => match raw.prefab.as_deref() {
+            Some("orc_camp") => {
+                let (section, description) = prefabs::deep_cavern::orc_camp_prefab();
+                PrefabSectionBuilder::new(section, description)
+            }
+            prefab => return Err(format!("unknown prefab section {:?}", prefab)),
+        },
+        _ => return Err(format!("unknown meta builder '{}'", raw.name)),
+    })
+}
+
+fn parse_position(raw: &BuilderRaw) -> Result<(X, Y), String> {
+    let x = match raw.x.as_deref() {
+        Some("left") => X::Left,
+        Some("center") => X::Center,
+        Some("right") => X::Right,
+        x => {
+            return Err(format!(
+                "'{}' needs x of left/center/right, got {:?}",
+                raw.name, x
+            ))
+        }
+    };
+    let y = match raw.y.as_deref() {
+        Some("top") => Y::Top,
+        Some("center") => Y::Center,
+        Some("bottom") => Y::Bottom,
+        y => {
+            return Err(format!(
+                "'{}' needs y of top/center/bottom, got {:?}",
+                raw.name, y
+            ))
+        }
+    };
+    Ok((x, y))
+}
+
+fn parse_sorter(raw: &BuilderRaw) -> Result<Sorter, String> {
+    match raw.sorter.as_deref() {
+        Some("leftmost") => Ok(Sorter::LeftMost),
+        Some("rightmost") => Ok(Sorter::RightMost),
+        Some("topmost") => Ok(Sorter::TopMost),
+        Some("bottommost") => Ok(Sorter::BottomMost),
+        Some("central") => Ok(Sorter::Central),
+        sorter => Err(format!("unknown room sorter {:?}", sorter)),
+    }
+}
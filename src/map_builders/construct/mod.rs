@@ -1,47 +1,42 @@
-use {
-    super::{
-        super::map::tiles::Surface,
-        initial::{
-            bsp_dungeon::BspDungeonBuilder, bsp_interior::BspInteriorBuilder,
-            cellular_automata::CellularAutomataBuilder, dla::DLABuilder,
-            drunkard::DrunkardsWalkBuilder, maze::MazeBuilder, simple_map::SimpleMapBuilder,
-            voronoi::VoronoiCellBuilder,
-        },
-        meta::{
-            area_ending_position::AreaEndingPosition,
-            area_starting_position::AreaStartingPosition,
-            corridor_spawner::CorridorSpawner,
-            cull_unreachable::CullUnreachable,
-            distant_exit::DistantExit,
-            door_placement::DoorPlacement,
-            nothing::Nothing,
-            //prefab_local::PrefabLocalBuilder,
-            prefab_section::PrefabSectionBuilder,
-            random_valid_points_finder,
-            room_based_spawner::RoomBasedSpawner,
-            room_based_stairs::RoomBasedStairs,
-            room_based_starting_position::RoomBasedStartingPosition,
-            room_corner_rounding::RoomCornerRounder,
-            room_draw::RoomDrawer,
-            room_exploder::RoomExploder,
-            room_sorter::{RoomSorter, Sorter},
-            rooms_corridor_dogleg::DoglegCorridors,
-            rooms_corridors_bsp::BSPCorridors,
-            rooms_corridors_lines::StraightLineCorridors,
-            rooms_corridors_nearest::NearestCorridors,
-            voronoi_spawner::VoronoiSpawner,
-            {random_position, X, Y},
-        },
-        prefabs,
-        rng::RandomGen,
-        BuilderChain, BuilderMap, InitialMapBuilder, Map, MetaMapBuilder,
+use super::{
+    super::map::tiles::Surface,
+    initial::{
+        bsp_dungeon::BspDungeonBuilder, bsp_interior::BspInteriorBuilder,
+        cellular_automata::CellularAutomataBuilder, dla::DLABuilder,
+        drunkard::DrunkardsWalkBuilder, maze::MazeBuilder, simple_map::SimpleMapBuilder,
+        voronoi::VoronoiCellBuilder,
     },
-    cavern::CavernDecorator,
+    meta::{
+        area_ending_position::AreaEndingPosition,
+        area_starting_position::AreaStartingPosition,
+        corridor_spawner::CorridorSpawner,
+        cull_unreachable::CullUnreachable,
+        distant_exit::DistantExit,
+        door_placement::DoorPlacement,
+        nothing::Nothing,
+        //prefab_local::PrefabLocalBuilder,
+        prefab_section::PrefabSectionBuilder,
+        random_valid_points_finder,
+        room_based_spawner::RoomBasedSpawner,
+        room_based_stairs::RoomBasedStairs,
+        room_based_starting_position::RoomBasedStartingPosition,
+        room_corner_rounding::RoomCornerRounder,
+        room_draw::RoomDrawer,
+        room_exploder::RoomExploder,
+        room_sorter::{RoomSorter, Sorter},
+        rooms_corridor_dogleg::DoglegCorridors,
+        rooms_corridors_bsp::BSPCorridors,
+        rooms_corridors_lines::StraightLineCorridors,
+        rooms_corridors_nearest::NearestCorridors,
+        voronoi_spawner::VoronoiSpawner,
+        {random_position, X, Y},
+    },
+    prefabs, BuilderChain, BuilderMap, InitialMapBuilder, Map, MetaMapBuilder,
 };
 
 pub mod cavern;
-pub mod deep_cavern;
 pub mod forest;
+pub mod layout;
 pub mod random;
 pub mod town;
 pub mod transition_cavern;
@@ -5,7 +5,7 @@ use bracket_lib::{
     terminal::{DistanceAlg, Point, Rect},
 };
 
-use super::{BuilderChain, BuilderMap, RandomGen, Surface};
+use super::{BuilderMap, Surface};
 
 #[derive(Debug)]
 enum BuildingTag {
@@ -20,21 +20,13 @@ enum BuildingTag {
     Unassigned,
 }
 
-pub fn town_builder(depth: i32, width: i32, height: i32) -> BuilderChain {
-    let mut builder = BuilderChain::new(depth, width, height, "Town of Lost Hope");
-    builder.start_with(TownBuilder::new());
-
-    builder
-}
-
 pub struct TownBuilder {}
 
 impl super::InitialMapBuilder for TownBuilder {
     #[allow(dead_code)]
     fn build_map(&mut self, data: &mut BuilderMap) {
-        let mut rng = RandomGen::default();
         let usable_area = get_usable_area(data);
-        let gap = rng.range(usable_area.y1 + 6, usable_area.y2 - 6);
+        let gap = data.rng.range(usable_area.y1 + 6, usable_area.y2 - 6);
 
         for tile in data.map.tiles.iter_mut() {
             tile.visible = true;
@@ -76,11 +68,10 @@ fn grass_layer(data: &mut BuilderMap) {
 }
 
 fn water_and_piers(data: &mut BuilderMap) {
-    let mut rng = RandomGen::default();
-    let mut n = (rng.range(1, 65536) as f32) / 65535f32;
+    let mut n = (data.rng.range(1, 65536) as f32) / 65535f32;
     let mut water_width: Vec<i32> = Vec::new();
     for y in 0..data.height {
-        let n_water = (f32::sin(n) * 10.0) as i32 + 14 + rng.range(1, 7);
+        let n_water = (f32::sin(n) * 10.0) as i32 + 14 + data.rng.range(1, 7);
         water_width.push(n_water);
         n += 0.1;
         for x in 0..n_water {
@@ -96,9 +87,9 @@ fn water_and_piers(data: &mut BuilderMap) {
 
     let mut dock_tiles = Vec::new();
 
-    for _ in 0..rng.range(7, 12) {
-        let y = rng.range(0, data.height);
-        for x in rng.range(3, 9)..water_width[y as usize] + 4 {
+    for _ in 0..data.rng.range(7, 12) {
+        let y = data.rng.range(0, data.height);
+        for x in data.rng.range(3, 9)..water_width[y as usize] + 4 {
             let idx = data.map.coord_to_index(x, y);
             data.map.tiles[idx].surface = Surface::Bridge;
             dock_tiles.push(idx);
@@ -172,16 +163,15 @@ fn buildings(
     data: &mut BuilderMap,
     available_building_tiles: &mut HashSet<usize>,
 ) -> Vec<(Rect, Vec<usize>)> {
-    let mut rng = RandomGen::default();
     let mut buildings: Vec<(Rect, Vec<usize>)> = Vec::new();
     let mut number_of_buildings = 0;
     let mut tries = 400;
     let usable_area = get_usable_area(data);
     while number_of_buildings < 12 && tries > 0 {
-        let bx = rng.range(usable_area.x1, usable_area.x2);
-        let by = rng.range(usable_area.y1, usable_area.y2);
-        let bw = rng.range(5, 12);
-        let bh = rng.range(5, 12);
+        let bx = data.rng.range(usable_area.x1, usable_area.x2);
+        let by = data.rng.range(usable_area.y1, usable_area.y2);
+        let bw = data.rng.range(5, 12);
+        let bh = data.rng.range(5, 12);
         let b = Rect::with_size(bx, by, bw, bh);
 
         let mut possible = true;
@@ -258,7 +248,6 @@ fn add_paths(data: &mut BuilderMap, doors: &[usize]) {
         }
     }
 
-    data.map.populate_blocked();
     for door_idx in doors.iter() {
         let mut nearest_roads: Vec<(usize, f32)> = Vec::new();
         let door_pt = Point::new(
@@ -292,11 +281,10 @@ fn add_paths(data: &mut BuilderMap, doors: &[usize]) {
 }
 
 fn add_doors(data: &mut BuilderMap, buildings: &mut [(Rect, Vec<usize>)]) -> Vec<usize> {
-    let mut rng = RandomGen::default();
     let mut doors = Vec::new();
 
     for building in buildings.iter() {
-        let roll = rng.range(0, building.1.len());
+        let roll = data.rng.range(0, building.1.len());
         let idx = building.1[roll];
 
         data.map.tiles[idx].surface = Surface::Floor;
@@ -344,7 +332,6 @@ fn building_factory(
     building_index: &[(usize, i32, BuildingTag)],
 ) {
     use BuildingTag::*;
-    let mut rng = RandomGen::default();
     for (i, building) in buildings.iter().enumerate() {
         let mut interior = match &building_index[i].2 {
             Pub => build_pub(),
@@ -354,7 +341,7 @@ fn building_factory(
             Alchemist => build_alchemist(),
             PlayerHouse => build_player_house(),
             Hovel => build_hovel(),
-            Abandoned => build_abandonned(rng.range(4, 10)),
+            Abandoned => build_abandonned(data.rng.range(4, 10)),
             Unassigned => Vec::new(),
         };
 
@@ -374,16 +361,14 @@ fn random_building_spawn(
     player_idx: usize,
 ) {
     let mut used_indexes = Vec::new();
-    let mut rng = RandomGen::default();
-
     for _ in 0..200 {
         if to_place.is_empty() {
             break;
         }
 
         let p = Point::new(
-            rng.range(building.x1 + 1, building.x2 - 1),
-            rng.range(building.y1 + 1, building.y2 - 1),
+            data.rng.range(building.x1 + 1, building.x2 - 1),
+            data.rng.range(building.y1 + 1, building.y2 - 1),
         );
         let idx = data.map.point2d_to_index(p);
 
@@ -434,6 +419,7 @@ fn build_blacksmith() -> Vec<&'static str> {
         "Water Trough",
         "Weapon Rack",
         "Armor Stand",
+        "Blacksmith's Chest",
     ]
 }
 fn build_clothier() -> Vec<&'static str> {
@@ -443,7 +429,7 @@ fn build_alchemist() -> Vec<&'static str> {
     vec!["Alchemist", "Chemistry Set", "Dead Thing", "Chair", "Table"]
 }
 fn build_player_house() -> Vec<&'static str> {
-    vec!["Mom", "Bed", "Cabinet", "Chair", "Table"]
+    vec!["Mom", "Bed", "Cabinet", "Chair", "Table", "Footlocker"]
 }
 fn build_hovel() -> Vec<&'static str> {
     vec!["Peasant", "Bed", "Chair", "Table"]
@@ -454,7 +440,6 @@ fn build_abandonned(n_rats: usize) -> Vec<&'static str> {
 }
 
 fn spawn_dockers(data: &mut BuilderMap, dock_tiles: &mut Vec<usize>) {
-    let mut rng = RandomGen::default();
     let dockers = ["Dock Worker", "Wannabe Pirate", "Fisher"];
     if dock_tiles.len() < 10 {
         println!("Too few dock tiles.");
@@ -462,14 +447,13 @@ fn spawn_dockers(data: &mut BuilderMap, dock_tiles: &mut Vec<usize>) {
     }
 
     for _ in 0..10 {
-        let idx = dock_tiles.remove(rng.range(0, dock_tiles.len()));
-        let name = dockers[rng.range(0, dockers.len())].to_string();
+        let idx = dock_tiles.remove(data.rng.range(0, dock_tiles.len()));
+        let name = dockers[data.rng.range(0, dockers.len())].to_string();
         data.spawn_list.push((idx, name));
     }
 }
 
 fn spawn_townsfolk(data: &mut BuilderMap, available_building_tiles: &mut HashSet<usize>) {
-    let mut rng = RandomGen::default();
     let townsfolk = ["Peasant", "Drunk", "Dock Worker", "Fisher"];
 
     let area = get_usable_area(data);
@@ -477,11 +461,14 @@ fn spawn_townsfolk(data: &mut BuilderMap, available_building_tiles: &mut HashSet
     let mut tries = 0;
 
     while tries < 200 && townsfolk_to_place > 0 {
-        let p = Point::new(rng.range(area.x1, area.x2), rng.range(area.y1, area.y2));
+        let p = Point::new(
+            data.rng.range(area.x1, area.x2),
+            data.rng.range(area.y1, area.y2),
+        );
         let idx = data.map.point2d_to_index(p);
 
         if !available_building_tiles.contains(&idx) && !data.map.is_blocked(idx) {
-            let name = townsfolk[rng.range(0, townsfolk.len())].to_string();
+            let name = townsfolk[data.rng.range(0, townsfolk.len())].to_string();
             data.spawn_list.push((idx, name));
             available_building_tiles.remove(&idx);
             townsfolk_to_place -= 1;
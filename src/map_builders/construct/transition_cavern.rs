@@ -1,34 +1,19 @@
 use super::{
-    AreaEndingPosition, AreaStartingPosition, BspDungeonBuilder, BuilderChain, BuilderMap,
-    CavernDecorator, CellularAutomataBuilder, CullUnreachable, DistantExit, NearestCorridors,
-    RoomBasedSpawner, RoomDrawer, RoomExploder, RoomSorter, Sorter, VoronoiSpawner, X, Y,
+    BspDungeonBuilder, BuilderChain, BuilderMap, NearestCorridors, RoomBasedSpawner, RoomDrawer,
+    RoomExploder, RoomSorter, Sorter,
 };
 
-pub fn transition_cavern_builder(depth: i32, width: i32, height: i32) -> BuilderChain {
-    let mut builder = BuilderChain::new(depth, width, height, "Into the fort");
-
-    builder
-        .start_with(CellularAutomataBuilder::new())
-        .with(AreaStartingPosition::new((X::Center, Y::Center)))
-        .with(CullUnreachable::new())
-        .with(AreaStartingPosition::new((X::Left, Y::Center)))
-        .with(VoronoiSpawner::new())
-        .with(CavernDecorator::new())
-        .with(CavernTransitionBuilder::new(depth))
-        .with(AreaStartingPosition::new((X::Left, Y::Center)))
-        .with(CullUnreachable::new())
-        .with(AreaEndingPosition::new((X::Right, Y::Center)))
-        .with(DistantExit::new());
-    builder
-}
-
-pub struct CavernTransitionBuilder {
-    depth: i32,
-}
+pub struct CavernTransitionBuilder {}
 
 impl super::MetaMapBuilder for CavernTransitionBuilder {
     fn build_map(&mut self, data: &mut BuilderMap) {
-        let mut builder = BuilderChain::new(self.depth, data.width, data.height, "New Map");
+        let mut builder = BuilderChain::new(
+            data.map.depth,
+            data.width,
+            data.height,
+            "New Map",
+            data.rng.next_u64(),
+        );
         builder
             .start_with(BspDungeonBuilder::new())
             .with(RoomDrawer::new())
@@ -63,7 +48,7 @@ impl super::MetaMapBuilder for CavernTransitionBuilder {
 }
 
 impl CavernTransitionBuilder {
-    pub fn new(depth: i32) -> Box<Self> {
-        Box::new(Self { depth })
+    pub fn new() -> Box<Self> {
+        Box::new(Self {})
     }
 }
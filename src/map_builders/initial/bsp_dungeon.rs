@@ -25,8 +25,8 @@ impl InitialMapBuilder for BspDungeonBuilder {
 
         let mut n_rooms = 0;
         while n_rooms < 240 {
-            let rect = self.get_random_rect();
-            let candidate = self.get_random_sub_rect(rect);
+            let rect = self.get_random_rect(&mut data.rng);
+            let candidate = self.get_random_sub_rect(rect, &mut data.rng);
 
             if self.is_possible(candidate, data, &rooms) {
                 rooms.push(candidate);
@@ -73,8 +73,7 @@ impl BspDungeonBuilder {
         ));
     }
 
-    fn get_random_rect(&mut self) -> Rect {
-        let mut rng = RandomGen::default();
+    fn get_random_rect(&mut self, rng: &mut RandomGen) -> Rect {
         if self.rects.len() == 1 {
             return self.rects[0];
         }
@@ -82,8 +81,7 @@ impl BspDungeonBuilder {
         self.rects[idx]
     }
 
-    fn get_random_sub_rect(&mut self, rect: Rect) -> Rect {
-        let mut rng = RandomGen::default();
+    fn get_random_sub_rect(&mut self, rect: Rect, rng: &mut RandomGen) -> Rect {
         let mut result = rect;
         let rect_width = i32::abs(rect.x1 - rect.x2);
         let rect_height = i32::abs(rect.y1 - rect.y2);
@@ -10,7 +10,6 @@ pub struct BspInteriorBuilder {
 impl InitialMapBuilder for BspInteriorBuilder {
     #[allow(dead_code)]
     fn build_map(&mut self, data: &mut BuilderMap) {
-        let mut rng = RandomGen::default();
         let mut rooms = Vec::<Rect>::new();
         self.rects.clear();
         self.rects.push(Rect::with_size(
@@ -20,7 +19,7 @@ impl InitialMapBuilder for BspInteriorBuilder {
             data.map.height - 2,
         ));
         let first_room = self.rects[0];
-        self.add_subrects(first_room);
+        self.add_subrects(first_room, &mut data.rng);
 
         let rooms_copy = self.rects.clone();
         for (i, r) in rooms_copy.iter().enumerate() {
@@ -43,10 +42,10 @@ impl InitialMapBuilder for BspInteriorBuilder {
         for i in 0..rooms.len() - 1 {
             let room = rooms[i];
             let next_room = rooms[i + 1];
-            let start_x = room.x1 + rng.range(0, i32::abs(room.x1 - room.x2));
-            let start_y = room.y1 + rng.range(0, i32::abs(room.y1 - room.y2));
-            let end_x = next_room.x1 + rng.range(0, i32::abs(next_room.x1 - next_room.x2));
-            let end_y = next_room.y1 + rng.range(0, i32::abs(next_room.y1 - next_room.y2));
+            let start_x = room.x1 + data.rng.range(0, i32::abs(room.x1 - room.x2));
+            let start_y = room.y1 + data.rng.range(0, i32::abs(room.y1 - room.y2));
+            let end_x = next_room.x1 + data.rng.range(0, i32::abs(next_room.x1 - next_room.x2));
+            let end_y = next_room.y1 + data.rng.range(0, i32::abs(next_room.y1 - next_room.y2));
             draw_corridor(&mut data.map, start_x, start_y, end_x, end_y);
             if i % 4 == 0 {
                 data.take_snapshot();
@@ -63,8 +62,7 @@ impl BspInteriorBuilder {
         Box::new(BspInteriorBuilder { rects: Vec::new() })
     }
 
-    fn add_subrects(&mut self, rect: Rect) {
-        let mut rng = RandomGen::default();
+    fn add_subrects(&mut self, rect: Rect, rng: &mut RandomGen) {
         if !self.rects.is_empty() {
             self.rects.remove(self.rects.len() - 1);
         }
@@ -78,23 +76,23 @@ impl BspInteriorBuilder {
             let h1 = Rect::with_size(rect.x1, rect.y1, half_width - 1, height);
             self.rects.push(h1);
             if half_width > MIN_ROOM_SIZE {
-                self.add_subrects(h1);
+                self.add_subrects(h1, rng);
             }
             let h2 = Rect::with_size(rect.x1 + half_width, rect.y1, half_width, height);
             self.rects.push(h2);
             if half_width > MIN_ROOM_SIZE {
-                self.add_subrects(h2);
+                self.add_subrects(h2, rng);
             }
         } else {
             let v1 = Rect::with_size(rect.x1, rect.y1, width, half_height - 1);
             self.rects.push(v1);
             if half_height > MIN_ROOM_SIZE {
-                self.add_subrects(v1);
+                self.add_subrects(v1, rng);
             }
             let v2 = Rect::with_size(rect.x1, rect.y1 + half_height, width, half_height);
             self.rects.push(v2);
             if half_height > MIN_ROOM_SIZE {
-                self.add_subrects(v2);
+                self.add_subrects(v2, rng);
             }
         }
     }
@@ -1,13 +1,12 @@
-use super::{BuilderMap, Map, RandomGen, Surface};
+use super::{BuilderMap, Map, Surface};
 
 pub struct CellularAutomataBuilder {}
 
 impl super::InitialMapBuilder for CellularAutomataBuilder {
     fn build_map(&mut self, data: &mut super::BuilderMap) {
-        let mut rng = RandomGen::default();
         for y in 1..data.map.height - 1 {
             for x in 1..data.map.width - 1 {
-                let roll = rng.range(0, 100);
+                let roll = data.rng.range(0, 100);
                 let idx = data.map.coord_to_index(x, y);
                 if roll > 45 {
                     data.map.tiles[idx].surface = Surface::Floor
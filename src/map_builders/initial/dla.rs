@@ -5,7 +5,7 @@ use bracket_lib::{
 
 use super::{
     super::meta::paint::{paint, Symmetry},
-    RandomGen, Surface,
+    Surface,
 };
 
 #[derive(PartialEq, Copy, Clone)]
@@ -84,7 +84,6 @@ impl DLABuilder {
     }
 
     fn build(&mut self, data: &mut super::BuilderMap) {
-        let mut rng = RandomGen::default();
         let starting_position = Point::new(data.map.width / 2, data.map.height / 2);
         let start_idx = data.map.point2d_to_index(starting_position);
         data.take_snapshot();
@@ -108,8 +107,8 @@ impl DLABuilder {
             match self.algorithm {
                 DLAAlgorithm::WalkInwards => {
                     let mut digger = Point::new(
-                        rng.range(2, data.map.width - 1),
-                        rng.range(2, data.map.height - 1),
+                        data.rng.range(2, data.map.width - 1),
+                        data.rng.range(2, data.map.height - 1),
                     );
                     let mut prev = digger;
                     let mut digger_idx = data.map.point2d_to_index(digger);
@@ -117,7 +116,7 @@ impl DLABuilder {
                     while data.map.tiles[digger_idx].surface == Surface::Wall {
                         prev = digger;
 
-                        match rng.range(1, 5) {
+                        match data.rng.range(1, 5) {
                             1 => {
                                 if digger.x > 2 {
                                     digger.x -= 1;
@@ -149,7 +148,7 @@ impl DLABuilder {
                     let mut digger = starting_position;
                     let mut digger_idx = data.map.point2d_to_index(digger);
                     while data.map.tiles[digger_idx].surface == Surface::Floor {
-                        match rng.range(1, 5) {
+                        match data.rng.range(1, 5) {
                             1 => {
                                 if digger.x > 2 {
                                     digger.x -= 1;
@@ -177,8 +176,8 @@ impl DLABuilder {
                 }
                 DLAAlgorithm::CentralAttractor => {
                     let mut digger = Point::new(
-                        rng.range(1, data.map.width - 1),
-                        rng.range(1, data.map.height - 1),
+                        data.rng.range(1, data.map.width - 1),
+                        data.rng.range(1, data.map.height - 1),
                     );
                     let mut prev = digger;
                     let mut digger_idx = data.map.point2d_to_index(digger);
@@ -2,7 +2,7 @@ use bracket_lib::{prelude::Algorithm2D, terminal::Point};
 
 use super::{
     super::meta::paint::{paint, Symmetry},
-    RandomGen, Surface,
+    Surface,
 };
 
 #[derive(PartialEq, Copy, Clone)]
@@ -95,7 +95,6 @@ impl DrunkardsWalkBuilder {
     }
 
     fn build(&mut self, data: &mut super::BuilderMap) {
-        let mut rng = RandomGen::default();
         let starting_point = Point::new(data.map.width / 2, data.map.height / 2);
         let start_idx = data.map.point2d_to_index(starting_point);
         data.map.tiles[start_idx].surface = Surface::Floor;
@@ -118,8 +117,8 @@ impl DrunkardsWalkBuilder {
                         starting_point
                     } else {
                         Point::new(
-                            rng.range(2, data.map.width - 1),
-                            rng.range(2, data.map.height - 1),
+                            data.rng.range(2, data.map.width - 1),
+                            data.rng.range(2, data.map.height - 1),
                         )
                     }
                 }
@@ -137,7 +136,7 @@ impl DrunkardsWalkBuilder {
                 );
                 data.map.tiles[drunk_idx].surface = Surface::DownStairs;
 
-                match rng.range(1, 5) {
+                match data.rng.range(1, 5) {
                     1 => {
                         if drunk.x > 2 {
                             drunk.x -= 1;
@@ -102,7 +102,7 @@ impl Grid {
         neighbours
     }
 
-    fn find_next_cell(&mut self) -> Option<usize> {
+    fn find_next_cell(&mut self, rng: &mut RandomGen) -> Option<usize> {
         let neighbours = self.get_available_neighbours();
         if neighbours.is_empty() {
             return None;
@@ -112,7 +112,6 @@ impl Grid {
             return Some(neighbours[0]);
         }
 
-        let mut rng = RandomGen::default();
         Some(neighbours[rng.range(0, neighbours.len())])
     }
 
@@ -120,7 +119,7 @@ impl Grid {
         let mut i = 0;
         loop {
             self.cells[self.current].visited = true;
-            let next = self.find_next_cell();
+            let next = self.find_next_cell(&mut data.rng);
 
             match next {
                 Some(next) => {
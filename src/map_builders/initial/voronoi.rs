@@ -3,7 +3,7 @@ use bracket_lib::{
     terminal::{DistanceAlg, Point},
 };
 
-use super::{RandomGen, Surface};
+use super::Surface;
 
 #[allow(dead_code)]
 #[derive(PartialEq, Clone, Copy)]
@@ -41,7 +41,6 @@ impl VoronoiCellBuilder {
 
 impl super::InitialMapBuilder for VoronoiCellBuilder {
     fn build_map(&mut self, data: &mut super::BuilderMap) {
-        let mut rng = RandomGen::default();
         let starting_point = Point::new(data.map.width / 2, data.map.height / 2);
 
         let start_idx = data.map.point2d_to_index(starting_point);
@@ -52,7 +51,10 @@ impl super::InitialMapBuilder for VoronoiCellBuilder {
         let n_seeds = 64;
 
         while voronoi_seeds.len() < n_seeds {
-            let v = Point::new(rng.range(1, data.map.width), rng.range(1, data.map.height));
+            let v = Point::new(
+                data.rng.range(1, data.map.width),
+                data.rng.range(1, data.map.height),
+            );
             let idx = data.map.point2d_to_index(v);
             let candidate = (idx, v);
             if !voronoi_seeds.contains(&candidate) {
@@ -11,7 +11,12 @@ impl super::MetaMapBuilder for CorridorSpawner {
     fn build_map(&mut self, data: &mut super::BuilderMap) {
         if let Some(corridors) = &data.corridors {
             for c in corridors.iter() {
-                super::spawner::spawn_in_region(c, data.map.depth, &mut data.spawn_list);
+                super::spawner::spawn_in_region(
+                    c,
+                    data.map.depth,
+                    &mut data.spawn_list,
+                    &mut data.rng,
+                );
             }
         } else {
             panic!("Corridor Based Spawning only works after corridors have been created");
@@ -16,7 +16,6 @@ impl super::MetaMapBuilder for CullUnreachable {
             .map
             .point2d_to_index(*data.starting_point.as_ref().unwrap());
 
-        data.map.populate_blocked();      
         let map_starts: Vec<usize> = vec![idx];
 
         let dijkstra_map = DijkstraMap::new(
@@ -27,7 +26,7 @@ impl super::MetaMapBuilder for CullUnreachable {
             1000.0,
         );
         for (i, tile) in data.map.tiles.iter_mut().enumerate() {                        
-            if tile.surface == Surface::Floor && dijkstra_map.map[i] == std::f32::MAX {
+            if tile.surface == Surface::Floor && dijkstra_map.map[i] == f32::MAX {
                 tile.surface = Surface::Wall;
             }
         }
@@ -15,7 +15,6 @@ impl super::MetaMapBuilder for DistantExit {
             .map
             .point2d_to_index(*data.starting_point.as_ref().unwrap());
 
-        data.map.populate_blocked();
         let map_starts: Vec<usize> = vec![idx];
 
         let dijkstra_map = DijkstraMap::new(
@@ -30,7 +29,7 @@ impl super::MetaMapBuilder for DistantExit {
 
         for (i, tile) in data.map.tiles.iter_mut().enumerate() {
             if tile.surface == Surface::Floor
-                && dijkstra_map.map[i] != std::f32::MAX
+                && dijkstra_map.map[i] != f32::MAX
                 && dijkstra_map.map[i] > exit_tile.1
             {
                 exit_tile.0 = i;
@@ -1,4 +1,6 @@
-use super::{RandomGen, Surface, Tile};
+use super::{Surface, Tile};
+
+const LOCKED_ONE_IN: i32 = 5;
 
 pub struct DoorPlacement {}
 
@@ -56,8 +58,16 @@ fn is_door_possible(map: &super::Map, idx: usize) -> bool {
     false
 }
 
+/// One door in `LOCKED_ONE_IN` is locked.
+fn pick_door(data: &mut super::BuilderMap) -> String {
+    if data.rng.range(0, LOCKED_ONE_IN) == 0 {
+        "Locked Door".to_string()
+    } else {
+        "Door".to_string()
+    }
+}
+
 fn find_doors_in_corridors(data: &mut super::BuilderMap, corridors: Vec<Vec<usize>>) {
-    let mut rng = RandomGen::default();
     for corridor in corridors.iter() {
         if corridor.len() < 2 {
             continue;
@@ -65,28 +75,29 @@ fn find_doors_in_corridors(data: &mut super::BuilderMap, corridors: Vec<Vec<usiz
 
         for tile in corridor.iter() {
             if !is_door_possible(&data.map, *tile)
-                || rng.range(0, 10) > 0
+                || data.rng.range(0, 10) > 0
                 || is_entity(&data.spawn_list, *tile)
             {
                 continue;
             }
 
-            data.spawn_list.push((*tile, "Door".to_string()));
+            let door = pick_door(data);
+            data.spawn_list.push((*tile, door));
             break;
         }
     }
 }
 
 fn find_doors_in_caves(data: &mut super::BuilderMap, tiles: Vec<Tile>) {
-    let mut rng = RandomGen::default();
     for (idx, tile) in tiles.iter().enumerate() {
         if tile.surface != Surface::Floor
             || !is_door_possible(&data.map, idx)
-            || rng.range(0, 10) > 0
+            || data.rng.range(0, 10) > 0
             || is_entity(&data.spawn_list, idx)
         {
             continue;
         }
-        data.spawn_list.push((idx, "Door".to_string()));
+        let door = pick_door(data);
+        data.spawn_list.push((idx, door));
     }
 }
@@ -85,8 +85,7 @@ pub enum Y {
     Bottom,
 }
 
-pub fn random_position() -> (X, Y) {
-    let mut rng = RandomGen::default();
+pub fn random_position(rng: &mut RandomGen) -> (X, Y) {
     (
         match rng.range(0, 3) {
             0 => X::Left,
@@ -1,8 +1,9 @@
 use bracket_lib::{prelude::Algorithm2D, terminal::Point};
 
-use super::{BuilderMap, Description, RandomGen, Surface};
+use super::{BuilderMap, Description, Surface};
 use std::collections::HashSet;
 
+#[allow(dead_code)]
 pub struct PrefabLocal {
     pub template: &'static str,
     pub width: usize,
@@ -19,8 +20,6 @@ pub struct PrefabLocalBuilder {
 
 impl super::MetaMapBuilder for PrefabLocalBuilder {
     fn build_map(&mut self, data: &mut BuilderMap) {
-        let mut rng = RandomGen::default();
-
         if self.list.is_empty() || self.number_max_of_prefab == 0 {
             return;
         }
@@ -28,7 +27,7 @@ impl super::MetaMapBuilder for PrefabLocalBuilder {
         let mut used_tiles: HashSet<usize> = HashSet::new();
 
         for _ in 0..self.number_max_of_prefab {
-            let vault_idx = rng.range(0, self.list.len());
+            let vault_idx = data.rng.range(0, self.list.len());
             let vault = &self.list[vault_idx].0;
             let mut vault_positions = Vec::<Point>::new();
 
@@ -62,7 +61,7 @@ impl super::MetaMapBuilder for PrefabLocalBuilder {
                 continue;
             }
 
-            let pos = &vault_positions[rng.range(0, vault_positions.len())];
+            let pos = &vault_positions[data.rng.range(0, vault_positions.len())];
 
             data.spawn_list.retain(|e| {
                 let idx = e.0 as i32;
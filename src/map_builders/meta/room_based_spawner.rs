@@ -4,7 +4,7 @@ impl super::MetaMapBuilder for RoomBasedSpawner {
     fn build_map(&mut self, data: &mut super::BuilderMap) {
         if let Some(rooms) = &data.rooms {
             for room in rooms.iter().skip(1) {
-                super::spawner::spawn_in_room(&data.map, room, &mut data.spawn_list);
+                super::spawner::spawn_in_room(&data.map, room, &mut data.spawn_list, &mut data.rng);
             }
         } else {
             panic!("Room Based Spawning only works after rooms have been created");
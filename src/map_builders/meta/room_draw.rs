@@ -3,19 +3,18 @@ use bracket_lib::{
     terminal::{DistanceAlg, Point, Rect},
 };
 
-use super::{BuilderMap, RandomGen, Surface};
+use super::{BuilderMap, Surface};
 pub struct RoomDrawer {}
 
 impl super::MetaMapBuilder for RoomDrawer {
     fn build_map(&mut self, data: &mut super::BuilderMap) {
-        let mut rng = RandomGen::default();
         let rooms: Vec<Rect> = if let Some(rb) = &data.rooms {
             rb.clone()
         } else {
             panic!("Room drawer require a builder with room structures");
         };
         for room in rooms.iter() {
-            match rng.range(0, 4) {
+            match data.rng.range(0, 4) {
                 0 => self.circle(data, room),
                 _ => self.rectangle(data, room),
             }
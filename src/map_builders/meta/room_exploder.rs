@@ -2,14 +2,13 @@ use bracket_lib::{prelude::Algorithm2D, terminal::Rect};
 
 use super::{
     paint::{paint, Symmetry},
-    RandomGen, Surface,
+    Surface,
 };
 
 pub struct RoomExploder {}
 
 impl super::MetaMapBuilder for RoomExploder {
     fn build_map(&mut self, data: &mut super::BuilderMap) {
-        let mut rng = RandomGen::default();
         let rooms: Vec<Rect> = if let Some(rb) = &data.rooms {
             rb.clone()
         } else {
@@ -18,7 +17,7 @@ impl super::MetaMapBuilder for RoomExploder {
 
         for room in rooms.iter() {
             let start = room.center();
-            let diggers = rng.range(-3, 16);
+            let diggers = data.rng.range(-3, 16);
             if diggers <= 0 {
                 continue;
             }
@@ -33,7 +32,7 @@ impl super::MetaMapBuilder for RoomExploder {
                     paint(&mut data.map, Symmetry::None, 1, p);
                     data.map.tiles[idx].surface = Surface::DownStairs;
 
-                    match rng.range(1, 5) {
+                    match data.rng.range(1, 5) {
                         1 => {
                             if p.x > 2 {
                                 p.x -= 1
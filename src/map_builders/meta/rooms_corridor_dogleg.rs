@@ -1,9 +1,6 @@
 use bracket_lib::terminal::Rect;
 
-use super::{
-    paint::{apply_horizontal_tunnel, apply_vertical_tunnel},
-    RandomGen,
-};
+use super::paint::{apply_horizontal_tunnel, apply_vertical_tunnel};
 pub struct DoglegCorridors {}
 
 impl super::MetaMapBuilder for DoglegCorridors {
@@ -14,8 +11,6 @@ impl super::MetaMapBuilder for DoglegCorridors {
             panic!("Dogleg corridors require a builder with room structures");
         };
 
-        let mut rng = RandomGen::default();
-
         let mut corridors: Vec<Vec<usize>> = Vec::new();
         for (i, room) in rooms.iter().enumerate() {
             if i == 0 {
@@ -23,7 +18,7 @@ impl super::MetaMapBuilder for DoglegCorridors {
             }
             let new = room.center();
             let prev = rooms[i - 1].center();
-            if rng.range(0, 2) == 1 {
+            if data.rng.range(0, 2) == 1 {
                 corridors.push(apply_horizontal_tunnel(
                     &mut data.map,
                     prev.x,
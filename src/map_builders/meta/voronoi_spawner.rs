@@ -1,7 +1,7 @@
 use bracket_lib::prelude::{CellularDistanceFunction, FastNoise, NoiseType};
 
-use super::{RandomGen, Surface};
-use std::collections::HashMap;
+use super::Surface;
+use std::collections::BTreeMap;
 
 pub struct VoronoiSpawner {}
 
@@ -14,9 +14,8 @@ impl VoronoiSpawner {
 
 impl super::MetaMapBuilder for VoronoiSpawner {
     fn build_map(&mut self, data: &mut super::BuilderMap) {
-        let mut rng = RandomGen::default();
-        let mut noise_areas: HashMap<i32, Vec<usize>> = HashMap::new();
-        let mut noise = FastNoise::seeded(rng.range(1, 65536) as u64);
+        let mut noise_areas: BTreeMap<i32, Vec<usize>> = BTreeMap::new();
+        let mut noise = FastNoise::seeded(data.rng.range(1, 65536) as u64);
         noise.set_noise_type(NoiseType::Cellular);
         noise.set_frequency(0.08);
         noise.set_cellular_distance_function(CellularDistanceFunction::Manhattan);
@@ -28,7 +27,7 @@ impl super::MetaMapBuilder for VoronoiSpawner {
                     let cell_value_f = noise.get_noise(x as f32, y as f32) * 10240.0;
                     let cell_value = cell_value_f as i32;
 
-                    if let std::collections::hash_map::Entry::Vacant(e) =
+                    if let std::collections::btree_map::Entry::Vacant(e) =
                         noise_areas.entry(cell_value)
                     {
                         e.insert(vec![idx]);
@@ -40,7 +39,12 @@ impl super::MetaMapBuilder for VoronoiSpawner {
         }
 
         for area in noise_areas.iter() {
-            super::spawner::spawn_in_region(area.1, data.map.depth, &mut data.spawn_list);
+            super::spawner::spawn_in_region(
+                area.1,
+                data.map.depth,
+                &mut data.spawn_list,
+                &mut data.rng,
+            );
         }
     }
 }
@@ -1,4 +1,7 @@
-use super::{rng, spawner, Map, SHOW_MAPGEN_VISUALIZER};
+use super::{
+    raws::{levels::find_level_layout, RAWS},
+    rng, spawner, Map, SHOW_MAPGEN_VISUALIZER,
+};
 use bracket_lib::terminal::{Point, Rect};
 use specs::World;
 
@@ -7,10 +10,7 @@ mod initial;
 mod meta;
 mod prefabs;
 
-use construct::{
-    cavern::cavern_builder, deep_cavern::deep_cavern_builder, forest::forest_builder,
-    random::random_builder, town::town_builder, transition_cavern::transition_cavern_builder,
-};
+pub use construct::layout::build_chain;
 
 pub struct BuilderMap {
     pub spawn_list: Vec<(usize, String)>,
@@ -21,6 +21,7 @@ pub struct BuilderMap {
     pub corridors: Option<Vec<Vec<usize>>>,
     pub width: i32,
     pub height: i32,
+    pub rng: rng::RandomGen,
 }
 
 impl BuilderMap {
@@ -42,7 +43,13 @@ pub struct BuilderChain {
 }
 
 impl BuilderChain {
-    pub fn new<S: ToString>(depth: i32, width: i32, height: i32, name: S) -> BuilderChain {
+    pub fn new<S: ToString>(
+        depth: i32,
+        width: i32,
+        height: i32,
+        name: S,
+        seed: u64,
+    ) -> BuilderChain {
         BuilderChain {
             starter: None,
             builders: Vec::new(),
@@ -55,6 +62,7 @@ impl BuilderChain {
                 corridors: None,
                 width,
                 height,
+                rng: rng::RandomGen::new(seed),
             },
         }
     }
@@ -101,13 +109,9 @@ pub trait MetaMapBuilder {
     fn build_map(&mut self, data: &mut BuilderMap);
 }
 
-pub fn level_builder(depth: i32, width: i32, height: i32) -> BuilderChain {
-    match depth {
-        0 => town_builder(depth, 80, 50),
-        1 => forest_builder(depth, 100, 60),
-        2 => cavern_builder(depth, 100, 60),
-        3 => deep_cavern_builder(depth, 80, 80),
-        4 => transition_cavern_builder(depth, 80, 80),
-        _ => random_builder(depth, width, height),
-    }
+pub fn level_builder(depth: i32, seed: u64) -> BuilderChain {
+    let raws = RAWS.lock().unwrap();
+    let level = find_level_layout(&raws, depth)
+        .unwrap_or_else(|| panic!("No level layout in the raws for depth {}", depth));
+    build_chain(level, depth, seed).unwrap_or_else(|e| panic!("{}: {}", level.name, e))
 }
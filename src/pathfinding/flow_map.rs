@@ -0,0 +1,178 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+};
+
+use bracket_lib::{
+    prelude::{Algorithm2D, BaseMap, DijkstraMap, SmallVec},
+    terminal::Point,
+};
+
+use super::super::map::{tiles::is_tile_walkable, Map};
+
+const FLOW_DEPTH: f32 = 200.0;
+const FLEE_FACTOR: f32 = -1.2;
+
+#[derive(PartialEq, Eq, Hash, Clone)]
+pub enum FlowGoal {
+    TowardPlayer,
+    AwayFromPlayer,
+    /// Toward anything the named faction attacks.
+    Hostiles(String),
+    /// Away from anything the named faction flees from.
+    Threats(String),
+    /// Away from anything the named faction attacks, for mobs whose nerve broke.
+    Enemies(String),
+}
+
+impl FlowGoal {
+    fn flees(&self) -> bool {
+        matches!(
+            self,
+            FlowGoal::AwayFromPlayer | FlowGoal::Threats(_) | FlowGoal::Enemies(_)
+        )
+    }
+}
+
+struct FlowMap {
+    sources: Vec<usize>,
+    checked_turn: i32,
+    dijkstra: DijkstraMap,
+}
+
+/// Dijkstra maps shared by the movement AI. Each one is checked at most once
+/// per turn, and only rebuilt when the terrain or its sources have changed.
+#[derive(Default)]
+pub struct FlowMaps {
+    terrain: u64,
+    turn: i32,
+    maps: HashMap<FlowGoal, FlowMap>,
+}
+
+impl FlowMaps {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `sources` is only called when the cached map has not been checked this turn.
+    pub fn get<F>(&mut self, goal: &FlowGoal, map: &Map, turn: i32, sources: F) -> &DijkstraMap
+    where
+        F: FnOnce() -> Vec<usize>,
+    {
+        if self.turn != turn || self.maps.is_empty() {
+            self.turn = turn;
+            let terrain = terrain_signature(map);
+            if terrain != self.terrain {
+                self.terrain = terrain;
+                self.maps.clear();
+            }
+        }
+
+        let checked = self
+            .maps
+            .get(goal)
+            .is_some_and(|flow| flow.checked_turn == turn);
+        if !checked {
+            let mut sources = sources();
+            sources.sort_unstable();
+            sources.dedup();
+
+            match self.maps.get_mut(goal) {
+                Some(flow) if flow.sources == sources => flow.checked_turn = turn,
+                _ => {
+                    let dijkstra = build_flow(&sources, map, goal.flees());
+                    self.maps.insert(
+                        goal.clone(),
+                        FlowMap {
+                            sources,
+                            checked_turn: turn,
+                            dijkstra,
+                        },
+                    );
+                }
+            }
+        }
+
+        &self.maps[goal].dijkstra
+    }
+}
+
+/// Next tile downhill from `idx`, skipping tiles that are currently occupied.
+pub fn next_step(flow: &DijkstraMap, idx: usize, map: &dyn BaseMap) -> Option<usize> {
+    let current = flow.map[idx];
+    map.get_available_exits(idx)
+        .iter()
+        .map(|(exit, _)| *exit)
+        .filter(|exit| flow.map[*exit] < current)
+        .min_by(|a, b| flow.map[*a].total_cmp(&flow.map[*b]))
+}
+
+fn build_flow(sources: &[usize], map: &Map, flee: bool) -> DijkstraMap {
+    let terrain = Terrain { map };
+    let mut dijkstra = DijkstraMap::new(map.width, map.height, sources, &terrain, FLOW_DEPTH);
+    for source in sources.iter() {
+        dijkstra.map[*source] = 0.0;
+    }
+
+    // Scaling the distances by a negative factor and relaxing them again makes
+    // fleeing units head for open space rather than the nearest corner.
+    if flee {
+        let starts: Vec<(usize, f32)> = dijkstra
+            .map
+            .iter()
+            .enumerate()
+            .filter(|(_, depth)| **depth < FLOW_DEPTH)
+            .map(|(idx, depth)| (idx, depth * FLEE_FACTOR))
+            .collect();
+        DijkstraMap::clear(&mut dijkstra);
+        for (idx, depth) in starts.iter() {
+            dijkstra.map[*idx] = *depth;
+        }
+        DijkstraMap::build_weighted(&mut dijkstra, &starts, &terrain);
+    }
+
+    dijkstra
+}
+
+fn terrain_signature(map: &Map) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (map.depth, map.width, map.height).hash(&mut hasher);
+    for tile in map.tiles.iter() {
+        is_tile_walkable(tile.surface).hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// The map as seen by flow maps: walls block, creatures do not.
+struct Terrain<'a> {
+    map: &'a Map,
+}
+
+impl BaseMap for Terrain<'_> {
+    fn get_available_exits(&self, idx: usize) -> SmallVec<[(usize, f32); 10]> {
+        let mut exits = SmallVec::new();
+        let pt = self.map.index_to_point2d(idx);
+
+        for (dx, dy) in [
+            (-1, 0),
+            (1, 0),
+            (0, -1),
+            (0, 1),
+            (-1, -1),
+            (1, -1),
+            (-1, 1),
+            (1, 1),
+        ] {
+            let exit = Point::new(pt.x + dx, pt.y + dy);
+            if !self.map.in_bounds(exit) {
+                continue;
+            }
+            let exit_idx = self.map.point2d_to_index(exit);
+            if is_tile_walkable(self.map.tiles[exit_idx].surface) {
+                exits.push((exit_idx, if dx != 0 && dy != 0 { 1.45 } else { 1.0 }));
+            }
+        }
+
+        exits
+    }
+}
@@ -1 +1,2 @@
 pub mod a_star;
+pub mod flow_map;
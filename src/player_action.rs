@@ -1,21 +1,25 @@
 use super::{
-    action::{WantsToMelee, WantsToPickupItem, WantsToUseItem},
+    action::{
+        WantsToBashDoor, WantsToCloseDoor, WantsToMelee, WantsToOpenContainer, WantsToOpenDoor,
+        WantsToPickupItem, WantsToUseItem,
+    },
     effect::Ranged,
     gui::menu::MainMenuSelection,
-    item::{Consumable, InBackpack, Item},
+    item::{Ammunition, Consumable, Equipped, InBackpack, Item, RangedWeapon},
     map::tiles::Surface,
-    props::Door,
+    props::{Container, ContainerMode, Door, Trapped},
     raws::{
-        factions::{faction_reaction, Reaction},
+        factions::{reaction_to, Reaction},
         RAWS,
     },
     state::{RunState, State},
+    systems::ranged_combat::{equipped_ranged_weapon, find_ammunition},
     unit::{Attributes, EntityMoved, Faction, Player, Vendor, VendorMode, Viewshed},
-    BlocksVisibility, Log, Map, Position, Renderable,
+    Log, Map, Position,
 };
 use bracket_lib::{
     prelude::Algorithm2D,
-    terminal::{to_cp437, BTerm, Point, VirtualKeyCode},
+    terminal::{BTerm, Point, VirtualKeyCode},
 };
 use specs::prelude::*;
 
@@ -29,10 +33,10 @@ pub fn try_move_player(dx: i32, dy: i32, ecs: &mut World) -> RunState {
     let entities = ecs.entities();
     let mut wants_to_melee = ecs.write_storage::<WantsToMelee>();
     let factions = ecs.read_storage::<Faction>();
-    let mut doors = ecs.write_storage::<Door>();
-    let mut blocks_visibility = ecs.write_storage::<BlocksVisibility>();
-    let mut renderables = ecs.write_storage::<Renderable>();
+    let doors = ecs.read_storage::<Door>();
+    let mut wants_to_open = ecs.write_storage::<WantsToOpenDoor>();
     let vendors = ecs.read_storage::<Vendor>();
+    let containers = ecs.read_storage::<Container>();
 
     let mut swap_entities = Vec::<(Entity, i32, i32)>::new();
     let mut result = RunState::AwaitingInput;
@@ -45,16 +49,19 @@ pub fn try_move_player(dx: i32, dy: i32, ecs: &mut World) -> RunState {
         }
 
         let dest_idx = map.point2d_to_index(new);
-        for potential_target in map.tiles[dest_idx].content.clone().iter() {
-            if combat_stats.get(*potential_target).is_some() {
-                if let Some(faction) = factions.get(*potential_target) {
-                    let reaction = faction_reaction(&faction.name, "Player", &RAWS.lock().unwrap());
+        if let Some(potential_target) = map.index.first_blocker(dest_idx) {
+            if combat_stats.get(potential_target).is_some() {
+                if let (Some(faction), Some(player_faction)) =
+                    (factions.get(potential_target), factions.get(entity))
+                {
+                    let reaction =
+                        reaction_to(&faction.name, player_faction, &RAWS.lock().unwrap());
                     if reaction == Reaction::Attack || reaction == Reaction::Flee {
                         wants_to_melee
                             .insert(
                                 entity,
                                 WantsToMelee {
-                                    target: *potential_target,
+                                    target: potential_target,
                                 },
                             )
                             .expect("Add target failed");
@@ -63,23 +70,29 @@ pub fn try_move_player(dx: i32, dy: i32, ecs: &mut World) -> RunState {
                 }
             }
 
-            if vendors.get(*potential_target).is_some() {
+            if vendors.get(potential_target).is_some() {
                 return RunState::ShowVendor {
-                    vendor: *potential_target,
+                    vendor: potential_target,
                     mode: VendorMode::Sell,
                 };
             }
 
-            if let Some(door) = doors.get_mut(*potential_target) {
-                door.open = true;
-                blocks_visibility.remove(*potential_target);
+            if containers.contains(potential_target) {
+                return open_container(ecs, entity, potential_target);
+            }
 
-                map.blocks_movement(dest_idx, false);
-                renderables.get_mut(*potential_target).unwrap().glyph = to_cp437('/');
-                viewshed.dirty = true;
+            if doors.contains(potential_target) {
+                wants_to_open
+                    .insert(
+                        entity,
+                        WantsToOpenDoor {
+                            door: potential_target,
+                        },
+                    )
+                    .expect("Unable to insert");
                 result = RunState::Ticking;
             } else {
-                swap_entities.push((*potential_target, pos.x, pos.y));
+                swap_entities.push((potential_target, pos.x, pos.y));
                 pos.x = (new.x).clamp(0, map.width - 1);
                 pos.y = (new.y).clamp(0, map.height - 1);
                 entity_moved
@@ -105,12 +118,11 @@ pub fn try_move_player(dx: i32, dy: i32, ecs: &mut World) -> RunState {
             .expect("Unable to insert marker");
         viewshed.dirty = true;
 
-        let moving_from = map.coord_to_index(pos.x, pos.y);
         pos.x = new.x.clamp(0, map.width - 1);
         pos.y = new.y.clamp(0, map.height - 1);
         let moving_to = map.coord_to_index(pos.x, pos.y);
 
-        map.move_entity(entity, moving_from, moving_to);
+        map.move_entity(entity, moving_to);
 
         ppos.x = pos.x;
         ppos.y = pos.y;
@@ -124,18 +136,104 @@ pub fn try_move_player(dx: i32, dy: i32, ecs: &mut World) -> RunState {
 
     for m in swap_entities.iter() {
         if let Some(their_pos) = positions.get_mut(m.0) {
-            let moving_from = map.coord_to_index(their_pos.x, their_pos.y);
             their_pos.x = m.1;
             their_pos.y = m.2;
             let moving_to = map.coord_to_index(their_pos.x, their_pos.y);
 
-            map.move_entity(m.0, moving_from, moving_to);
+            map.move_entity(m.0, moving_to);
         }
     }
 
     result
 }
 
+/// The first door around the player that is `open`, or closed if not.
+fn adjacent_door(ecs: &World, open: bool) -> Option<Entity> {
+    let ppos = *ecs.fetch::<Point>();
+    let doors = ecs.read_storage::<Door>();
+    let positions = ecs.read_storage::<Position>();
+    (&ecs.entities(), &doors, &positions)
+        .join()
+        .find(|(_, door, pos)| {
+            door.open == open
+                && (pos.x - ppos.x).abs() <= 1
+                && (pos.y - ppos.y).abs() <= 1
+                && (pos.x, pos.y) != (ppos.x, ppos.y)
+        })
+        .map(|(entity, _, _)| entity)
+}
+
+fn close_door(ecs: &mut World) -> RunState {
+    match adjacent_door(ecs, true) {
+        None => {
+            Log::new().append("There is no open door nearby.").build();
+            RunState::AwaitingInput
+        }
+        Some(door) => {
+            ecs.write_storage::<WantsToCloseDoor>()
+                .insert(*ecs.fetch::<Entity>(), WantsToCloseDoor { door })
+                .expect("Unable to insert");
+            RunState::Ticking
+        }
+    }
+}
+
+fn bash_door(ecs: &mut World) -> RunState {
+    match adjacent_door(ecs, false) {
+        None => {
+            Log::new().append("There is no closed door nearby.").build();
+            RunState::AwaitingInput
+        }
+        Some(door) => {
+            ecs.write_storage::<WantsToBashDoor>()
+                .insert(*ecs.fetch::<Entity>(), WantsToBashDoor { door })
+                .expect("Unable to insert");
+            RunState::Ticking
+        }
+    }
+}
+
+/// Shows what is inside `container`, unless it has to be unlocked or its
+/// trap sprung first, which takes a turn.
+fn open_container(ecs: &World, entity: Entity, container: Entity) -> RunState {
+    let locked = ecs
+        .read_storage::<Container>()
+        .get(container)
+        .is_some_and(|container| container.locked);
+    if locked || ecs.read_storage::<Trapped>().contains(container) {
+        ecs.write_storage::<WantsToOpenContainer>()
+            .insert(entity, WantsToOpenContainer { container })
+            .expect("Unable to insert");
+        return RunState::Ticking;
+    }
+    RunState::ShowContainer {
+        container,
+        mode: ContainerMode::Take,
+    }
+}
+
+/// Searches a container lying on the floor, such as a corpse, when there is
+/// nothing else here to pick up.
+fn search_container(ecs: &World) -> Option<RunState> {
+    let ppos = *ecs.fetch::<Point>();
+    let positions = ecs.read_storage::<Position>();
+    let here = |pos: &Position| pos.x == ppos.x && pos.y == ppos.y;
+    if (&ecs.read_storage::<Item>(), &positions)
+        .join()
+        .any(|(_, pos)| here(pos))
+    {
+        return None;
+    }
+    (
+        &ecs.entities(),
+        &ecs.read_storage::<Container>(),
+        &positions,
+    )
+        .join()
+        .find(|(_, _, pos)| here(pos))
+        .map(|(container, _, _)| open_container(ecs, *ecs.fetch::<Entity>(), container))
+}
+
 fn get_item(ecs: &mut World) {
     let ppos = ecs.fetch::<Point>();
     let player_entity = ecs.fetch::<Entity>();
@@ -167,9 +265,56 @@ fn get_item(ecs: &mut World) {
     }
 }
 
+fn fire_ranged_weapon(ecs: &mut World) -> RunState {
+    let player_entity = ecs.fetch::<Entity>();
+    let entities = ecs.entities();
+    let weapon = equipped_ranged_weapon(
+        &entities,
+        &ecs.read_storage::<Equipped>(),
+        &ecs.read_storage::<RangedWeapon>(),
+        *player_entity,
+    );
+
+    match weapon {
+        None => {
+            Log::new()
+                .append("You have no ranged weapon equipped.")
+                .build();
+            RunState::AwaitingInput
+        }
+        Some((weapon_entity, weapon)) => {
+            if let Some(kind) = &weapon.ammo {
+                let ammo = find_ammunition(
+                    &entities,
+                    &ecs.read_storage::<InBackpack>(),
+                    &ecs.read_storage::<Ammunition>(),
+                    *player_entity,
+                    weapon_entity,
+                    kind,
+                );
+                if ammo.is_none() {
+                    Log::new()
+                        .append("You are out of")
+                        .item(kind)
+                        .append(".")
+                        .build();
+                    return RunState::AwaitingInput;
+                }
+            }
+            RunState::ShowFireTargeting {
+                range: weapon.range,
+            }
+        }
+    }
+}
+
 pub fn input(gs: &mut State, ctx: &mut BTerm) -> RunState {
-    if ctx.shift && ctx.key.is_some() {
-        if let Some(key) = match ctx.key.unwrap() {
+    key_input(gs, ctx.key, ctx.shift)
+}
+
+pub fn key_input(gs: &mut State, key: Option<VirtualKeyCode>, shift: bool) -> RunState {
+    if let (true, Some(key)) = (shift, key) {
+        if let Some(key) = match key {
             VirtualKeyCode::Key1 => Some(1),
             VirtualKeyCode::Key2 => Some(2),
             VirtualKeyCode::Key3 => Some(3),
@@ -185,7 +330,7 @@ pub fn input(gs: &mut State, ctx: &mut BTerm) -> RunState {
         }
     }
 
-    match ctx.key {
+    match key {
         None => return RunState::AwaitingInput,
         Some(key) => match key {
             VirtualKeyCode::Left | VirtualKeyCode::Numpad4 => {
@@ -206,10 +351,19 @@ pub fn input(gs: &mut State, ctx: &mut BTerm) -> RunState {
             VirtualKeyCode::Numpad1 => return try_move_player(-1, 1, &mut gs.ecs),
 
             //action
-            VirtualKeyCode::G => get_item(&mut gs.ecs),
+            VirtualKeyCode::G => {
+                if let Some(state) = search_container(&gs.ecs) {
+                    return state;
+                }
+                get_item(&mut gs.ecs)
+            }
             VirtualKeyCode::I => return RunState::ShowInventory,
             VirtualKeyCode::D => return RunState::ShowDropItem,
             VirtualKeyCode::R => return RunState::ShowRemoveItem,
+            VirtualKeyCode::F => return fire_ranged_weapon(&mut gs.ecs),
+            VirtualKeyCode::C => return RunState::ShowCastSpell,
+            VirtualKeyCode::X => return close_door(&mut gs.ecs),
+            VirtualKeyCode::B => return bash_door(&mut gs.ecs),
             VirtualKeyCode::Minus => return RunState::ShowCheatMenu,
             VirtualKeyCode::Escape => {
                 return RunState::MainMenu {
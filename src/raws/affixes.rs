@@ -0,0 +1,222 @@
+use serde::Deserialize;
+use specs::prelude::*;
+use std::collections::HashMap;
+
+use super::{
+    get_damage_type, parse_attribute, parse_dice_string, AttributeBonus, Cursed, DamageBonus, Item,
+    MeleeWeapon, Name, RandomGen, RandomTable, RangedWeapon, RawMaster, Stackable, Wearable,
+};
+
+const ENCHANT_CHANCE_BASE: i32 = 10;
+const ENCHANT_CHANCE_PER_DEPTH: i32 = 4;
+const ENCHANT_CHANCE_MAX: i32 = 60;
+
+#[derive(Deserialize, Debug)]
+pub struct AffixRaw {
+    pub name: String,
+    /// "prefix" or "suffix", which decides where the name goes.
+    pub position: String,
+    /// "weapon", "armor" or "any".
+    pub applies_to: String,
+    pub weight: i32,
+    pub min_depth: i32,
+    pub max_depth: i32,
+    pub hit_bonus: Option<i32>,
+    pub damage_bonus: Option<i32>,
+    pub armor_class: Option<i32>,
+    pub attributes: Option<HashMap<String, i32>>,
+    pub extra_damage: Option<ExtraDamageRaw>,
+    pub initiative: Option<f32>,
+    pub value: Option<i32>,
+    pub cursed: Option<bool>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ExtraDamageRaw {
+    pub damage_type: String,
+    pub dice: String,
+}
+
+impl AffixRaw {
+    fn fits(&self, position: &str, weapon: bool, depth: i32) -> bool {
+        let kind = match self.applies_to.as_str() {
+            "any" => true,
+            "weapon" => weapon,
+            "armor" => !weapon,
+            _ => false,
+        };
+        kind && self.position == position && depth >= self.min_depth && depth <= self.max_depth
+    }
+}
+
+/// Gives a freshly spawned weapon or piece of armor a chance, growing with
+/// depth, to roll a prefix and a suffix from raws/affixes.json.
+pub fn enchant_item(raws: &RawMaster, ecs: &mut World, item: Entity, depth: i32) {
+    let weapon = ecs.read_storage::<MeleeWeapon>().contains(item)
+        || ecs.read_storage::<RangedWeapon>().contains(item);
+    // Stacks are split and merged by their raw name, so they stay plain.
+    if (!weapon && !ecs.read_storage::<Wearable>().contains(item))
+        || ecs.read_storage::<Stackable>().contains(item)
+    {
+        return;
+    }
+
+    let chance = i32::min(
+        ENCHANT_CHANCE_MAX,
+        ENCHANT_CHANCE_BASE + depth * ENCHANT_CHANCE_PER_DEPTH,
+    );
+    let mut affixes = Vec::<&AffixRaw>::new();
+    for position in ["prefix", "suffix"] {
+        let mut rng = ecs.write_resource::<RandomGen>();
+        if rng.roll_dice(1, 100) > chance {
+            continue;
+        }
+        let mut table = RandomTable::new();
+        for affix in raws.raws.affixes.iter() {
+            if affix.fits(position, weapon, depth) {
+                table = table.add(affix.name.clone(), affix.weight);
+            }
+        }
+        let rolled = table.roll(&mut rng);
+        if let Some(affix) = raws.raws.affixes.iter().find(|a| a.name == rolled) {
+            affixes.push(affix);
+        }
+    }
+
+    for affix in affixes.iter() {
+        apply_affix(ecs, item, affix);
+    }
+}
+
+/// Puts the affix called `name` on `item`, whatever its depth and weight.
+pub fn apply_named_affix(raws: &RawMaster, ecs: &mut World, item: Entity, name: &str) {
+    match raws.raws.affixes.iter().find(|affix| affix.name == name) {
+        Some(affix) => apply_affix(ecs, item, affix),
+        None => println!("Warning: unknown affix {}", name),
+    }
+}
+
+fn apply_affix(ecs: &mut World, item: Entity, affix: &AffixRaw) {
+    let hit_bonus = affix.hit_bonus.unwrap_or(0);
+    let damage_bonus = affix.damage_bonus.unwrap_or(0);
+    if let Some(weapon) = ecs.write_storage::<MeleeWeapon>().get_mut(item) {
+        weapon.hit_bonus += hit_bonus;
+        weapon.damage_bonus += damage_bonus;
+    }
+    if let Some(weapon) = ecs.write_storage::<RangedWeapon>().get_mut(item) {
+        weapon.hit_bonus += hit_bonus;
+        weapon.damage_bonus += damage_bonus;
+    }
+    if let Some(armor) = ecs.write_storage::<Wearable>().get_mut(item) {
+        armor.armor_class += affix.armor_class.unwrap_or(0);
+    }
+    if let Some(details) = ecs.write_storage::<Item>().get_mut(item) {
+        details.initiative_penalty += affix.initiative.unwrap_or(0.0);
+        details.base_value = i32::max(1, details.base_value + affix.value.unwrap_or(0));
+    }
+
+    if let Some(attributes) = &affix.attributes {
+        let mut bonuses = ecs.write_storage::<AttributeBonus>();
+        if !bonuses.contains(item) {
+            bonuses
+                .insert(item, AttributeBonus { modifiers: vec![] })
+                .expect("Unable to insert");
+        }
+        let bonus = bonuses.get_mut(item).unwrap();
+        for (name, amount) in attributes.iter() {
+            match parse_attribute(&name.to_lowercase()) {
+                Some(attribute) => bonus.modifiers.push((attribute, *amount)),
+                None => println!("Warning: unknown attribute {} in {}", name, affix.name),
+            }
+        }
+    }
+
+    if let Some(extra) = &affix.extra_damage {
+        let dice = parse_dice_string(&extra.dice);
+        ecs.write_storage::<DamageBonus>()
+            .insert(
+                item,
+                DamageBonus {
+                    damage_type: get_damage_type(Some(&extra.damage_type)),
+                    n_dice: dice.n_dice,
+                    die_type: dice.die_type,
+                    bonus: dice.bonus,
+                },
+            )
+            .expect("Unable to insert");
+    }
+
+    if affix.cursed.unwrap_or(false) {
+        ecs.write_storage::<Cursed>()
+            .insert(item, Cursed {})
+            .expect("Unable to insert");
+    }
+
+    if let Some(name) = ecs.write_storage::<Name>().get_mut(item) {
+        name.name = match affix.position.as_str() {
+            "prefix" => format!("{} {}", affix.name, name.name),
+            _ => format!("{} {}", name.name, affix.name),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{super::new_state, AttributeType, RAWS},
+        *,
+    };
+
+    fn longsword(ecs: &mut World) -> Entity {
+        ecs.create_entity()
+            .with(Name::new("Longsword"))
+            .with(MeleeWeapon::base())
+            .with(Item {
+                initiative_penalty: 0.0,
+                weight: 1.5,
+                base_value: 15,
+            })
+            .build()
+    }
+
+    #[test]
+    fn affixes_add_their_stats_and_names() {
+        let mut ecs = new_state(Some(1)).ecs;
+        let raws = RAWS.lock().unwrap();
+        let sword = longsword(&mut ecs);
+
+        apply_named_affix(&raws, &mut ecs, sword, "Masterwork");
+        apply_named_affix(&raws, &mut ecs, sword, "of Might");
+
+        let weapons = ecs.read_storage::<MeleeWeapon>();
+        assert_eq!(weapons.get(sword).unwrap().hit_bonus, 2);
+        assert_eq!(weapons.get(sword).unwrap().damage_bonus, 2);
+        assert_eq!(
+            ecs.read_storage::<Item>().get(sword).unwrap().base_value,
+            125
+        );
+        let bonus = ecs.read_storage::<AttributeBonus>();
+        let modifiers = &bonus.get(sword).unwrap().modifiers;
+        assert!(modifiers.len() == 1 && modifiers[0] == (AttributeType::Might, 1));
+        assert_eq!(
+            ecs.read_storage::<Name>().get(sword).unwrap().name,
+            "Masterwork Longsword of Might"
+        );
+        assert!(!ecs.read_storage::<Cursed>().contains(sword));
+    }
+
+    #[test]
+    fn cursed_affixes_curse_the_item() {
+        let mut ecs = new_state(Some(1)).ecs;
+        let raws = RAWS.lock().unwrap();
+        let sword = longsword(&mut ecs);
+
+        apply_named_affix(&raws, &mut ecs, sword, "Rusted");
+
+        let weapons = ecs.read_storage::<MeleeWeapon>();
+        assert_eq!(weapons.get(sword).unwrap().hit_bonus, -2);
+        assert_eq!(weapons.get(sword).unwrap().damage_bonus, -1);
+        assert_eq!(ecs.read_storage::<Item>().get(sword).unwrap().base_value, 5);
+        assert!(ecs.read_storage::<Cursed>().contains(sword));
+    }
+}
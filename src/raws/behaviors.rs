@@ -0,0 +1,31 @@
+use super::RawMaster;
+use serde::Deserialize;
+
+/// A named weighting of the actions a mob's utility AI picks from. Actions
+/// left out of `weights` are never chosen.
+#[derive(Deserialize)]
+pub struct BehaviorRaw {
+    pub name: String,
+    pub weights: WeightsRaw,
+    pub preferred_range: Option<i32>,
+    pub guard_radius: Option<i32>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WeightsRaw {
+    pub attack: Option<f32>,
+    pub approach: Option<f32>,
+    pub flee: Option<f32>,
+    pub use_item: Option<f32>,
+    pub guard: Option<f32>,
+    pub wander: Option<f32>,
+    pub call_for_help: Option<f32>,
+}
+
+pub fn find_behavior<'a>(raws: &'a RawMaster, name: &str) -> Option<&'a BehaviorRaw> {
+    raws.raws
+        .behaviors
+        .iter()
+        .find(|behavior| behavior.name == name)
+}
@@ -0,0 +1,19 @@
+use super::RawMaster;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Deserialize)]
+pub struct ClassRaw {
+    pub name: String,
+    pub description: String,
+    pub skills: Option<HashMap<String, i32>>,
+    pub equipped: Vec<String>,
+    pub carried: Vec<String>,
+    pub gold: i32,
+    pub spells: Option<Vec<String>>,
+    pub faction_standing: Option<HashMap<String, String>>,
+}
+
+pub fn find_class<'a>(raws: &'a RawMaster, name: &str) -> Option<&'a ClassRaw> {
+    raws.raws.classes.iter().find(|class| class.name == name)
+}
@@ -1,8 +1,8 @@
-use super::RawMaster;
-use serde::Deserialize;
+use super::{Faction, RawMaster};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
 pub enum Reaction {
     Ignore,
     Attack,
@@ -28,3 +28,20 @@ pub fn faction_reaction(my: &str, their: &str, raws: &RawMaster) -> Reaction {
     print!("Ignore");
     Reaction::Ignore
 }
+
+pub fn parse_reaction(reaction: &str) -> Reaction {
+    match reaction {
+        "ignore" => Reaction::Ignore,
+        "flee" => Reaction::Flee,
+        _ => Reaction::Attack,
+    }
+}
+
+/// How `my` faction reacts to `other`, honouring the standing `other` holds
+/// with that faction before falling back to the faction table.
+pub fn reaction_to(my: &str, other: &Faction, raws: &RawMaster) -> Reaction {
+    match other.standing.get(my) {
+        Some(reaction) => *reaction,
+        None => faction_reaction(my, &other.name, raws),
+    }
+}
@@ -1,13 +1,19 @@
 use serde::Deserialize;
-use specs::prelude::*;
-use std::collections::HashMap;
+use specs::{
+    prelude::*,
+    saveload::{MarkedBuilder, SimpleMarker},
+};
+use std::collections::{HashMap, HashSet};
 
 use super::{
-    parse_dice_string,
-    rawmaster::{get_renderable_component, spawn_position},
-    AreaOfEffect, Confusion, Consumable, EquipmentSlot, Equippable, InflictsDamage, Item,
-    MeleeWeapon, Name, ProvidesHealing, Ranged, RawMaster, RenderableRaw, SpawnType,
-    WeaponAttribute, Wearable,
+    c, parse_dice_string,
+    rawmaster::{
+        get_damage_type, get_renderable_component, get_resistances_component, spawn_effects,
+        spawn_position,
+    },
+    Ammunition, Consumable, Disguise, EquipmentSlot, Equippable, Identification, InBackpack,
+    InContainer, Item, MeleeWeapon, Name, RandomGen, RangedWeapon, RawMaster, RenderableRaw,
+    SerializeMe, Shield, SpawnType, Stackable, WeaponAttribute, Wearable,
 };
 
 #[derive(Deserialize, Debug)]
@@ -17,10 +23,13 @@ pub struct ItemRaw {
     pub consumable: Option<ConsumableRaw>,
     pub weapon: Option<WeaponRaw>,
     pub wearable: Option<WearableRaw>,
+    pub ammunition: Option<String>,
     pub initiative_penalty: Option<f32>,
     pub weight: Option<f32>,
     pub value: Option<i32>,
     pub vendor_category: Option<String>,
+    pub unidentified: Option<String>,
+    pub stackable: Option<bool>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -34,12 +43,24 @@ pub struct WeaponRaw {
     pub attribute: String,
     pub base_damage: String,
     pub hit_bonus: i32,
+    pub damage_type: Option<String>,
+    pub ammo: Option<String>,
+    pub two_handed: Option<bool>,
+    pub off_hand: Option<bool>,
+}
+
+impl WeaponRaw {
+    pub fn ranged_range(&self) -> Option<i32> {
+        self.range.parse::<i32>().ok()
+    }
 }
 
 #[derive(Deserialize, Debug)]
 pub struct WearableRaw {
     pub armor_class: i32,
     pub slot: String,
+    pub resistances: Option<HashMap<String, String>>,
+    pub block_chance: Option<i32>,
 }
 
 pub fn spawn_named_item(
@@ -53,11 +74,48 @@ pub fn spawn_named_item(
     }
 
     let item_template = &raws.raws.items[raws.item_index[key]];
-    let mut eb = ecs.create_entity();
+    let stackable = item_template.stackable.unwrap_or(false);
+
+    // A stackable item joins the one its owner already carries or holds.
+    if stackable {
+        let mut stacks = ecs.write_storage::<Stackable>();
+        let entities = ecs.entities();
+        let names = ecs.read_storage::<Name>();
+        let existing = match &pos {
+            SpawnType::Carried { by } => (&entities, &ecs.read_storage::<InBackpack>(), &names)
+                .join()
+                .find(|(item, carried, name)| {
+                    carried.owner == *by && name.name == key && stacks.contains(*item)
+                })
+                .map(|(item, _, _)| item),
+            SpawnType::Contained { by } => (&entities, &ecs.read_storage::<InContainer>(), &names)
+                .join()
+                .find(|(item, held, name)| {
+                    held.owner == *by && name.name == key && stacks.contains(*item)
+                })
+                .map(|(item, _, _)| item),
+            _ => None,
+        };
+        if let Some(item) = existing {
+            stacks.get_mut(item).unwrap().quantity += 1;
+            return Some(item);
+        }
+    }
+
+    let disguise_color = ecs
+        .fetch::<Identification>()
+        .disguises
+        .get(key)
+        .map(|disguise| c(&disguise.color));
+    let mut eb = ecs.create_entity().marked::<SimpleMarker<SerializeMe>>();
     eb = spawn_position(pos, eb, key, raws);
 
     if let Some(renderable) = &item_template.renderable {
-        eb = eb.with(get_renderable_component(renderable));
+        let mut renderable = get_renderable_component(renderable);
+        if let Some(color) = disguise_color {
+            renderable.fg = color;
+        }
+        eb = eb.with(renderable);
     }
 
     eb = eb.with(Name {
@@ -70,61 +128,55 @@ pub fn spawn_named_item(
         base_value: item_template.value.unwrap_or(0),
     });
 
+    if stackable {
+        eb = eb.with(Stackable { quantity: 1 });
+    }
+
     if let Some(consumable) = &item_template.consumable {
         eb = eb.with(Consumable {});
-        for effect in consumable.effects.iter() {
-            let effect_name = effect.0.as_str();
-            match effect_name {
-                "provides_healing" => {
-                    eb = eb.with(ProvidesHealing {
-                        heal_amount: effect.1.parse::<i32>().unwrap(),
-                    })
-                }
-                "ranged" => {
-                    eb = eb.with(Ranged {
-                        range: effect.1.parse::<i32>().unwrap(),
-                    })
-                }
-                "damage" => {
-                    eb = eb.with(InflictsDamage {
-                        damage: effect.1.parse::<i32>().unwrap(),
-                    })
-                }
-                "area_of_effect" => {
-                    eb = eb.with(AreaOfEffect {
-                        radius: effect.1.parse::<i32>().unwrap(),
-                    })
-                }
-                "confusion" => {
-                    eb = eb.with(Confusion {
-                        duration: effect.1.parse::<i32>().unwrap(),
-                    })
-                }
-                _ => {
-                    println!(
-                        "Warning: consumable effect {} not implemented.",
-                        effect_name
-                    );
-                }
-            }
-        }
+        eb = spawn_effects(eb, &consumable.effects);
     }
     if let Some(weapon) = &item_template.weapon {
-        eb = eb.with(Equippable {
-            slot: EquipmentSlot::Melee,
-        });
         let dice = parse_dice_string(&weapon.base_damage);
+        let attribute = match weapon.attribute.as_str() {
+            "Quickness" => WeaponAttribute::Quickness,
+            _ => WeaponAttribute::Might,
+        };
+        let damage_type = get_damage_type(weapon.damage_type.as_deref());
 
-        eb = eb.with(MeleeWeapon {
-            attribute: match weapon.attribute.as_str() {
-                "Quickness" => WeaponAttribute::Quickness,
-                _ => WeaponAttribute::Might,
-            },
-            damage_n_dice: dice.n_dice,
-            damage_bonus: dice.bonus,
-            damage_die_type: dice.die_type,
-            hit_bonus: weapon.hit_bonus,
-        });
+        if let Some(range) = weapon.ranged_range() {
+            eb = eb.with(Equippable {
+                slot: EquipmentSlot::Ranged,
+            });
+            eb = eb.with(RangedWeapon {
+                range,
+                attribute,
+                damage_n_dice: dice.n_dice,
+                damage_bonus: dice.bonus,
+                damage_die_type: dice.die_type,
+                hit_bonus: weapon.hit_bonus,
+                damage_type,
+                ammo: weapon.ammo.clone(),
+            });
+        } else {
+            eb = eb.with(Equippable {
+                slot: EquipmentSlot::Melee,
+            });
+            eb = eb.with(MeleeWeapon {
+                attribute,
+                damage_n_dice: dice.n_dice,
+                damage_bonus: dice.bonus,
+                damage_die_type: dice.die_type,
+                hit_bonus: weapon.hit_bonus,
+                damage_type,
+                two_handed: weapon.two_handed.unwrap_or(false),
+                off_hand: weapon.off_hand.unwrap_or(false),
+            });
+        }
+    }
+
+    if let Some(kind) = &item_template.ammunition {
+        eb = eb.with(Ammunition { kind: kind.clone() });
     }
 
     if let Some(wearable) = &item_template.wearable {
@@ -134,11 +186,99 @@ pub fn spawn_named_item(
             slot,
             armor_class: wearable.armor_class,
         });
+        if let Some(block_chance) = wearable.block_chance {
+            eb = eb.with(Shield { block_chance });
+        }
+        if let Some(resistances) = &wearable.resistances {
+            eb = eb.with(get_resistances_component(resistances));
+        }
     }
 
     Some(eb.build())
 }
 
+const DISGUISE_SALT: i32 = -1;
+
+const POTION_LOOKS: [(&str, &str); 10] = [
+    ("Murky", "#6B8E23"),
+    ("Fizzy", "#FFD700"),
+    ("Crimson", "#DC143C"),
+    ("Azure", "#1E90FF"),
+    ("Milky", "#F5F5F5"),
+    ("Violet", "#9400D3"),
+    ("Amber", "#FFBF00"),
+    ("Smoky", "#A9A9A9"),
+    ("Bubbling", "#7FFFD4"),
+    ("Oily", "#8B4513"),
+];
+
+const SCROLL_COLORS: [&str; 6] = [
+    "#FFFFAA", "#AAFFFF", "#FFAAFF", "#AAFFAA", "#FFD0A0", "#D0A0FF",
+];
+
+const SCROLL_SYLLABLES: [&str; 12] = [
+    "ka", "zu", "mi", "ro", "sha", "te", "no", "ki", "ra", "yo", "fu", "hi",
+];
+/// Rolls for an unused syllable label before falling back to a numbered one.
+const SCROLL_LABEL_ATTEMPTS: usize = 100;
+
+/// Hands every unidentified item type a disguise for the run, shuffled from
+/// the run seed so a reload or replay keeps them.
+pub fn disguise_items(raws: &RawMaster, seed: u64) -> Identification {
+    let mut rng = RandomGen::from_run_seed(seed, DISGUISE_SALT);
+    let mut identification = Identification::default();
+
+    let mut potion_looks = POTION_LOOKS.to_vec();
+    let mut labels = HashSet::<String>::new();
+
+    for item in raws.raws.items.iter() {
+        let disguise = match item.unidentified.as_deref() {
+            None => continue,
+            Some("potion") => {
+                if potion_looks.is_empty() {
+                    println!("Warning: out of potion looks for {}", item.name);
+                    continue;
+                }
+                let idx = rng.range(0, potion_looks.len());
+                let (look, color) = potion_looks.swap_remove(idx);
+                Disguise {
+                    name: format!("{} Potion", look),
+                    color: color.to_string(),
+                }
+            }
+            Some("scroll") => {
+                let rolled = (0..SCROLL_LABEL_ATTEMPTS).find_map(|_| {
+                    let label: String = (0..rng.range(2, 4))
+                        .map(|_| *rng.random_slice_entry(&SCROLL_SYLLABLES).unwrap())
+                        .collect();
+                    labels.insert(label.clone()).then_some(label)
+                });
+                let label = rolled.unwrap_or_else(|| {
+                    let label = format!("number {}", labels.len() + 1);
+                    labels.insert(label.clone());
+                    label
+                });
+                let mut label_chars = label.chars();
+                let label = match label_chars.next() {
+                    Some(first) => first.to_uppercase().chain(label_chars).collect(),
+                    None => label,
+                };
+                Disguise {
+                    name: format!("{} Scroll", label),
+                    color: rng.random_slice_entry(&SCROLL_COLORS).unwrap().to_string(),
+                }
+            }
+            Some(kind) => {
+                println!("Warning: unknown disguise {} for {}", kind, item.name);
+                continue;
+            }
+        };
+        identification.disguises.insert(item.name.clone(), disguise);
+    }
+
+    identification
+}
+
 pub fn find_slot_for_equippable_item(tag: &str, raws: &RawMaster) -> EquipmentSlot {
     if !raws.item_index.contains_key(tag) {
         panic!("Trying to equip an unknown item: {}", tag);
@@ -146,8 +286,11 @@ pub fn find_slot_for_equippable_item(tag: &str, raws: &RawMaster) -> EquipmentSl
 
     let idx = raws.item_index[tag];
     let item = &raws.raws.items[idx];
-    if item.weapon.is_some() {
-        return EquipmentSlot::Melee;
+    if let Some(weapon) = &item.weapon {
+        return match weapon.ranged_range() {
+            Some(_) => EquipmentSlot::Ranged,
+            None => EquipmentSlot::Melee,
+        };
     } else if let Some(wearable) = &item.wearable {
         return string_to_slot(&wearable.slot);
     }
@@ -159,6 +302,7 @@ pub fn string_to_slot(slot: &str) -> EquipmentSlot {
     match slot {
         "Shield" => EquipmentSlot::Shield,
         "Melee" => EquipmentSlot::Melee,
+        "Ranged" => EquipmentSlot::Ranged,
         "Head" => EquipmentSlot::Head,
         "Torso" => EquipmentSlot::Torso,
         "Legs" => EquipmentSlot::Legs,
@@ -175,9 +319,9 @@ pub fn get_vendor_items(categories: &[String], raws: &RawMaster) -> Vec<(String,
     let mut result: Vec<(String, i32)> = Vec::new();
 
     for item in raws.raws.items.iter() {
-        if let Some(cat) = &item.vendor_category {
-            if categories.contains(cat) && item.value.is_some() {
-                result.push((item.name.clone(), item.value.unwrap()));
+        if let (Some(cat), Some(value)) = (&item.vendor_category, item.value) {
+            if categories.contains(cat) {
+                result.push((item.name.clone(), value));
             }
         }
     }
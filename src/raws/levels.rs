@@ -0,0 +1,70 @@
+use super::{super::map_builders::build_chain, RawMaster};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct LevelLayoutRaw {
+    pub min_depth: i32,
+    pub max_depth: Option<i32>,
+    pub name: String,
+    pub width: i32,
+    pub height: i32,
+    pub theme: String,
+    pub outdoors: bool,
+    pub start: BuilderRaw,
+    pub meta: Vec<BuilderRaw>,
+}
+
+#[derive(Deserialize)]
+pub struct BuilderRaw {
+    pub name: String,
+    pub variant: Option<String>,
+    pub x: Option<String>,
+    pub y: Option<String>,
+    pub sorter: Option<String>,
+    pub prefab: Option<String>,
+}
+
+impl LevelLayoutRaw {
+    fn contains(&self, depth: i32) -> bool {
+        depth >= self.min_depth && self.max_depth.is_none_or(|max| depth <= max)
+    }
+}
+
+pub fn find_level_layout(raws: &RawMaster, depth: i32) -> Option<&LevelLayoutRaw> {
+    raws.raws.levels.iter().find(|level| level.contains(depth))
+}
+
+/// Checks that the layouts cover every depth from 0 onwards exactly once, and
+/// that every builder chain can be assembled.
+pub fn validate_levels(levels: &[LevelLayoutRaw]) -> Result<(), String> {
+    let mut next_depth = 0;
+    for (i, level) in levels.iter().enumerate() {
+        if level.min_depth != next_depth {
+            return Err(format!(
+                "'{}' starts at depth {}, expected {}",
+                level.name, level.min_depth, next_depth
+            ));
+        }
+        match level.max_depth {
+            Some(max) if max < level.min_depth => {
+                return Err(format!("'{}' ends before it starts", level.name))
+            }
+            Some(max) => next_depth = max + 1,
+            None if i + 1 < levels.len() => {
+                return Err(format!("'{}' is open-ended but is not last", level.name))
+            }
+            None => {}
+        }
+        if level.width < 20 || level.height < 20 {
+            return Err(format!("'{}' must be at least 20x20", level.name));
+        }
+        if let Err(e) = build_chain(level, level.min_depth, 0) {
+            return Err(format!("'{}': {}", level.name, e));
+        }
+    }
+
+    match levels.last() {
+        Some(level) if level.max_depth.is_none() => Ok(()),
+        _ => Err("the last level must leave max_depth open".to_string()),
+    }
+}
@@ -1,4 +1,4 @@
-use super::{RandomTable, RawMaster};
+use super::{RandomGen, RandomTable, RawMaster};
 use serde::Deserialize;
 
 #[derive(Deserialize)]
@@ -11,9 +11,12 @@ pub struct LootTableRaw {
 pub struct LootDropRaw {
     pub name: String,
     pub weight: i32,
+    /// Dice rolled for how many drop, for stackable items.
+    pub quantity: Option<String>,
 }
 
-pub fn get_loots(raws: &RawMaster, table: &str) -> Option<String> {
+/// Rolls an item off `table`, along with how many of it drop.
+pub fn get_loots(raws: &RawMaster, table: &str, rng: &mut RandomGen) -> Option<(String, i32)> {
     if !raws.loot_index.contains_key(table) {
         return None;
     }
@@ -24,5 +27,12 @@ pub fn get_loots(raws: &RawMaster, table: &str) -> Option<String> {
         random_table = random_table.add(item.name.clone(), item.weight);
     }
 
-    Some(random_table.roll())
+    let name = random_table.roll(rng);
+    let quantity = options
+        .drops
+        .iter()
+        .find(|drop| drop.name == name)
+        .and_then(|drop| drop.quantity.as_ref())
+        .map_or(1, |dice| i32::max(1, rng.roll_str(dice)));
+    Some((name, quantity))
 }
@@ -1,16 +1,27 @@
 use super::{
     super::colors::c,
+    behaviors::find_behavior,
     parse_dice_string,
-    rawmaster::{get_renderable_component, spawn_position},
-    spawn_named_entity, Attribute, Attributes, BlocksTile, Entity, EquipmentChanged, Faction,
-    Initiative, LightSource, LootTable, Movement, MovementMode, Name, NaturalAttack,
-    NaturalProperty, Pools, Quips, RandomGen, RawMaster, RenderableRaw, Skills, SpawnType, Vendor,
-    Viewshed,
+    rawmaster::{
+        get_damage_type, get_renderable_component, get_resistances_component, get_skills_component,
+        spawn_position,
+    },
+    spawn_named_entity,
+    spells::known_spells,
+    Attribute, Attributes, Behavior, BlocksTile, Entity, EquipmentChanged, Faction, Initiative,
+    LightSource, LootTable, Morale, Movement, MovementMode, Name, NaturalAttack, NaturalProperty,
+    OpensDoors, Pool, Pools, Quips, RandomGen, RawMaster, RenderableRaw, SerializeMe, SpawnType,
+    SquadLeader, Vendor, Viewshed,
 };
 use serde::Deserialize;
-use specs::prelude::*;
+use specs::{
+    prelude::*,
+    saveload::{MarkedBuilder, SimpleMarker},
+};
 use std::collections::HashMap;
 
+const DEFAULT_COURAGE: i32 = 5;
+
 #[derive(Deserialize)]
 pub struct MobRaw {
     pub name: String,
@@ -25,12 +36,19 @@ pub struct MobRaw {
     pub mana: Option<i32>,
     pub hp: Option<i32>,
     pub equipped: Option<Vec<String>>,
+    pub carried: Option<Vec<String>>,
     pub natural: Option<MobNaturalRaw>,
     pub loot_table: Option<String>,
     pub light: Option<LightRaw>,
     pub faction: Option<String>,
     pub money: Option<String>,
     pub vendor: Option<Vec<String>>,
+    pub spells: Option<Vec<String>>,
+    pub squad: Option<SquadRaw>,
+    pub courage: Option<i32>,
+    pub behavior: Option<String>,
+    pub opens_doors: Option<bool>,
+    pub resistances: Option<HashMap<String, String>>,
 }
 
 #[derive(Deserialize)]
@@ -52,6 +70,13 @@ pub struct NaturalAttackRaw {
     pub name: String,
     pub hit_bonus: i32,
     pub damage: String,
+    pub damage_type: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct SquadRaw {
+    pub followers: Vec<String>,
+    pub radius: i32,
 }
 
 #[derive(Deserialize)]
@@ -69,9 +94,12 @@ pub fn spawn_named_mob(
     if !raws.mob_index.contains_key(key) {
         return None;
     }
-    let mut rng = RandomGen::default();
     let mob_template = &raws.raws.mobs[raws.mob_index[key]];
-    let mut eb = ecs.create_entity();
+    let money = mob_template
+        .money
+        .as_ref()
+        .map(|money| ecs.write_resource::<RandomGen>().roll_str(money));
+    let mut eb = ecs.create_entity().marked::<SimpleMarker<SerializeMe>>();
     eb = spawn_position(pos, eb, key, raws);
 
     if let Some(renderable) = &mob_template.renderable {
@@ -130,32 +158,17 @@ pub fn spawn_named_mob(
 
     let mut pools = Pools::new_npc(attr);
 
-    if let Some(money) = &mob_template.money {
-        pools.money = rng.roll_str(money);
+    if let Some(mana) = mob_template.mana {
+        pools.mana = Pool::new(mana);
     }
 
-    eb = eb.with(pools);
-
-    let mut skills = Skills::default();
-
-    if let Some(mobskills) = &mob_template.skills {
-        for s in mobskills.iter() {
-            match s.0.as_str() {
-                "Melee" => {
-                    skills.melee = *s.1;
-                }
-                "Magic" => {
-                    skills.magic = *s.1;
-                }
-                "Defense" => {
-                    skills.defense = *s.1;
-                }
-                _ => println!("Unknown skill : {}", s.0),
-            }
-        }
+    if let Some(money) = money {
+        pools.money = money;
     }
 
-    eb = eb.with(skills);
+    eb = eb.with(pools);
+
+    eb = eb.with(get_skills_component(&mob_template.skills));
 
     if let Some(natural) = &mob_template.natural {
         let mut natural_property = NaturalProperty {
@@ -172,6 +185,7 @@ pub fn spawn_named_mob(
                     damage_bonus: dice.bonus,
                     damage_die_type: dice.die_type,
                     damage_n_dice: dice.n_dice,
+                    damage_type: get_damage_type(attack.damage_type.as_deref()),
                 });
             }
         }
@@ -193,21 +207,55 @@ pub fn spawn_named_mob(
 
     eb = eb.with(Initiative { current: 2 });
 
-    eb = eb.with(Faction {
-        name: mob_template
-            .faction
-            .clone()
-            .unwrap_or_else(|| "Mindless".to_string()),
+    eb = eb.with(Morale {
+        courage: mob_template.courage.unwrap_or(DEFAULT_COURAGE),
+        broken: false,
     });
 
+    eb = eb.with(Faction::new(
+        mob_template.faction.as_deref().unwrap_or("Mindless"),
+    ));
+
     eb = eb.with(EquipmentChanged {});
 
+    if let Some(spells) = &mob_template.spells {
+        eb = eb.with(known_spells(raws, spells));
+    }
+
     if let Some(vendor) = &mob_template.vendor {
         eb = eb.with(Vendor {
             categories: vendor.clone(),
         });
     }
 
+    if let Some(behavior) = &mob_template.behavior {
+        if find_behavior(raws, behavior).is_some() {
+            eb = eb.with(Behavior {
+                profile: behavior.clone(),
+                home: None,
+                called_turn: None,
+            });
+        } else {
+            println!("Warning: unknown behavior {}", behavior);
+        }
+    }
+
+    if let Some(resistances) = &mob_template.resistances {
+        eb = eb.with(get_resistances_component(resistances));
+    }
+
+    if mob_template.opens_doors.unwrap_or(false) {
+        eb = eb.with(OpensDoors {});
+    }
+
+    if let Some(squad) = &mob_template.squad {
+        eb = eb.with(SquadLeader {
+            followers: squad.followers.clone(),
+            radius: squad.radius,
+            formed: false,
+        });
+    }
+
     //Mob Equippement
     let mob = eb.build();
 
@@ -217,5 +265,11 @@ pub fn spawn_named_mob(
         }
     }
 
+    if let Some(carrying) = &mob_template.carried {
+        for tag in carrying.iter() {
+            spawn_named_entity(raws, ecs, tag, SpawnType::Carried { by: mob });
+        }
+    }
+
     Some(mob)
 }
@@ -1,10 +1,17 @@
+pub mod affixes;
+pub mod behaviors;
+pub mod classes;
 pub mod factions;
 pub mod items;
+pub mod levels;
 pub mod loot;
 pub mod mobs;
+pub mod progression;
 pub mod props;
 pub mod rawmaster;
+pub mod recipes;
 pub mod spawn_table;
+pub mod spells;
 
 use lazy_static::lazy_static;
 pub use rawmaster::*;
@@ -20,7 +27,7 @@ use super::{
     rng::{parse_dice_string, RandomGen},
     spawner::random_table::RandomTable,
     unit::*,
-    BlocksTile, BlocksVisibility, Entity, Hidden, Name, Position, Renderable,
+    BlocksTile, BlocksVisibility, Entity, Hidden, Name, Position, Renderable, SerializeMe,
 };
 
 lazy_static! {
@@ -41,6 +48,13 @@ pub fn load_raws() {
         spawn_tables: from_reader(open("raws/table_spawn.json")).expect(e),
         loot_tables: from_reader(open("raws/table_loot.json")).expect(e),
         faction_tables: from_reader(open("raws/table_faction.json")).expect(e),
+        spells: from_reader(open("raws/spells.json")).expect(e),
+        progression: from_reader(open("raws/progression.json")).expect(e),
+        classes: from_reader(open("raws/classes.json")).expect(e),
+        levels: from_reader(open("raws/levels.json")).expect(e),
+        behaviors: from_reader(open("raws/behaviors.json")).expect(e),
+        affixes: from_reader(open("raws/affixes.json")).expect(e),
+        recipes: from_reader(open("raws/recipes.json")).expect(e),
     };
 
     RAWS.lock().unwrap().load(raws);
@@ -54,6 +68,13 @@ pub struct Raws {
     pub spawn_tables: Vec<spawn_table::SpawnTableEntry>,
     pub loot_tables: Vec<loot::LootTableRaw>,
     pub faction_tables: Vec<factions::FactionInfoRaw>,
+    pub spells: Vec<spells::SpellRaw>,
+    pub progression: Vec<progression::LevelRaw>,
+    pub classes: Vec<classes::ClassRaw>,
+    pub levels: Vec<levels::LevelLayoutRaw>,
+    pub behaviors: Vec<behaviors::BehaviorRaw>,
+    pub affixes: Vec<affixes::AffixRaw>,
+    pub recipes: Vec<recipes::RecipeRaw>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -0,0 +1,36 @@
+use super::RawMaster;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct LevelRaw {
+    pub level: i32,
+    pub xp: i32,
+    pub attribute_points: i32,
+    pub skill_points: i32,
+}
+
+/// Every level has to cost some xp, or the level-up loop never ends.
+pub fn validate_progression(progression: &[LevelRaw]) -> Result<(), String> {
+    match progression.iter().find(|l| l.xp <= 0) {
+        Some(l) => Err(format!("level {} needs {} xp", l.level, l.xp)),
+        None => Ok(()),
+    }
+}
+
+/// Levels beyond the end of the table reuse its last entry.
+fn level_entry(raws: &RawMaster, level: i32) -> Option<&LevelRaw> {
+    raws.raws
+        .progression
+        .iter()
+        .find(|l| l.level == level)
+        .or_else(|| raws.raws.progression.last())
+}
+
+pub fn xp_to_next_level(raws: &RawMaster, level: i32) -> i32 {
+    level_entry(raws, level).map_or(level * 1000, |l| l.xp)
+}
+
+/// Attribute and skill points granted when leaving `level`.
+pub fn level_up_points(raws: &RawMaster, level: i32) -> (i32, i32) {
+    level_entry(raws, level).map_or((1, 1), |l| (l.attribute_points, l.skill_points))
+}
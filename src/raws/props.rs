@@ -1,12 +1,20 @@
 use super::{
-    c, get_renderable_component, mobs::LightRaw, spawn_position, BlocksTile, BlocksVisibility,
-    Door, EntryTrigger, Hidden, InflictsDamage, LightSource, Name, RawMaster, RenderableRaw,
-    SingleActivation, SpawnType, Viewshed,
+    c, get_damage_type, get_renderable_component, items::spawn_named_item, loot::get_loots,
+    mobs::LightRaw, spawn_position, BlocksTile, BlocksVisibility, Container, Door, EntryTrigger,
+    Hidden, InflictsDamage, LightSource, Name, RandomGen, RawMaster, RenderableRaw, SerializeMe,
+    SingleActivation, SpawnType, Trapped, Viewshed,
 };
+use bracket_lib::terminal::to_cp437;
 use serde::Deserialize;
-use specs::prelude::*;
+use specs::{
+    prelude::*,
+    saveload::{MarkedBuilder, SimpleMarker},
+};
 use std::collections::HashMap;
 
+const DEFAULT_LOCK: i32 = 12;
+const DEFAULT_DOOR_STRENGTH: i32 = 12;
+
 #[derive(Deserialize)]
 pub struct PropRaw {
     pub name: String,
@@ -14,9 +22,20 @@ pub struct PropRaw {
     pub hidden: Option<bool>,
     pub blocks_tile: Option<bool>,
     pub blocks_visibility: Option<bool>,
-    pub door_open: Option<bool>,
+    pub door: Option<DoorRaw>,
     pub entry_trigger: Option<EntryTriggerRaw>,
     pub light: Option<LightRaw>,
+    pub container: Option<ContainerRaw>,
+}
+
+#[derive(Deserialize)]
+pub struct DoorRaw {
+    pub open: Option<bool>,
+    pub open_glyph: String,
+    pub locked: Option<bool>,
+    pub key: Option<String>,
+    pub lock: Option<i32>,
+    pub strength: Option<i32>,
 }
 
 #[derive(Deserialize)]
@@ -24,6 +43,19 @@ pub struct EntryTriggerRaw {
     pub effects: HashMap<String, String>,
 }
 
+#[derive(Deserialize)]
+pub struct ContainerRaw {
+    /// The table in table_loot.json the container is filled from.
+    pub loot: Option<String>,
+    /// Dice rolled for how many times the loot table is rolled.
+    pub rolls: Option<String>,
+    pub locked: Option<bool>,
+    pub key: Option<String>,
+    pub lock: Option<i32>,
+    /// Same effects as an entry trigger, sprung once on opening.
+    pub trap: Option<EntryTriggerRaw>,
+}
+
 pub fn spawn_named_prop(
     raws: &RawMaster,
     ecs: &mut World,
@@ -35,7 +67,7 @@ pub fn spawn_named_prop(
     }
 
     let prop_template = &raws.raws.props[raws.prop_index[key]];
-    let mut eb = ecs.create_entity();
+    let mut eb = ecs.create_entity().marked::<SimpleMarker<SerializeMe>>();
 
     eb = spawn_position(pos, eb, key, raws);
 
@@ -62,21 +94,47 @@ pub fn spawn_named_prop(
             eb = eb.with(BlocksVisibility {})
         }
     }
-    if let Some(door_open) = prop_template.door_open {
-        eb = eb.with(Door { open: door_open });
+    if let Some(door) = &prop_template.door {
+        let open = door.open.unwrap_or(false);
+        let open_glyph = to_cp437(door.open_glyph.chars().next().unwrap());
+        let closed_glyph = prop_template
+            .renderable
+            .as_ref()
+            .map_or(open_glyph, |renderable| {
+                to_cp437(renderable.glyph.chars().next().unwrap())
+            });
+        if open {
+            if let Some(renderable) = &prop_template.renderable {
+                let mut renderable = get_renderable_component(renderable);
+                renderable.glyph = open_glyph;
+                eb = eb.with(renderable);
+            }
+        } else {
+            eb = eb.with(BlocksTile {}).with(BlocksVisibility {});
+        }
+        eb = eb.with(Door {
+            open,
+            locked: door.locked.unwrap_or(false),
+            key: door.key.clone(),
+            lock: door.lock.unwrap_or(DEFAULT_LOCK),
+            strength: door.strength.unwrap_or(DEFAULT_DOOR_STRENGTH),
+            open_glyph,
+            closed_glyph,
+        });
     }
     if let Some(entry_trigger) = &prop_template.entry_trigger {
         eb = eb.with(EntryTrigger {});
-        for effect in entry_trigger.effects.iter() {
-            match effect.0.as_str() {
-                "damage" => {
-                    eb = eb.with(InflictsDamage {
-                        damage: effect.1.parse::<i32>().unwrap(),
-                    })
-                }
-                "single_activation" => eb = eb.with(SingleActivation {}),
-                _ => {}
-            }
+        eb = spawn_trigger_effects(eb, entry_trigger);
+    }
+    if let Some(container) = &prop_template.container {
+        eb = eb.with(Container {
+            locked: container.locked.unwrap_or(false),
+            key: container.key.clone(),
+            lock: container.lock.unwrap_or(DEFAULT_LOCK),
+        });
+        if let Some(trap) = &container.trap {
+            eb = eb.with(Trapped {});
+            eb = spawn_trigger_effects(eb, trap);
         }
     }
 
@@ -92,5 +150,50 @@ pub fn spawn_named_prop(
         });
     }
 
-    Some(eb.build())
+    let entity = eb.build();
+    if let Some(container) = &prop_template.container {
+        fill_container(raws, ecs, entity, container);
+    }
+    Some(entity)
+}
+
+fn spawn_trigger_effects<'a>(
+    mut eb: EntityBuilder<'a>,
+    trigger: &EntryTriggerRaw,
+) -> EntityBuilder<'a> {
+    for effect in trigger.effects.iter() {
+        match effect.0.as_str() {
+            "damage" => {
+                eb = eb.with(InflictsDamage {
+                    damage: effect.1.parse::<i32>().unwrap(),
+                    damage_type: get_damage_type(
+                        trigger.effects.get("damage_type").map(String::as_str),
+                    ),
+                })
+            }
+            "single_activation" => eb = eb.with(SingleActivation {}),
+            _ => {}
+        }
+    }
+    eb
+}
+
+/// Rolls the container's loot table and spawns what comes up inside it.
+fn fill_container(raws: &RawMaster, ecs: &mut World, entity: Entity, container: &ContainerRaw) {
+    let table = match &container.loot {
+        Some(table) => table,
+        None => return,
+    };
+    let rolls = match &container.rolls {
+        Some(dice) => ecs.write_resource::<RandomGen>().roll_str(dice),
+        None => 1,
+    };
+    for _ in 0..rolls {
+        let loot = get_loots(raws, table, &mut ecs.write_resource::<RandomGen>());
+        if let Some((name, quantity)) = loot {
+            for _ in 0..quantity {
+                spawn_named_item(raws, ecs, &name, SpawnType::Contained { by: entity });
+            }
+        }
+    }
 }
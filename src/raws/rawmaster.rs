@@ -1,7 +1,16 @@
 use super::{
-    c, factions::Reaction, items::find_slot_for_equippable_item, items::spawn_named_item,
-    mobs::spawn_named_mob, props::spawn_named_prop, Equipped, InBackpack, Position, Raws,
-    Renderable, RenderableRaw,
+    c,
+    factions::{parse_reaction, Reaction},
+    items::find_slot_for_equippable_item,
+    items::spawn_named_item,
+    levels::validate_levels,
+    mobs::spawn_named_mob,
+    progression::validate_progression,
+    props::spawn_named_prop,
+    AreaOfEffect, AttributeType, DamageType, Equipped, GrantsStatus, Identifies, InBackpack,
+    InContainer, InflictsDamage, Position, ProvidesFood, ProvidesHealing, Ranged, Raws,
+    RemovesCurse, Renderable, RenderableRaw, Resistance, Resistances, Skills, StatusKind,
+    StatusTemplate,
 };
 use bracket_lib::terminal::to_cp437;
 use specs::prelude::*;
@@ -11,6 +20,7 @@ pub enum SpawnType {
     AtPosition { x: i32, y: i32 },
     Equipped { by: Entity },
     Carried { by: Entity },
+    Contained { by: Entity },
 }
 
 pub struct RawMaster {
@@ -19,6 +29,7 @@ pub struct RawMaster {
     pub mob_index: HashMap<String, usize>,
     pub prop_index: HashMap<String, usize>,
     pub loot_index: HashMap<String, usize>,
+    pub spell_index: HashMap<String, usize>,
     pub faction_index: HashMap<String, HashMap<String, Reaction>>,
 }
 
@@ -32,11 +43,19 @@ impl RawMaster {
                 spawn_tables: Vec::new(),
                 loot_tables: Vec::new(),
                 faction_tables: Vec::new(),
+                spells: Vec::new(),
+                progression: Vec::new(),
+                classes: Vec::new(),
+                levels: Vec::new(),
+                behaviors: Vec::new(),
+                affixes: Vec::new(),
+                recipes: Vec::new(),
             },
             item_index: HashMap::new(),
             mob_index: HashMap::new(),
             prop_index: HashMap::new(),
             loot_index: HashMap::new(),
+            spell_index: HashMap::new(),
             faction_index: HashMap::new(),
         }
     }
@@ -68,6 +87,10 @@ impl RawMaster {
             used_names.insert(prop.name.clone());
         }
 
+        for (i, spell) in self.raws.spells.iter().enumerate() {
+            self.spell_index.insert(spell.name.clone(), i);
+        }
+
         for (i, loot) in self.raws.loot_tables.iter().enumerate() {
             self.loot_index.insert(loot.name.clone(), i);
         }
@@ -81,20 +104,29 @@ impl RawMaster {
             }
         }
 
+        for recipe in self.raws.recipes.iter() {
+            let ingredients = recipe.ingredients.iter().map(|i| &i.name);
+            for name in std::iter::once(&recipe.output).chain(ingredients) {
+                if !self.item_index.contains_key(name) {
+                    println!("WARNING -  Recipe references unspecified item {}", name);
+                }
+            }
+        }
+
         for faction in self.raws.faction_tables.iter() {
             let mut reactions: HashMap<String, Reaction> = HashMap::new();
             for other in faction.responses.iter() {
-                reactions.insert(
-                    other.0.clone(),
-                    match other.1.as_str() {
-                        "ignore" => Reaction::Ignore,
-                        "flee" => Reaction::Flee,
-                        _ => Reaction::Attack,
-                    },
-                );
+                reactions.insert(other.0.clone(), parse_reaction(other.1));
             }
             self.faction_index.insert(faction.name.clone(), reactions);
         }
+
+        if let Err(e) = validate_levels(&self.raws.levels) {
+            panic!("Invalid level layout in raws/levels.json: {}", e);
+        }
+        if let Err(e) = validate_progression(&self.raws.progression) {
+            panic!("Invalid progression in raws/progression.json: {}", e);
+        }
     }
 }
 
@@ -123,6 +155,7 @@ pub fn spawn_position<'a>(
     match spawn {
         SpawnType::AtPosition { x, y } => entity.with(Position { x, y }),
         SpawnType::Carried { by } => entity.with(InBackpack { owner: by }),
+        SpawnType::Contained { by } => entity.with(InContainer { owner: by }),
         SpawnType::Equipped { by } => entity.with(Equipped {
             owner: by,
             slot: find_slot_for_equippable_item(tag, raws),
@@ -130,6 +163,160 @@ pub fn spawn_position<'a>(
     }
 }
 
+fn parse_amount_duration(value: &str) -> (i32, i32) {
+    let mut parts = value.split(',');
+    let amount = parts.next().unwrap().trim().parse::<i32>().unwrap();
+    let duration = parts.next().unwrap_or("1").trim().parse::<i32>().unwrap();
+    (amount, duration)
+}
+
+pub fn parse_attribute(name: &str) -> Option<AttributeType> {
+    match name {
+        "might" => Some(AttributeType::Might),
+        "fitness" => Some(AttributeType::Fitness),
+        "quickness" => Some(AttributeType::Quickness),
+        "intelligence" => Some(AttributeType::Intelligence),
+        _ => None,
+    }
+}
+
+fn parse_status(effect_name: &str, value: &str) -> Option<StatusTemplate> {
+    let attribute = parse_attribute(effect_name);
+
+    let (kind, duration) = match (effect_name, attribute) {
+        ("confusion", _) => (StatusKind::Confusion, value.parse::<i32>().unwrap()),
+        ("stun", _) => (StatusKind::Stun, value.parse::<i32>().unwrap()),
+        ("poison" | "bleed" | "regeneration" | "slow" | "haste", _) | (_, Some(_)) => {
+            let (amount, duration) = parse_amount_duration(value);
+            let kind = match (effect_name, attribute) {
+                (_, Some(attribute)) => StatusKind::AttributeModifier { attribute, amount },
+                ("poison", _) => StatusKind::Poison { damage: amount },
+                ("bleed", _) => StatusKind::Bleed { damage: amount },
+                ("regeneration", _) => StatusKind::Regeneration { heal: amount },
+                ("slow", _) => StatusKind::Slow {
+                    initiative_penalty: amount,
+                },
+                _ => StatusKind::Haste {
+                    initiative_bonus: amount,
+                },
+            };
+            (kind, duration)
+        }
+        _ => return None,
+    };
+
+    Some(StatusTemplate { kind, duration })
+}
+
+pub fn spawn_effects<'a>(
+    mut eb: EntityBuilder<'a>,
+    effects: &HashMap<String, String>,
+) -> EntityBuilder<'a> {
+    let mut statuses = Vec::new();
+
+    // Sorted so statuses are granted, and logged, in the same order every run.
+    let mut sorted: Vec<(&String, &String)> = effects.iter().collect();
+    sorted.sort();
+    for effect in sorted {
+        let effect_name = effect.0.as_str();
+        if let Some(status) = parse_status(effect_name, effect.1) {
+            statuses.push(status);
+            continue;
+        }
+        match effect_name {
+            "provides_healing" => {
+                eb = eb.with(ProvidesHealing {
+                    heal_amount: effect.1.parse::<i32>().unwrap(),
+                })
+            }
+            "food" => eb = eb.with(ProvidesFood {}),
+            "identify" => eb = eb.with(Identifies {}),
+            "remove_curse" => eb = eb.with(RemovesCurse {}),
+            "ranged" => {
+                eb = eb.with(Ranged {
+                    range: effect.1.parse::<i32>().unwrap(),
+                })
+            }
+            "damage" => {
+                eb = eb.with(InflictsDamage {
+                    damage: effect.1.parse::<i32>().unwrap(),
+                    damage_type: get_damage_type(effects.get("damage_type").map(String::as_str)),
+                })
+            }
+            "damage_type" => {}
+            "area_of_effect" => {
+                eb = eb.with(AreaOfEffect {
+                    radius: effect.1.parse::<i32>().unwrap(),
+                })
+            }
+            _ => {
+                println!("Warning: effect {} not implemented.", effect_name);
+            }
+        }
+    }
+
+    if !statuses.is_empty() {
+        eb = eb.with(GrantsStatus { effects: statuses });
+    }
+
+    eb
+}
+
+/// Untyped damage in the raws is treated as a plain blow.
+pub fn get_damage_type(name: Option<&str>) -> DamageType {
+    match name {
+        None => DamageType::Bludgeoning,
+        Some(name) => DamageType::parse(name).unwrap_or_else(|| {
+            println!("Warning: unknown damage type {}", name);
+            DamageType::Bludgeoning
+        }),
+    }
+}
+
+pub fn get_resistances_component(raw: &HashMap<String, String>) -> Resistances {
+    let mut resistances = Resistances::default();
+
+    for (damage_type, resistance) in raw.iter() {
+        match (
+            DamageType::parse(damage_type),
+            Resistance::parse(resistance),
+        ) {
+            (Some(damage_type), Some(resistance)) => {
+                resistances.table.insert(damage_type, resistance);
+            }
+            _ => println!("Unknown resistance : {} {}", damage_type, resistance),
+        }
+    }
+
+    resistances
+}
+
+pub fn get_skills_component(raw_skills: &Option<HashMap<String, i32>>) -> Skills {
+    let mut skills = Skills::default();
+
+    if let Some(raw_skills) = raw_skills {
+        for s in raw_skills.iter() {
+            match s.0.as_str() {
+                "Melee" => {
+                    skills.melee = *s.1;
+                }
+                "Ranged" => {
+                    skills.ranged = *s.1;
+                }
+                "Magic" => {
+                    skills.magic = *s.1;
+                }
+                "Defense" => {
+                    skills.defense = *s.1;
+                }
+                _ => println!("Unknown skill : {}", s.0),
+            }
+        }
+    }
+
+    skills
+}
+
 pub fn get_renderable_component(renderable: &RenderableRaw) -> Renderable {
     Renderable {
         glyph: to_cp437(renderable.glyph.chars().next().unwrap()),
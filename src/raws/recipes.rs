@@ -0,0 +1,172 @@
+use serde::Deserialize;
+
+use super::{parse_attribute, Attributes, RandomGen, RawMaster, Skills};
+
+/// How far past the difficulty a roll has to land for a better result.
+const FINE_MARGIN: i32 = 5;
+const MASTERWORK_MARGIN: i32 = 10;
+
+#[derive(Deserialize)]
+pub struct RecipeRaw {
+    /// The item the recipe makes.
+    pub output: String,
+    pub quantity: Option<i32>,
+    /// The vendor category of the workshop that can make it.
+    pub workshop: String,
+    pub ingredients: Vec<IngredientRaw>,
+    /// "melee", "ranged", "magic" or "defense", added to the quality roll.
+    pub skill: Option<String>,
+    /// Whose bonus is added to the quality roll.
+    pub attribute: Option<String>,
+    /// Without one, the recipe always comes out standard.
+    pub difficulty: Option<i32>,
+    pub quality: Option<QualityRaw>,
+}
+
+#[derive(Deserialize)]
+pub struct IngredientRaw {
+    pub name: String,
+    pub quantity: i32,
+}
+
+/// Affixes from raws/affixes.json put on the result for each quality.
+#[derive(Deserialize)]
+pub struct QualityRaw {
+    pub shoddy: Option<String>,
+    pub fine: Option<String>,
+    pub masterwork: Option<String>,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum CraftQuality {
+    Shoddy,
+    Standard,
+    Fine,
+    Masterwork,
+}
+
+impl RecipeRaw {
+    pub fn ingredients_text(&self) -> String {
+        self.ingredients
+            .iter()
+            .map(|ingredient| format!("{} {}", ingredient.quantity, ingredient.name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn quality_affix(&self, quality: CraftQuality) -> Option<&String> {
+        let affixes = self.quality.as_ref()?;
+        match quality {
+            CraftQuality::Shoddy => affixes.shoddy.as_ref(),
+            CraftQuality::Standard => None,
+            CraftQuality::Fine => affixes.fine.as_ref(),
+            CraftQuality::Masterwork => affixes.masterwork.as_ref(),
+        }
+    }
+
+    /// Rolls d20 plus the recipe's attribute bonus and skill against its
+    /// difficulty. Returns the quality along with the roll, if one was made.
+    pub fn roll_quality(
+        &self,
+        rng: &mut RandomGen,
+        attributes: Option<&Attributes>,
+        skills: Option<&Skills>,
+    ) -> (CraftQuality, Option<(i32, i32)>) {
+        let difficulty = match self.difficulty {
+            Some(difficulty) => difficulty,
+            None => return (CraftQuality::Standard, None),
+        };
+
+        let attribute_bonus = match (&self.attribute, attributes) {
+            (Some(name), Some(attributes)) => match parse_attribute(&name.to_lowercase()) {
+                Some(attribute) => attributes.attribute(attribute).bonus(),
+                None => 0,
+            },
+            _ => 0,
+        };
+        let skill_bonus = match (self.skill.as_deref(), skills) {
+            (Some("melee"), Some(skills)) => skills.melee,
+            (Some("ranged"), Some(skills)) => skills.ranged,
+            (Some("magic"), Some(skills)) => skills.magic,
+            (Some("defense"), Some(skills)) => skills.defense,
+            _ => 0,
+        };
+
+        let natural_roll = rng.roll_dice(1, 20);
+        let roll = natural_roll + attribute_bonus + skill_bonus;
+        let quality = if natural_roll == 20 || roll >= difficulty + MASTERWORK_MARGIN {
+            CraftQuality::Masterwork
+        } else if roll >= difficulty + FINE_MARGIN {
+            CraftQuality::Fine
+        } else if roll >= difficulty {
+            CraftQuality::Standard
+        } else {
+            CraftQuality::Shoddy
+        };
+        (quality, Some((roll, difficulty)))
+    }
+}
+
+/// The recipes the workshops of a vendor with these categories can make.
+pub fn get_recipes<'a>(categories: &[String], raws: &'a RawMaster) -> Vec<&'a RecipeRaw> {
+    raws.raws
+        .recipes
+        .iter()
+        .filter(|recipe| categories.contains(&recipe.workshop))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEED: u64 = 3;
+
+    fn recipe(difficulty: Option<i32>) -> RecipeRaw {
+        RecipeRaw {
+            output: "Longsword".to_string(),
+            quantity: None,
+            workshop: "weapon".to_string(),
+            ingredients: vec![],
+            skill: Some("melee".to_string()),
+            attribute: None,
+            difficulty,
+            quality: None,
+        }
+    }
+
+    #[test]
+    fn quality_follows_the_margin_over_the_difficulty() {
+        let skills = Skills::new(3, 0, 0, 0);
+        let natural_roll = RandomGen::new(SEED).roll_dice(1, 20);
+        assert!(
+            natural_roll < 20,
+            "pick a seed that doesn't roll a natural 20"
+        );
+        let roll = natural_roll + skills.melee;
+
+        let cases = [
+            (roll + 1, CraftQuality::Shoddy),
+            (roll, CraftQuality::Standard),
+            (roll - FINE_MARGIN + 1, CraftQuality::Standard),
+            (roll - FINE_MARGIN, CraftQuality::Fine),
+            (roll - MASTERWORK_MARGIN + 1, CraftQuality::Fine),
+            (roll - MASTERWORK_MARGIN, CraftQuality::Masterwork),
+        ];
+        for (difficulty, expected) in cases {
+            let mut rng = RandomGen::new(SEED);
+            let (quality, rolled) =
+                recipe(Some(difficulty)).roll_quality(&mut rng, None, Some(&skills));
+            assert!(quality == expected, "difficulty {}", difficulty);
+            assert_eq!(rolled, Some((roll, difficulty)));
+        }
+    }
+
+    #[test]
+    fn recipes_without_a_difficulty_come_out_standard() {
+        let mut rng = RandomGen::new(SEED);
+        let (quality, rolled) = recipe(None).roll_quality(&mut rng, None, None);
+        assert!(quality == CraftQuality::Standard);
+        assert_eq!(rolled, None);
+    }
+}
@@ -0,0 +1,74 @@
+use super::{
+    rawmaster::spawn_effects, KnownSpell, KnownSpells, Name, RawMaster, SerializeMe, SpellTemplate,
+};
+use serde::Deserialize;
+use specs::{
+    prelude::*,
+    saveload::{MarkedBuilder, SimpleMarker},
+};
+use std::collections::HashMap;
+
+#[derive(Deserialize, Debug)]
+pub struct SpellRaw {
+    pub name: String,
+    pub mana_cost: i32,
+    pub difficulty: Option<i32>,
+    pub effects: HashMap<String, String>,
+}
+
+pub fn spawn_named_spell(raws: &RawMaster, ecs: &mut World, key: &str) -> Option<Entity> {
+    if !raws.spell_index.contains_key(key) {
+        return None;
+    }
+
+    let spell_template = &raws.raws.spells[raws.spell_index[key]];
+    let mut eb = ecs.create_entity().marked::<SimpleMarker<SerializeMe>>();
+
+    eb = eb.with(SpellTemplate {
+        mana_cost: spell_template.mana_cost,
+        difficulty: spell_template.difficulty.unwrap_or(0),
+    });
+    eb = eb.with(Name {
+        name: spell_template.name.clone(),
+    });
+    eb = spawn_effects(eb, &spell_template.effects);
+
+    Some(eb.build())
+}
+
+pub fn spawn_all_spells(raws: &RawMaster, ecs: &mut World) {
+    for spell in raws.raws.spells.iter() {
+        spawn_named_spell(raws, ecs, &spell.name);
+    }
+}
+
+pub fn known_spells(raws: &RawMaster, names: &[String]) -> KnownSpells {
+    let mut known = KnownSpells::default();
+
+    for name in names.iter() {
+        if !raws.spell_index.contains_key(name) {
+            println!("Warning: unknown spell {}", name);
+            continue;
+        }
+        let spell = &raws.raws.spells[raws.spell_index[name]];
+        known.spells.push(KnownSpell {
+            display_name: spell.name.clone(),
+            mana_cost: spell.mana_cost,
+        });
+    }
+
+    known
+}
+
+pub fn find_spell_entity(ecs: &World, name: &str) -> Option<Entity> {
+    let names = ecs.read_storage::<Name>();
+    let spell_templates = ecs.read_storage::<SpellTemplate>();
+
+    for (entity, spell_name, _) in (&ecs.entities(), &names, &spell_templates).join() {
+        if spell_name.name == name {
+            return Some(entity);
+        }
+    }
+
+    None
+}
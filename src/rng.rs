@@ -1,6 +1,7 @@
 use rand::{Rng, RngCore, SeedableRng};
 use rand_xorshift::XorShiftRng;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub struct DiceType {
@@ -29,6 +30,7 @@ impl Default for DiceType {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct RandomGen {
     rng: XorShiftRng,
 }
@@ -40,6 +42,11 @@ impl RandomGen {
         }
     }
 
+    pub fn from_run_seed(seed: u64, salt: i32) -> Self {
+        let mut rng = Self::new(seed ^ (salt as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        Self::new(rng.next_u64())
+    }
+
     pub fn range<T>(&mut self, min: T, max: T) -> T
     where
         T: rand::distributions::uniform::SampleUniform + PartialOrd,
@@ -100,7 +107,7 @@ pub fn parse_dice_string(dice: &str) -> DiceType {
     result
 }
 
-fn get_seed() -> u64 {
+pub fn get_seed() -> u64 {
     let mut buf = [0u8; 8];
     if getrandom::getrandom(&mut buf).is_ok() {
         u64::from_be_bytes(buf)
@@ -0,0 +1,453 @@
+use super::{
+    action::*,
+    effect::*,
+    item::*,
+    logger::{clone_log, restore_log},
+    map::{master::MasterMap, Map},
+    props::*,
+    rng::RandomGen,
+    unit::*,
+    BlocksTile, BlocksVisibility, Hidden, Name, OtherLevelPosition, ParticleLifetime, Position,
+    Renderable, SerializationHelper, SerializeMe, TurnCounter,
+};
+use bracket_lib::terminal::Point;
+use specs::{
+    prelude::*,
+    saveload::{
+        DeserializeComponents, MarkedBuilder, SerializeComponents, SimpleMarker,
+        SimpleMarkerAllocator,
+    },
+};
+use std::{
+    convert::Infallible,
+    fs::{self, File},
+    io::Write,
+    path::Path,
+};
+
+const SAVE_PATH: &str = "./savegame.json";
+
+macro_rules! serialize_individually {
+    ($ecs:expr, $ser:expr, $data:expr, $( $type:ty),*) => {
+        $(
+        SerializeComponents::<Infallible, SimpleMarker<SerializeMe>>::serialize(
+            &( $ecs.read_storage::<$type>(), ),
+            &$data.0,
+            &$data.1,
+            &mut $ser,
+        )
+        .expect("Unable to serialize");
+        )*
+    };
+}
+
+macro_rules! deserialize_individually {
+    ($ecs:expr, $de:expr, $data:expr, $( $type:ty),*) => {
+        $(
+        DeserializeComponents::<Infallible, _>::deserialize(
+            &mut ( &mut $ecs.write_storage::<$type>(), ),
+            &$data.0,
+            &mut $data.1,
+            &mut $data.2,
+            &mut $de,
+        )
+        .expect("Unable to deserialize");
+        )*
+    };
+}
+
+pub fn does_save_exist() -> bool {
+    Path::new(SAVE_PATH).exists()
+}
+
+pub fn delete_save() {
+    if does_save_exist() {
+        fs::remove_file(SAVE_PATH).expect("Unable to delete file");
+    }
+}
+
+pub fn save_game(ecs: &mut World) {
+    let writer = File::create(SAVE_PATH).expect("Unable to create save file");
+    write_save(ecs, writer);
+}
+
+fn write_save<W: Write>(ecs: &mut World, writer: W) {
+    let map = ecs.get_mut::<Map>().unwrap().clone();
+    let master = ecs.get_mut::<MasterMap>().unwrap().clone();
+    let rng = ecs.get_mut::<RandomGen>().unwrap().clone();
+    let turns = *ecs.fetch::<TurnCounter>();
+    let reputation = (*ecs.fetch::<Reputation>()).clone();
+    let identification = (*ecs.fetch::<Identification>()).clone();
+    let helper = ecs
+        .create_entity()
+        .with(SerializationHelper {
+            map,
+            master,
+            rng,
+            turns,
+            reputation,
+            identification,
+            log: clone_log(),
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+
+    {
+        let data = (
+            ecs.entities(),
+            ecs.read_storage::<SimpleMarker<SerializeMe>>(),
+        );
+
+        let mut serializer = serde_json::Serializer::new(writer);
+        serialize_individually!(
+            ecs,
+            serializer,
+            data,
+            Renderable,
+            BlocksTile,
+            Name,
+            Position,
+            OtherLevelPosition,
+            ParticleLifetime,
+            Hidden,
+            BlocksVisibility,
+            Player,
+            Viewshed,
+            Quips,
+            EntityMoved,
+            SufferDamage,
+            Attribute,
+            Attributes,
+            Skills,
+            KnownSpells,
+            Pool,
+            Pools,
+            HungerClock,
+            LevelUpPoints,
+            NaturalAttack,
+            NaturalProperty,
+            LootTable,
+            Initiative,
+            MyTurn,
+            Faction,
+            CharacterClass,
+            MovementMode,
+            Chasing,
+            Morale,
+            SquadLeader,
+            SquadMember,
+            Scattering,
+            Behavior,
+            Vendor,
+            WantsToMelee,
+            WantsToShoot,
+            WantsToPickupItem,
+            WantsToUseItem,
+            WantsToCastSpell,
+            WantsToDropItem,
+            WantsToRemoveItem,
+            WantsToApproach,
+            WantsToFlee,
+            WantsToOpenDoor,
+            WantsToCloseDoor,
+            WantsToBashDoor,
+            WantsToOpenContainer,
+            Item,
+            InBackpack,
+            InContainer,
+            Consumable,
+            Equippable,
+            Equipped,
+            MeleeWeapon,
+            RangedWeapon,
+            Ammunition,
+            Wearable,
+            Shield,
+            AttributeBonus,
+            DamageBonus,
+            Cursed,
+            Stackable,
+            EquipmentChanged,
+            ProvidesHealing,
+            ProvidesFood,
+            Identifies,
+            RemovesCurse,
+            SpellTemplate,
+            Ranged,
+            EntryTrigger,
+            InflictsDamage,
+            AreaOfEffect,
+            GrantsStatus,
+            StatusEffect,
+            Resistances,
+            SingleActivation,
+            Door,
+            OpensDoors,
+            LightSource,
+            Container,
+            Trapped,
+            SerializationHelper
+        );
+    }
+
+    ecs.delete_entity(helper).expect("Unable to delete helper");
+}
+
+pub fn load_game(ecs: &mut World) {
+    let data = fs::read_to_string(SAVE_PATH).expect("Unable to read save file");
+    read_save(ecs, &data);
+}
+
+fn read_save(ecs: &mut World, data: &str) {
+    {
+        let mut to_delete = Vec::new();
+        for e in ecs.entities().join() {
+            to_delete.push(e);
+        }
+        for del in to_delete.iter() {
+            ecs.delete_entity(*del).expect("Deletion failed");
+        }
+    }
+
+    let mut de = serde_json::Deserializer::from_str(data);
+
+    {
+        let mut d = (
+            &mut ecs.entities(),
+            &mut ecs.write_storage::<SimpleMarker<SerializeMe>>(),
+            &mut ecs.write_resource::<SimpleMarkerAllocator<SerializeMe>>(),
+        );
+
+        deserialize_individually!(
+            ecs,
+            de,
+            d,
+            Renderable,
+            BlocksTile,
+            Name,
+            Position,
+            OtherLevelPosition,
+            ParticleLifetime,
+            Hidden,
+            BlocksVisibility,
+            Player,
+            Viewshed,
+            Quips,
+            EntityMoved,
+            SufferDamage,
+            Attribute,
+            Attributes,
+            Skills,
+            KnownSpells,
+            Pool,
+            Pools,
+            HungerClock,
+            LevelUpPoints,
+            NaturalAttack,
+            NaturalProperty,
+            LootTable,
+            Initiative,
+            MyTurn,
+            Faction,
+            CharacterClass,
+            MovementMode,
+            Chasing,
+            Morale,
+            SquadLeader,
+            SquadMember,
+            Scattering,
+            Behavior,
+            Vendor,
+            WantsToMelee,
+            WantsToShoot,
+            WantsToPickupItem,
+            WantsToUseItem,
+            WantsToCastSpell,
+            WantsToDropItem,
+            WantsToRemoveItem,
+            WantsToApproach,
+            WantsToFlee,
+            WantsToOpenDoor,
+            WantsToCloseDoor,
+            WantsToBashDoor,
+            WantsToOpenContainer,
+            Item,
+            InBackpack,
+            InContainer,
+            Consumable,
+            Equippable,
+            Equipped,
+            MeleeWeapon,
+            RangedWeapon,
+            Ammunition,
+            Wearable,
+            Shield,
+            AttributeBonus,
+            DamageBonus,
+            Cursed,
+            Stackable,
+            EquipmentChanged,
+            ProvidesHealing,
+            ProvidesFood,
+            Identifies,
+            RemovesCurse,
+            SpellTemplate,
+            Ranged,
+            EntryTrigger,
+            InflictsDamage,
+            AreaOfEffect,
+            GrantsStatus,
+            StatusEffect,
+            Resistances,
+            SingleActivation,
+            Door,
+            OpensDoors,
+            LightSource,
+            Container,
+            Trapped,
+            SerializationHelper
+        );
+    }
+
+    let mut to_delete: Option<Entity> = None;
+    {
+        let entities = ecs.entities();
+        let helpers = ecs.read_storage::<SerializationHelper>();
+        let players = ecs.read_storage::<Player>();
+        let positions = ecs.read_storage::<Position>();
+        let mut viewsheds = ecs.write_storage::<Viewshed>();
+
+        for (e, helper) in (&entities, &helpers).join() {
+            *ecs.write_resource::<Map>() = helper.map.clone();
+            *ecs.write_resource::<MasterMap>() = helper.master.clone();
+            *ecs.write_resource::<RandomGen>() = helper.rng.clone();
+            *ecs.write_resource::<TurnCounter>() = helper.turns;
+            *ecs.write_resource::<Reputation>() = helper.reputation.clone();
+            *ecs.write_resource::<Identification>() = helper.identification.clone();
+            restore_log(&mut helper.log.clone());
+            to_delete = Some(e);
+        }
+
+        for (e, _, pos, viewshed) in (&entities, &players, &positions, &mut viewsheds).join() {
+            *ecs.write_resource::<Point>() = pos.into_point();
+            *ecs.write_resource::<Entity>() = e;
+            viewshed.dirty = true;
+        }
+    }
+
+    if let Some(helper) = to_delete {
+        ecs.delete_entity(helper).expect("Unable to delete helper");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::new_state, *};
+
+    fn named(ecs: &World, name: &str) -> Entity {
+        (&ecs.entities(), &ecs.read_storage::<Name>())
+            .join()
+            .find(|(_, n)| n.name == name)
+            .map(|(entity, _)| entity)
+            .unwrap_or_else(|| panic!("{} is missing", name))
+    }
+
+    fn spawn(ecs: &mut World, name: &str) -> Entity {
+        ecs.create_entity()
+            .with(Name::new(name))
+            .marked::<SimpleMarker<SerializeMe>>()
+            .build()
+    }
+
+    #[test]
+    fn entity_links_survive_a_round_trip() {
+        let mut gs = new_state(Some(1));
+        let ecs = &mut gs.ecs;
+
+        let player = spawn(ecs, "Player");
+        ecs.write_storage::<Player>()
+            .insert(player, Player {})
+            .expect("Unable to insert");
+        let leader = spawn(ecs, "Leader");
+        let chest = spawn(ecs, "Chest");
+        let carried = spawn(ecs, "Carried");
+        let worn = spawn(ecs, "Worn");
+        let stored = spawn(ecs, "Stored");
+        let follower = spawn(ecs, "Follower");
+        let status = spawn(ecs, "Status");
+
+        ecs.write_storage::<InBackpack>()
+            .insert(carried, InBackpack { owner: player })
+            .expect("Unable to insert");
+        ecs.write_storage::<Equipped>()
+            .insert(
+                worn,
+                Equipped {
+                    owner: player,
+                    slot: EquipmentSlot::Melee,
+                },
+            )
+            .expect("Unable to insert");
+        ecs.write_storage::<InContainer>()
+            .insert(stored, InContainer { owner: chest })
+            .expect("Unable to insert");
+        ecs.write_storage::<SquadMember>()
+            .insert(
+                follower,
+                SquadMember {
+                    leader,
+                    slot: Point::new(1, 0),
+                },
+            )
+            .expect("Unable to insert");
+        ecs.write_storage::<StatusEffect>()
+            .insert(
+                status,
+                StatusEffect {
+                    target: follower,
+                    kind: StatusKind::Stun,
+                    duration: 3,
+                },
+            )
+            .expect("Unable to insert");
+
+        let mut save = Vec::new();
+        write_save(ecs, &mut save);
+        read_save(ecs, std::str::from_utf8(&save).unwrap());
+
+        let player = named(ecs, "Player");
+        assert!(ecs.read_storage::<Player>().contains(player));
+
+        let owner = ecs
+            .read_storage::<InBackpack>()
+            .get(named(ecs, "Carried"))
+            .unwrap()
+            .owner;
+        assert_eq!(owner, player);
+        let owner = ecs
+            .read_storage::<Equipped>()
+            .get(named(ecs, "Worn"))
+            .unwrap()
+            .owner;
+        assert_eq!(owner, player);
+        let owner = ecs
+            .read_storage::<InContainer>()
+            .get(named(ecs, "Stored"))
+            .unwrap()
+            .owner;
+        assert_eq!(owner, named(ecs, "Chest"));
+        let follower = named(ecs, "Follower");
+        let leader = ecs
+            .read_storage::<SquadMember>()
+            .get(follower)
+            .unwrap()
+            .leader;
+        assert_eq!(leader, named(ecs, "Leader"));
+        let target = ecs
+            .read_storage::<StatusEffect>()
+            .get(named(ecs, "Status"))
+            .unwrap()
+            .target;
+        assert_eq!(target, follower);
+    }
+}
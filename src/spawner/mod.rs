@@ -1,23 +1,37 @@
 use super::rng::RandomGen;
 use bracket_lib::terminal::{to_cp437, Rect};
-use specs::prelude::*;
-use std::collections::HashMap;
+use specs::{
+    prelude::*,
+    saveload::{MarkedBuilder, SimpleMarker},
+};
 
 const MAX_SPAWNS: i32 = 6;
 
 use super::{
     colors::*,
-    item::EquipmentChanged,
+    item::{EquipmentChanged, Identification, InContainer},
     map::{tiles::Surface, Map},
     props::LightSource,
-    raws::{spawn_named_entity, spawn_table::get_spawn_table_for_depth, SpawnType, RAWS},
-    unit::{Attributes, Faction, Initiative, Player, Pools, Skills, Viewshed},
-    Name, Position, Renderable,
+    raws::{
+        affixes::enchant_item, classes::find_class, factions::parse_reaction, get_skills_component,
+        spawn_named_entity, spawn_table::get_spawn_table_for_depth, spells::known_spells,
+        SpawnType, RAWS,
+    },
+    unit::{
+        Attribute, Attributes, CharacterClass, Faction, HungerClock, Initiative, Player, Pools,
+        Viewshed,
+    },
+    BlocksTile, Name, Position, Renderable, SerializeMe,
 };
 
 pub mod random_table;
 
-pub fn spawn_in_room(map: &Map, room: &Rect, spawn_list: &mut Vec<(usize, String)>) {
+pub fn spawn_in_room(
+    map: &Map,
+    room: &Rect,
+    spawn_list: &mut Vec<(usize, String)>,
+    rng: &mut RandomGen,
+) {
     let mut possible_targets = Vec::<usize>::new();
 
     for y in room.y1..room.y2 {
@@ -29,30 +43,28 @@ pub fn spawn_in_room(map: &Map, room: &Rect, spawn_list: &mut Vec<(usize, String
         }
     }
 
-    spawn_in_region(&possible_targets, map.depth, spawn_list);
+    spawn_in_region(&possible_targets, map.depth, spawn_list, rng);
 }
 
-pub fn spawn_in_region(area: &[usize], depth: i32, spawn_list: &mut Vec<(usize, String)>) {
+pub fn spawn_in_region(
+    area: &[usize],
+    depth: i32,
+    spawn_list: &mut Vec<(usize, String)>,
+    rng: &mut RandomGen,
+) {
     let spawn_table = get_spawn_table_for_depth(&RAWS.lock().unwrap(), depth);
-    let mut rng = RandomGen::default();
-    let mut spawn_points: HashMap<usize, String> = HashMap::new();
     let mut areas: Vec<usize> = Vec::from(area);
-    {
-        let num_spawns = i32::min(areas.len() as i32, rng.range(-2, MAX_SPAWNS - 2) + depth);
-        if num_spawns <= 0 {
-            return;
-        }
 
-        for _ in 0..num_spawns {
-            let array_index = rng.range(0, areas.len());
-            let map_idx = areas[array_index];
-            spawn_points.insert(map_idx, spawn_table.roll());
-            areas.remove(array_index);
-        }
+    let num_spawns = i32::min(areas.len() as i32, rng.range(-2, MAX_SPAWNS - 2) + depth);
+    if num_spawns <= 0 {
+        return;
     }
 
-    for spawn in spawn_points.iter() {
-        spawn_list.push((*spawn.0, spawn.1.to_string()));
+    for _ in 0..num_spawns {
+        let array_index = rng.range(0, areas.len());
+        let map_idx = areas[array_index];
+        spawn_list.push((map_idx, spawn_table.roll(rng)));
+        areas.remove(array_index);
     }
 }
 
@@ -60,15 +72,21 @@ pub fn spawn_entity(ecs: &mut World, spawn: &(&usize, &String)) {
     let map = ecs.fetch::<Map>();
     let x = *spawn.0 as i32 % map.width;
     let y = *spawn.0 as i32 / map.width;
+    let depth = map.depth;
     std::mem::drop(map);
 
-    let spawn_result = spawn_named_entity(
-        &RAWS.lock().unwrap(),
-        ecs,
-        spawn.1,
-        SpawnType::AtPosition { x, y },
-    );
-    if spawn_result.is_some() {
+    let raws = RAWS.lock().unwrap();
+    let spawn_result = spawn_named_entity(&raws, ecs, spawn.1, SpawnType::AtPosition { x, y });
+    if let Some(entity) = spawn_result {
+        enchant_item(&raws, ecs, entity, depth);
+        let contents: Vec<Entity> = (&ecs.entities(), &ecs.read_storage::<InContainer>())
+            .join()
+            .filter(|(_, held)| held.owner == entity)
+            .map(|(item, _)| item)
+            .collect();
+        for item in contents {
+            enchant_item(&raws, ecs, item, depth);
+        }
         return;
     }
 
@@ -77,8 +95,46 @@ pub fn spawn_entity(ecs: &mut World, spawn: &(&usize, &String)) {
     }
 }
 
-pub fn build_player_entity(ecs: &mut World, x: i32, y: i32) -> Entity {
-    let attributes = Attributes::default();
+/// The choices made on the character creation screen.
+pub struct PlayerBuild {
+    pub name: String,
+    pub class: String,
+    pub attributes: [i32; 4],
+}
+
+impl Default for PlayerBuild {
+    fn default() -> Self {
+        Self {
+            name: "Player".to_string(),
+            class: "Samurai".to_string(),
+            attributes: [Attribute::default().base; 4],
+        }
+    }
+}
+
+pub fn build_player_entity(ecs: &mut World, x: i32, y: i32, build: &PlayerBuild) -> Entity {
+    let raws = &RAWS.lock().unwrap();
+    let class = find_class(raws, &build.class).expect("Unknown player class");
+
+    let [might, fitness, quickness, intelligence] = build.attributes;
+    let attributes = Attributes::new(
+        Attribute::new(might),
+        Attribute::new(fitness),
+        Attribute::new(quickness),
+        Attribute::new(intelligence),
+        1,
+    );
+    let mut pools = Pools::new_player(attributes);
+    pools.money = class.gold;
+
+    let mut faction = Faction::new("Player");
+    if let Some(standing) = &class.faction_standing {
+        for (other, reaction) in standing.iter() {
+            faction
+                .standing
+                .insert(other.clone(), parse_reaction(reaction));
+        }
+    }
 
     let player = ecs
         .create_entity()
@@ -90,60 +146,42 @@ pub fn build_player_entity(ecs: &mut World, x: i32, y: i32) -> Entity {
             render_order: 0,
         })
         .with(Player {})
+        .with(BlocksTile {})
         .with(Viewshed::new(8))
-        .with(Name::new("Player"))
+        .with(Name::new(&build.name))
+        .with(CharacterClass {
+            name: class.name.clone(),
+        })
         .with(attributes)
-        .with(Pools::new_player(attributes))
-        .with(Skills::new(1, 1, 1))
+        .with(pools)
+        .with(get_skills_component(&class.skills))
+        .with(known_spells(
+            raws,
+            class.spells.as_deref().unwrap_or_default(),
+        ))
         .with(LightSource {
             color: c(YELLOW5),
             range: 8,
         })
         .with(Initiative { current: 0 })
-        .with(Faction {
-            name: "Player".to_string(),
-        })
+        .with(HungerClock::new())
+        .with(faction)
         .with(EquipmentChanged {})
+        .marked::<SimpleMarker<SerializeMe>>()
         .build();
 
-    spawn_named_entity(
-        &RAWS.lock().unwrap(),
-        ecs,
-        "Rusty Longsword",
-        SpawnType::Equipped { by: player },
-    );
-
-    spawn_named_entity(
-        &RAWS.lock().unwrap(),
-        ecs,
-        "Stained Tunic",
-        SpawnType::Equipped { by: player },
-    );
-    spawn_named_entity(
-        &RAWS.lock().unwrap(),
-        ecs,
-        "Torn Trousers",
-        SpawnType::Equipped { by: player },
-    );
-    spawn_named_entity(
-        &RAWS.lock().unwrap(),
-        ecs,
-        "Old Boots",
-        SpawnType::Equipped { by: player },
-    );
+    for item in class.equipped.iter() {
+        spawn_named_entity(raws, ecs, item, SpawnType::Equipped { by: player });
+    }
+    for item in class.carried.iter() {
+        spawn_named_entity(raws, ecs, item, SpawnType::Carried { by: player });
+    }
 
-    spawn_named_entity(
-        &RAWS.lock().unwrap(),
-        ecs,
-        "Health Potion",
-        SpawnType::Carried { by: player },
-    );
-    spawn_named_entity(
-        &RAWS.lock().unwrap(),
-        ecs,
-        "Health Potion",
-        SpawnType::Carried { by: player },
-    );
+    // Everyone knows what they packed for the journey.
+    let mut identification = ecs.write_resource::<Identification>();
+    for item in class.equipped.iter().chain(class.carried.iter()) {
+        identification.identify(item);
+    }
 
     player
 }
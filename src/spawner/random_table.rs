@@ -38,9 +38,7 @@ impl RandomTable {
         self
     }
 
-    pub fn roll(&self) -> String {
-        let mut rng = RandomGen::default();
-
+    pub fn roll(&self, rng: &mut RandomGen) -> String {
         if self.total_weight == 0 {
             return "None".to_string();
         }
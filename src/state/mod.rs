@@ -1,41 +1,58 @@
 use super::{
-    action::{WantsToDropItem, WantsToRemoveItem, WantsToUseItem},
+    action::{WantsToCastSpell, WantsToDropItem, WantsToRemoveItem, WantsToShoot, WantsToUseItem},
     camera::{render_camera, render_debug_map},
     effect::Ranged,
     gui::{
         cheat::{show_cheat_menu, CheatMenuResult},
+        creation::{show_character_creation, CharacterCreation, CreationResult},
         inventory::*,
+        level_up::{allocate_points, show_level_up, LevelUpResult, LEVEL_UP_CHOICES},
         menu::*,
         target::ranged_target,
         tooltips::draw_tooltips,
         ui::draw_ui,
     },
-    item::Item,
+    item::{item_label, EquipmentChanged, Identification, InBackpack, InContainer, Stackable},
     map::{
         master::level_transition,
         master::{freeze_level_entities, unfreeze_level_entities, MasterMap},
         Map,
     },
     player_action::input,
-    raws::{items::spawn_named_item, SpawnType, RAWS},
-    spawner::build_player_entity,
+    props::ContainerMode,
+    raws::{
+        affixes::apply_named_affix,
+        items::{disguise_items, spawn_named_item},
+        recipes::{get_recipes, CraftQuality},
+        spells::spawn_all_spells,
+        SpawnType, RAWS,
+    },
+    rng::{get_seed, RandomGen},
+    saveload::{delete_save, load_game, save_game},
+    spawner::{build_player_entity, PlayerBuild},
     systems::{
         ai::{
-            adjacent::AdjacentAI, approaching::ApproachAI, default::DefaultMoveAI,
+            adjacent::AdjacentAI, approaching::ApproachAI, chase::ChaseAI, default::DefaultMoveAI,
             encumbrance::EncumbranceSystem, fleeing::FleeAI, initiative::InitiativeSystem,
-            quipping::QuipSystem, turn_status::TurnStatusSystem, visible::VisibleAI,
+            morale::MoraleSystem, quipping::QuipSystem, squad::SquadAI,
+            turn_status::TurnStatusSystem, utility::UtilityAI, visible::VisibleAI,
         },
+        container::ContainerSystem,
         damage::{delete_the_deads, DamageSystem},
+        door::DoorSystem,
         inventory::*,
         lighting::LightingSystem,
         map_indexing::MapIndexingSystem,
         melee_combat::MeleeCombatSystem,
         particle::{cull_dead_particles, ParticleSpawnSystem},
+        ranged_combat::RangedCombatSystem,
+        reputation::ReputationSystem,
+        spell::SpellCastSystem,
         trigger::TriggerSystem,
         visibility::VisibilitySystem,
     },
-    unit::{Pools, VendorMode},
-    First, Log, FIRST_LEVEL, SHOW_MAPGEN_VISUALIZER,
+    unit::{Attributes, LevelUpPoints, Pools, Reputation, Skills, Vendor, VendorMode},
+    First, Log, Name, TurnCounter, FIRST_LEVEL, SHOW_MAPGEN_VISUALIZER,
 };
 mod sub;
 use sub::*;
@@ -49,8 +66,31 @@ pub enum RunState {
     PreRun,
     ShowInventory,
     ShowDropItem,
-    ShowTargeting { range: i32, item: Entity },
-    MainMenu { menu_selection: MainMenuSelection },
+    ShowDropQuantity {
+        item: Entity,
+        quantity: Option<i32>,
+    },
+    ShowTargeting {
+        range: i32,
+        item: Entity,
+    },
+    ShowFireTargeting {
+        range: i32,
+    },
+    ShowCastSpell,
+    ShowSpellTargeting {
+        range: i32,
+        spell: Entity,
+    },
+    MainMenu {
+        menu_selection: MainMenuSelection,
+    },
+    EnterSeed {
+        seed: Option<u64>,
+    },
+    CharacterCreation {
+        seed: u64,
+    },
     NextLevel,
     GameOver,
     ShowRemoveItem,
@@ -58,39 +98,64 @@ pub enum RunState {
     PreviousLevel,
     ShowCheatMenu,
     Ticking,
-    ShowVendor { vendor: Entity, mode: VendorMode },
+    ShowVendor {
+        vendor: Entity,
+        mode: VendorMode,
+    },
+    ShowContainer {
+        container: Entity,
+        mode: ContainerMode,
+    },
+    LevelUp {
+        selection: usize,
+        allocated: [i32; LEVEL_UP_CHOICES],
+    },
 }
 
+/// Systems run one after another, in the order they are listed. Letting specs
+/// run them in parallel makes entity ids, and with them join and RNG order,
+/// differ between runs of the same seed.
 pub fn new_dispatcher() -> Dispatcher<'static, 'static> {
     DispatcherBuilder::new()
-        .with(MapIndexingSystem {}, "map_index", &[])
-        .with(VisibilitySystem {}, "visibility", &[])
-        .with(InitiativeSystem {}, "initiative", &[])
-        .with(TurnStatusSystem {}, "turn_status", &[])
-        .with(AdjacentAI {}, "adjacent", &[])
-        .with(VisibleAI {}, "visible", &[])
-        .with(ApproachAI {}, "approach", &[])
-        .with(FleeAI {}, "flee", &[])
-        .with(DefaultMoveAI {}, "default_move", &[])
-        .with(MeleeCombatSystem {}, "melee", &[])
-        .with(ItemCollectionSystem {}, "pickup", &[])
-        .with(ItemUseSystem {}, "use", &[])
-        .with(ItemRemoveSystem {}, "remove", &[])
-        .with(ItemDropSystem {}, "drop", &[])
-        .with(ItemUseSystem {}, "item", &[])
-        .with(TriggerSystem {}, "triggers", &[])
-        .with(QuipSystem {}, "quipping", &[])
-        .with(DamageSystem {}, "damage", &[])
-        .with(EncumbranceSystem {}, "encumbrance", &[])
-        .with(LightingSystem {}, "lighting", &[])
-        .with(ParticleSpawnSystem {}, "particles", &[])
+        .with_thread_local(MapIndexingSystem::default())
+        .with_thread_local(VisibilitySystem {})
+        .with_thread_local(InitiativeSystem {})
+        .with_thread_local(TurnStatusSystem {})
+        .with_thread_local(MoraleSystem {})
+        .with_thread_local(UtilityAI {})
+        .with_thread_local(AdjacentAI {})
+        .with_thread_local(VisibleAI {})
+        .with_thread_local(ApproachAI {})
+        .with_thread_local(FleeAI {})
+        .with_thread_local(ChaseAI {})
+        .with_thread_local(SquadAI {})
+        .with_thread_local(DefaultMoveAI {})
+        .with_thread_local(DoorSystem {})
+        .with_thread_local(ContainerSystem {})
+        .with_thread_local(MeleeCombatSystem {})
+        .with_thread_local(RangedCombatSystem {})
+        .with_thread_local(SpellCastSystem {})
+        .with_thread_local(ItemCollectionSystem {})
+        .with_thread_local(ItemUseSystem {})
+        .with_thread_local(ItemRemoveSystem {})
+        .with_thread_local(ItemDropSystem {})
+        .with_thread_local(ItemUseSystem {})
+        .with_thread_local(TriggerSystem {})
+        .with_thread_local(QuipSystem {})
+        .with_thread_local(ReputationSystem {})
+        .with_thread_local(DamageSystem {})
+        .with_thread_local(EncumbranceSystem {})
+        .with_thread_local(LightingSystem {})
+        .with_thread_local(ParticleSpawnSystem {})
         .build()
 }
 
 pub struct State {
     pub ecs: World,
     pub gen: MapGen,
+    pub creation: CharacterCreation,
     pub dispatcher: Dispatcher<'static, 'static>,
+    pub seed: Option<u64>,
 }
 
 pub struct MapGen {
@@ -101,12 +166,12 @@ pub struct MapGen {
 }
 
 impl State {
-    fn run_systems(&mut self) {
+    pub fn run_systems(&mut self) {
         self.dispatcher.run_now(&self.ecs);
         self.ecs.maintain();
     }
 
-    fn cleanup(&mut self) {
+    fn cleanup(&mut self, seed: u64, build: &PlayerBuild) {
         let mut to_delete = Vec::new();
         for e in self.ecs.entities().join() {
             to_delete.push(e);
@@ -116,14 +181,24 @@ impl State {
             self.ecs.delete_entity(*del).expect("Deletion failed");
         }
 
-        *self.ecs.write_resource::<Entity>() = build_player_entity(&mut self.ecs, 0, 0);
+        spawn_all_spells(&RAWS.lock().unwrap(), &mut self.ecs);
+        self.ecs.insert(disguise_items(&RAWS.lock().unwrap(), seed));
+        *self.ecs.write_resource::<Entity>() = build_player_entity(&mut self.ecs, 0, 0, build);
 
         Log::clear();
-        self.ecs.insert(MasterMap::new());
+        self.ecs.insert(RandomGen::new(seed));
+        self.ecs.insert(MasterMap::new(seed));
+        self.ecs.insert(TurnCounter::default());
+        self.ecs.insert(Reputation::default());
         self.generate_world_map(FIRST_LEVEL, 0);
     }
 
-    fn goto_level(&mut self, offset: i32) {
+    pub fn start_new_game(&mut self, seed: u64, build: &PlayerBuild) {
+        self.cleanup(seed, build);
+        Log::new().append("Run seed:").item(&seed).build();
+    }
+
+    pub fn goto_level(&mut self, offset: i32) {
         freeze_level_entities(&mut self.ecs);
         let depth = self.ecs.write_resource::<Map>().depth + offset;
         self.generate_world_map(depth, offset);
@@ -146,11 +221,13 @@ impl GameState for State {
     fn tick(&mut self, ctx: &mut BTerm) {
         let mut newrunstate = *self.ecs.fetch::<RunState>();
         ctx.cls();
-        cull_dead_particles(&mut self.ecs, ctx);
+        cull_dead_particles(&mut self.ecs, ctx.frame_time_ms);
 
         match newrunstate {
             RunState::GameOver => {}
             RunState::MainMenu { .. } => {}
+            RunState::EnterSeed { .. } => {}
+            RunState::CharacterCreation { .. } => {}
             _ => {
                 self.ecs.write_resource::<First>().run = false;
                 render_camera(&self.ecs, ctx);
@@ -166,7 +243,14 @@ impl GameState for State {
                 newrunstate = RunState::AwaitingInput;
             }
             RunState::AwaitingInput => {
-                newrunstate = input(self, ctx);
+                newrunstate = if has_level_up_points(&self.ecs) {
+                    RunState::LevelUp {
+                        selection: 0,
+                        allocated: [0; LEVEL_UP_CHOICES],
+                    }
+                } else {
+                    input(self, ctx)
+                };
             }
             RunState::Ticking => {
                 while newrunstate == RunState::Ticking {
@@ -180,10 +264,20 @@ impl GameState for State {
             }
             RunState::ShowInventory => newrunstate = show_inv_use(self, ctx),
             RunState::ShowDropItem => newrunstate = show_inv_drop(self, ctx),
+            RunState::ShowDropQuantity { item, quantity } => {
+                newrunstate = show_drop_quantity(self, ctx, item, quantity)
+            }
             RunState::ShowRemoveItem => newrunstate = show_inv_remove(self, ctx),
             RunState::ShowTargeting { range, item } => {
                 newrunstate = show_targeting(self, ctx, range, item)
             }
+            RunState::ShowFireTargeting { range } => {
+                newrunstate = show_fire_targeting(self, ctx, range)
+            }
+            RunState::ShowCastSpell => newrunstate = show_cast_spell(self, ctx),
+            RunState::ShowSpellTargeting { range, spell } => {
+                newrunstate = show_spell_targeting(self, ctx, range, spell)
+            }
             RunState::MainMenu { .. } => match main_menu(self, ctx) {
                 MainMenuResult::NoSelection { selected } => {
                     newrunstate = RunState::MainMenu {
@@ -191,22 +285,58 @@ impl GameState for State {
                     }
                 }
                 MainMenuResult::Selected { selected } => match selected {
-                    MainMenuSelection::NewGame => {
-                        self.cleanup();
-                        newrunstate = RunState::MapGeneration
+                    MainMenuSelection::NewGame => match self.seed {
+                        Some(seed) => {
+                            self.creation = CharacterCreation::new(seed);
+                            newrunstate = RunState::CharacterCreation { seed }
+                        }
+                        None => newrunstate = RunState::EnterSeed { seed: None },
+                    },
+                    MainMenuSelection::LoadGame => {
+                        load_game(&mut self.ecs);
+                        newrunstate = RunState::PreRun;
                     }
                     MainMenuSelection::Continue => {
                         newrunstate = RunState::AwaitingInput;
                     }
+                    MainMenuSelection::SaveAndQuit => {
+                        save_game(&mut self.ecs);
+                        ::std::process::exit(0);
+                    }
                     MainMenuSelection::Quit => {
                         ::std::process::exit(0);
                     }
                 },
             },
+            RunState::EnterSeed { seed } => match seed_entry(self, ctx, seed) {
+                SeedEntryResult::NoResponse { seed } => newrunstate = RunState::EnterSeed { seed },
+                SeedEntryResult::Cancel => {
+                    newrunstate = RunState::MainMenu {
+                        menu_selection: MainMenuSelection::NewGame,
+                    }
+                }
+                SeedEntryResult::Selected { seed } => {
+                    self.creation = CharacterCreation::new(seed);
+                    newrunstate = RunState::CharacterCreation { seed }
+                }
+            },
+            RunState::CharacterCreation { seed } => match show_character_creation(self, ctx) {
+                CreationResult::NoResponse => {}
+                CreationResult::Cancel => {
+                    newrunstate = RunState::MainMenu {
+                        menu_selection: MainMenuSelection::NewGame,
+                    }
+                }
+                CreationResult::Done { build } => {
+                    self.start_new_game(seed, &build);
+                    newrunstate = RunState::MapGeneration
+                }
+            },
             RunState::GameOver => match game_over(ctx) {
                 GameOverResult::NoSelection => {}
                 GameOverResult::QuitToMenu => {
-                    self.cleanup();
+                    delete_save();
+                    self.cleanup(get_seed(), &PlayerBuild::default());
                     self.ecs.write_resource::<First>().run = true;
                     newrunstate = RunState::MainMenu {
                         menu_selection: MainMenuSelection::NewGame,
@@ -233,7 +363,7 @@ impl GameState for State {
                 }
                 CheatMenuResult::Heal => {
                     let mut pools = self.ecs.write_storage::<Pools>();
-                    let mut player_pools = pools.get_mut(*self.ecs.fetch::<Entity>()).unwrap();
+                    let player_pools = pools.get_mut(*self.ecs.fetch::<Entity>()).unwrap();
                     player_pools.hit_points.current = player_pools.hit_points.max;
                     newrunstate = RunState::AwaitingInput;
                 }
@@ -245,7 +375,7 @@ impl GameState for State {
                 }
                 CheatMenuResult::GodMode => {
                     let mut pools = self.ecs.write_storage::<Pools>();
-                    let mut player_pools = pools.get_mut(*self.ecs.fetch::<Entity>()).unwrap();
+                    let player_pools = pools.get_mut(*self.ecs.fetch::<Entity>()).unwrap();
                     player_pools.god_mode = !player_pools.god_mode;
                     newrunstate = RunState::AwaitingInput;
                 }
@@ -255,8 +385,9 @@ impl GameState for State {
                 match result {
                     VendorResult::Cancel => newrunstate = RunState::AwaitingInput,
                     VendorResult::NoResponse => {}
-                    VendorResult::Sell => sell(&mut self.ecs, entity.unwrap()),
+                    VendorResult::Sell => sell(&mut self.ecs, entity.unwrap(), value.unwrap()),
                     VendorResult::Buy => buy(&mut self.ecs, value.unwrap(), &name.unwrap()),
+                    VendorResult::Craft => craft(&mut self.ecs, vendor, &name.unwrap()),
                     VendorResult::BuyMode => {
                         newrunstate = RunState::ShowVendor {
                             vendor,
@@ -269,8 +400,53 @@ impl GameState for State {
                             mode: VendorMode::Sell,
                         }
                     }
+                    VendorResult::CraftMode => {
+                        newrunstate = RunState::ShowVendor {
+                            vendor,
+                            mode: VendorMode::Craft,
+                        }
+                    }
                 }
             }
+            RunState::ShowContainer { container, mode } => {
+                let (result, entity) = show_container_menu(self, ctx, container, mode);
+                match result {
+                    ContainerResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    ContainerResult::NoResponse => {}
+                    ContainerResult::Take => take_item(&mut self.ecs, entity.unwrap()),
+                    ContainerResult::Put => put_item(&mut self.ecs, container, entity.unwrap()),
+                    ContainerResult::TakeMode => {
+                        newrunstate = RunState::ShowContainer {
+                            container,
+                            mode: ContainerMode::Take,
+                        }
+                    }
+                    ContainerResult::PutMode => {
+                        newrunstate = RunState::ShowContainer {
+                            container,
+                            mode: ContainerMode::Put,
+                        }
+                    }
+                }
+            }
+            RunState::LevelUp {
+                selection,
+                allocated,
+            } => match show_level_up(self, ctx, selection, allocated) {
+                LevelUpResult::NoResponse {
+                    selection,
+                    allocated,
+                } => {
+                    newrunstate = RunState::LevelUp {
+                        selection,
+                        allocated,
+                    }
+                }
+                LevelUpResult::Confirm { allocated } => {
+                    apply_level_up(&mut self.ecs, &allocated);
+                    newrunstate = RunState::AwaitingInput;
+                }
+            },
         }
 
         *self.ecs.write_resource::<RunState>() = newrunstate;
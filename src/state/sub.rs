@@ -1,9 +1,8 @@
 use super::*;
-use bracket_lib::terminal::BTerm;
+use bracket_lib::{prelude::Algorithm2D, terminal::BTerm};
 use specs::prelude::*;
 
-pub fn sell(ecs: &mut World, entity: Entity) {
-    let price = ecs.read_storage::<Item>().get(entity).unwrap().base_value;
+pub fn sell(ecs: &mut World, entity: Entity, price: i32) {
     ecs.write_storage::<Pools>()
         .get_mut(*ecs.fetch::<Entity>())
         .unwrap()
@@ -18,6 +17,7 @@ pub fn buy(ecs: &mut World, value: i32, name: &str) {
         player_pools.money -= value;
         std::mem::drop(pools);
         let player_entity = *ecs.fetch::<Entity>();
+        ecs.write_resource::<Identification>().identify(name);
         spawn_named_item(
             &RAWS.lock().unwrap(),
             ecs,
@@ -27,6 +27,200 @@ pub fn buy(ecs: &mut World, value: i32, name: &str) {
     }
 }
 
+/// Makes one of the vendor's recipes out of the ingredients in the player's
+/// pack, rolling for the quality of the result.
+pub fn craft(ecs: &mut World, vendor: Entity, output: &str) {
+    let raws = RAWS.lock().unwrap();
+    let player_entity = *ecs.fetch::<Entity>();
+    let categories = match ecs.read_storage::<Vendor>().get(vendor) {
+        Some(vendor) => vendor.categories.clone(),
+        None => return,
+    };
+    let recipe = match get_recipes(&categories, &raws)
+        .into_iter()
+        .find(|recipe| recipe.output == output)
+    {
+        Some(recipe) => recipe,
+        None => return,
+    };
+
+    let missing = recipe.ingredients.iter().any(|ingredient| {
+        count_carried(ecs, player_entity, &ingredient.name) < ingredient.quantity
+    });
+    if missing {
+        Log::new()
+            .append("You need")
+            .item(&recipe.ingredients_text())
+            .append("to make the")
+            .item(&recipe.output)
+            .append(".")
+            .build();
+        return;
+    }
+    for ingredient in recipe.ingredients.iter() {
+        consume_carried(ecs, player_entity, &ingredient.name, ingredient.quantity);
+    }
+
+    let (quality, roll) = recipe.roll_quality(
+        &mut ecs.write_resource::<RandomGen>(),
+        ecs.read_storage::<Attributes>().get(player_entity),
+        ecs.read_storage::<Skills>().get(player_entity),
+    );
+    let stackable = raws
+        .item_index
+        .get(&recipe.output)
+        .is_some_and(|i| raws.raws.items[*i].stackable.unwrap_or(false));
+    // Batches of consumables come out larger or smaller instead of taking affixes.
+    let mut quantity = recipe.quantity.unwrap_or(1);
+    if stackable {
+        match quality {
+            CraftQuality::Shoddy => quantity = i32::max(1, quantity - 1),
+            CraftQuality::Masterwork => quantity += 1,
+            _ => {}
+        }
+    }
+
+    ecs.write_resource::<Identification>()
+        .identify(&recipe.output);
+    let mut crafted = Vec::new();
+    for _ in 0..quantity {
+        let pos = SpawnType::Carried { by: player_entity };
+        if let Some(item) = spawn_named_item(&raws, ecs, &recipe.output, pos) {
+            crafted.push(item);
+        }
+    }
+    crafted.dedup();
+    if let (false, Some(affix)) = (stackable, recipe.quality_affix(quality)) {
+        for item in crafted.iter() {
+            apply_named_affix(&raws, ecs, *item, affix);
+        }
+    }
+    ecs.write_storage::<EquipmentChanged>()
+        .insert(player_entity, EquipmentChanged {})
+        .expect("Unable to insert");
+
+    // Named after the affixed item, but counting only what was just made.
+    let label = match crafted.first() {
+        Some(item) => item_label(
+            ecs.read_storage::<Name>().get(*item).unwrap().name.clone(),
+            Some(&Stackable { quantity }),
+        ),
+        None => return,
+    };
+    let mut log = Log::new();
+    if let Some((roll, difficulty)) = roll {
+        log = log
+            .append("(Roll")
+            .roll(&format!("{:02}", roll))
+            .append("vs")
+            .roll(&format!("{:02}", difficulty))
+            .append("difficulty|");
+    }
+    log = match quality {
+        CraftQuality::Shoddy => log.append("You botch the work, and make the").bad(&label),
+        CraftQuality::Standard => log.append("You make the").item(&label),
+        CraftQuality::Fine => log.append("You make the").good(&label),
+        CraftQuality::Masterwork => log
+            .good(&"A masterpiece!")
+            .append("You make the")
+            .good(&label),
+    };
+    log.append(".").build();
+}
+
+/// Moves an item out of a container into the player's pack.
+pub fn take_item(ecs: &mut World, item: Entity) {
+    let player_entity = *ecs.fetch::<Entity>();
+    log_transfer(ecs, item, "You take the", None);
+    ecs.write_storage::<InContainer>().remove(item);
+    let existing = {
+        let backpack = ecs.read_storage::<InBackpack>();
+        existing_stack(ecs, item, |other| {
+            backpack
+                .get(other)
+                .is_some_and(|pack| pack.owner == player_entity)
+        })
+    };
+    match existing {
+        Some(stack) => merge_stack(ecs, item, stack),
+        None => {
+            ecs.write_storage::<InBackpack>()
+                .insert(
+                    item,
+                    InBackpack {
+                        owner: player_entity,
+                    },
+                )
+                .expect("Unable to insert");
+        }
+    }
+    ecs.write_storage::<EquipmentChanged>()
+        .insert(player_entity, EquipmentChanged {})
+        .expect("Unable to insert");
+}
+
+/// Moves an item out of the player's pack into `container`.
+pub fn put_item(ecs: &mut World, container: Entity, item: Entity) {
+    let player_entity = *ecs.fetch::<Entity>();
+    log_transfer(ecs, item, "You put the", Some(container));
+    ecs.write_storage::<InBackpack>().remove(item);
+    let existing = {
+        let held = ecs.read_storage::<InContainer>();
+        existing_stack(ecs, item, |other| {
+            held.get(other).is_some_and(|held| held.owner == container)
+        })
+    };
+    match existing {
+        Some(stack) => merge_stack(ecs, item, stack),
+        None => {
+            ecs.write_storage::<InContainer>()
+                .insert(item, InContainer { owner: container })
+                .expect("Unable to insert");
+        }
+    }
+    ecs.write_storage::<EquipmentChanged>()
+        .insert(player_entity, EquipmentChanged {})
+        .expect("Unable to insert");
+}
+
+fn log_transfer(ecs: &World, item: Entity, text: &str, container: Option<Entity>) {
+    let names = ecs.read_storage::<Name>();
+    let label = item_label(
+        ecs.fetch::<Identification>()
+            .display_name(&names.get(item).unwrap().name),
+        ecs.read_storage::<Stackable>().get(item),
+    );
+    let mut log = Log::new().append(text).item(&label);
+    if let Some(container) = container.and_then(|container| names.get(container)) {
+        log = log.append("in the").item(&container.name);
+    }
+    log.append(".").build();
+}
+
+/// Another stack of the same item as `item`, among those `holds` picks out.
+fn existing_stack(ecs: &World, item: Entity, holds: impl Fn(Entity) -> bool) -> Option<Entity> {
+    let names = ecs.read_storage::<Name>();
+    let stacks = ecs.read_storage::<Stackable>();
+    let name = &names.get(item)?.name;
+    if !stacks.contains(item) {
+        return None;
+    }
+    (&ecs.entities(), &names, &stacks)
+        .join()
+        .find(|(other, other_name, _)| *other != item && other_name.name == *name && holds(*other))
+        .map(|(other, _, _)| other)
+}
+
+fn merge_stack(ecs: &mut World, item: Entity, into: Entity) {
+    let mut stacks = ecs.write_storage::<Stackable>();
+    let quantity = stacks.get(item).map_or(1, |stack| stack.quantity);
+    if let Some(stack) = stacks.get_mut(into) {
+        stack.quantity += quantity;
+    }
+    std::mem::drop(stacks);
+    ecs.delete_entity(item).expect("Unable to delete");
+}
+
 pub fn map_generation(gen: &mut MapGen, ctx: &mut BTerm) -> RunState {
     if !SHOW_MAPGEN_VISUALIZER {
         return gen.next_state.unwrap();
@@ -87,12 +281,26 @@ pub fn show_inv_drop(gs: &mut State, ctx: &mut BTerm) -> RunState {
         ItemMenuResult::Cancel => RunState::AwaitingInput,
         ItemMenuResult::NoResponse => RunState::ShowDropItem,
         ItemMenuResult::Selected => {
+            let item = entity.unwrap();
+            if gs
+                .ecs
+                .read_storage::<Stackable>()
+                .get(item)
+                .is_some_and(|stack| stack.quantity > 1)
+            {
+                return RunState::ShowDropQuantity {
+                    item,
+                    quantity: None,
+                };
+            }
+
             gs.ecs
                 .write_storage::<WantsToDropItem>()
                 .insert(
                     *gs.ecs.fetch::<Entity>(),
                     WantsToDropItem {
-                        item: entity.unwrap(),
+                        item,
+                        quantity: None,
                     },
                 )
                 .expect("Unable to insert intent");
@@ -101,6 +309,28 @@ pub fn show_inv_drop(gs: &mut State, ctx: &mut BTerm) -> RunState {
     }
 }
 
+pub fn show_drop_quantity(
+    gs: &mut State,
+    ctx: &mut BTerm,
+    item: Entity,
+    quantity: Option<i32>,
+) -> RunState {
+    match drop_quantity_menu(gs, ctx, item, quantity) {
+        QuantityResult::Cancel => RunState::AwaitingInput,
+        QuantityResult::NoResponse { quantity } => RunState::ShowDropQuantity { item, quantity },
+        QuantityResult::Selected { quantity } => {
+            gs.ecs
+                .write_storage::<WantsToDropItem>()
+                .insert(
+                    *gs.ecs.fetch::<Entity>(),
+                    WantsToDropItem { item, quantity },
+                )
+                .expect("Unable to insert intent");
+            RunState::Ticking
+        }
+    }
+}
+
 pub fn show_inv_remove(gs: &mut State, ctx: &mut BTerm) -> RunState {
     let (result, entity) = remove_item_menu(gs, ctx);
     match result {
@@ -121,6 +351,95 @@ pub fn show_inv_remove(gs: &mut State, ctx: &mut BTerm) -> RunState {
     }
 }
 
+pub fn show_cast_spell(gs: &mut State, ctx: &mut BTerm) -> RunState {
+    let (result, entity) = show_spell_menu(gs, ctx);
+    match result {
+        ItemMenuResult::Cancel => RunState::AwaitingInput,
+        ItemMenuResult::NoResponse => RunState::ShowCastSpell,
+        ItemMenuResult::Selected => {
+            let spell = entity.unwrap();
+
+            if let Some(is_ranged) = gs.ecs.read_storage::<Ranged>().get(spell) {
+                return RunState::ShowSpellTargeting {
+                    range: is_ranged.range,
+                    spell,
+                };
+            }
+
+            gs.ecs
+                .write_storage::<WantsToCastSpell>()
+                .insert(
+                    *gs.ecs.fetch::<Entity>(),
+                    WantsToCastSpell {
+                        spell,
+                        target: None,
+                    },
+                )
+                .expect("Unable to insert intent");
+            RunState::Ticking
+        }
+    }
+}
+
+pub fn show_spell_targeting(
+    gs: &mut State,
+    ctx: &mut BTerm,
+    range: i32,
+    spell: Entity,
+) -> RunState {
+    let (result, point) = ranged_target(gs, ctx, range);
+    match result {
+        ItemMenuResult::Cancel => RunState::AwaitingInput,
+        ItemMenuResult::NoResponse => RunState::ShowSpellTargeting { range, spell },
+        ItemMenuResult::Selected => {
+            gs.ecs
+                .write_storage::<WantsToCastSpell>()
+                .insert(
+                    *gs.ecs.fetch::<Entity>(),
+                    WantsToCastSpell {
+                        spell,
+                        target: point,
+                    },
+                )
+                .expect("Unable to insert intent");
+            RunState::Ticking
+        }
+    }
+}
+
+pub fn show_fire_targeting(gs: &mut State, ctx: &mut BTerm, range: i32) -> RunState {
+    let (result, point) = ranged_target(gs, ctx, range);
+    match result {
+        ItemMenuResult::Cancel => RunState::AwaitingInput,
+        ItemMenuResult::NoResponse => RunState::ShowFireTargeting { range },
+        ItemMenuResult::Selected => {
+            let target = {
+                let map = gs.ecs.fetch::<Map>();
+                let pools = gs.ecs.read_storage::<Pools>();
+                let idx = map.point2d_to_index(point.unwrap());
+                let target = map.index.entities_at(idx).find(|e| pools.get(*e).is_some());
+                target
+            };
+
+            match target {
+                None => {
+                    Log::new()
+                        .append("There is nothing to shoot there.")
+                        .build();
+                    RunState::AwaitingInput
+                }
+                Some(target) => {
+                    gs.ecs
+                        .write_storage::<WantsToShoot>()
+                        .insert(*gs.ecs.fetch::<Entity>(), WantsToShoot { target })
+                        .expect("Unable to insert intent");
+                    RunState::Ticking
+                }
+            }
+        }
+    }
+}
+
 pub fn show_targeting(gs: &mut State, ctx: &mut BTerm, range: i32, item: Entity) -> RunState {
     let (result, point) = ranged_target(gs, ctx, range);
     match result {
@@ -141,3 +460,107 @@ pub fn show_targeting(gs: &mut State, ctx: &mut BTerm, range: i32, item: Entity)
         }
     }
 }
+
+pub fn has_level_up_points(ecs: &World) -> bool {
+    ecs.read_storage::<LevelUpPoints>()
+        .get(*ecs.fetch::<Entity>())
+        .is_some()
+}
+
+pub fn apply_level_up(ecs: &mut World, allocated: &[i32; LEVEL_UP_CHOICES]) {
+    let player_entity = *ecs.fetch::<Entity>();
+    let mut attributes = ecs.write_storage::<Attributes>();
+    let mut skills = ecs.write_storage::<Skills>();
+    let mut pools = ecs.write_storage::<Pools>();
+    let player_attributes = attributes.get_mut(player_entity).unwrap();
+
+    allocate_points(
+        player_attributes,
+        skills.get_mut(player_entity).unwrap(),
+        allocated,
+    );
+
+    let player_pools = pools.get_mut(player_entity).unwrap();
+    player_pools.hit_points.max = player_attributes.player_max_hp();
+    player_pools.hit_points.current = player_pools.hit_points.max;
+    player_pools.mana.max = player_attributes.max_mana();
+    player_pools.mana.current = player_pools.mana.max;
+
+    ecs.write_storage::<LevelUpPoints>().remove(player_entity);
+    ecs.write_storage::<EquipmentChanged>()
+        .insert(player_entity, EquipmentChanged {})
+        .expect("Unable to insert EquipmentChanged");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::super::new_state, *};
+
+    fn arrows(ecs: &mut World, quantity: i32, spawn: SpawnType) -> Entity {
+        let arrows = spawn_named_item(&RAWS.lock().unwrap(), ecs, "Arrow", spawn).unwrap();
+        set_quantity(ecs, arrows, quantity);
+        arrows
+    }
+
+    fn quantity(ecs: &World, item: Entity) -> Option<i32> {
+        if !ecs.entities().is_alive(item) {
+            return None;
+        }
+        ecs.read_storage::<Stackable>()
+            .get(item)
+            .map(|stack| stack.quantity)
+    }
+
+    #[test]
+    fn taken_stacks_merge_into_the_pack() {
+        let mut ecs = new_state(Some(1)).ecs;
+        let player = *ecs.fetch::<Entity>();
+        let chest = ecs.create_entity().with(Name::new("Chest")).build();
+        let carried = arrows(&mut ecs, 3, SpawnType::Carried { by: player });
+        let stored = arrows(&mut ecs, 5, SpawnType::Contained { by: chest });
+
+        take_item(&mut ecs, stored);
+        ecs.maintain();
+
+        assert_eq!(quantity(&ecs, carried), Some(8));
+        assert_eq!(quantity(&ecs, stored), None);
+    }
+
+    #[test]
+    fn put_stacks_merge_into_the_container() {
+        let mut ecs = new_state(Some(1)).ecs;
+        let player = *ecs.fetch::<Entity>();
+        let chest = ecs.create_entity().with(Name::new("Chest")).build();
+        let carried = arrows(&mut ecs, 3, SpawnType::Carried { by: player });
+        let stored = arrows(&mut ecs, 5, SpawnType::Contained { by: chest });
+        let other_chest = ecs.create_entity().with(Name::new("Barrel")).build();
+        let elsewhere = arrows(&mut ecs, 1, SpawnType::Contained { by: other_chest });
+
+        put_item(&mut ecs, chest, carried);
+        ecs.maintain();
+
+        assert_eq!(quantity(&ecs, stored), Some(8));
+        assert_eq!(quantity(&ecs, carried), None);
+        // Only stacks in the same container are merged.
+        assert_eq!(quantity(&ecs, elsewhere), Some(1));
+    }
+
+    #[test]
+    fn items_without_a_match_move_as_they_are() {
+        let mut ecs = new_state(Some(1)).ecs;
+        let chest = ecs.create_entity().with(Name::new("Chest")).build();
+        let stored = arrows(&mut ecs, 5, SpawnType::Contained { by: chest });
+
+        take_item(&mut ecs, stored);
+        ecs.maintain();
+
+        let player = *ecs.fetch::<Entity>();
+        let owner = ecs
+            .read_storage::<InBackpack>()
+            .get(stored)
+            .map(|pack| pack.owner);
+        assert_eq!(owner, Some(player));
+        assert!(!ecs.read_storage::<InContainer>().contains(stored));
+        assert_eq!(quantity(&ecs, stored), Some(5));
+    }
+}
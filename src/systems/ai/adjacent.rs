@@ -1,4 +1,7 @@
-use super::{faction_reaction, Faction, Map, MyTurn, Position, Reaction, WantsToMelee, RAWS};
+use super::{
+    reaction_to, Behavior, Chasing, Faction, Map, Morale, MyTurn, Position, Reaction, TurnCounter,
+    WantsToMelee, RAWS,
+};
 use specs::prelude::*;
 
 pub struct AdjacentAI {}
@@ -13,81 +16,46 @@ impl<'a> System<'a> for AdjacentAI {
         WriteStorage<'a, WantsToMelee>,
         Entities<'a>,
         ReadExpect<'a, Entity>,
+        WriteStorage<'a, Chasing>,
+        ReadExpect<'a, TurnCounter>,
+        ReadStorage<'a, Morale>,
+        ReadStorage<'a, Behavior>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut turns, factions, positions, map, mut want_melee, entities, player) = data;
+        let (
+            mut turns,
+            factions,
+            positions,
+            map,
+            mut want_melee,
+            entities,
+            player,
+            mut chasing,
+            turn_counter,
+            morale,
+            behaviors,
+        ) = data;
 
         let mut turn_done = Vec::<Entity>::new();
-        for (entity, _, my_faction, pos) in (&entities, &turns, &factions, &positions).join() {
-            if entity == *player {
+        for (entity, _, my_faction, pos, _) in
+            (&entities, &turns, &factions, &positions, !&behaviors).join()
+        {
+            if entity == *player || morale.get(entity).is_some_and(|m| m.broken) {
                 continue;
             }
 
             let mut reactions: Vec<(Entity, Reaction)> = Vec::new();
-            let idx = map.coord_to_index(pos.x, pos.y);
-            let w = map.width;
-            let h = map.height;
-
-            if pos.x > 0 {
-                evaluate(idx - 1, &factions, &my_faction.name, &mut reactions, &map);
-            }
-            if pos.x < w - 1 {
-                evaluate(idx + 1, &factions, &my_faction.name, &mut reactions, &map);
-            }
-            if pos.y > 0 {
-                evaluate(
-                    idx - w as usize,
-                    &factions,
-                    &my_faction.name,
-                    &mut reactions,
-                    &map,
-                );
-            }
-            if pos.y < h - 1 {
-                evaluate(
-                    idx + w as usize,
-                    &factions,
-                    &my_faction.name,
-                    &mut reactions,
-                    &map,
-                );
-            }
-            if pos.y > 0 && pos.x > 0 {
-                evaluate(
-                    (idx - w as usize) - 1,
-                    &factions,
-                    &my_faction.name,
-                    &mut reactions,
-                    &map,
-                );
-            }
-            if pos.y > 0 && pos.x < w - 1 {
-                evaluate(
-                    (idx - w as usize) + 1,
-                    &factions,
-                    &my_faction.name,
-                    &mut reactions,
-                    &map,
-                );
-            }
-            if pos.y < h - 1 && pos.x > 0 {
-                evaluate(
-                    (idx + w as usize) - 1,
-                    &factions,
-                    &my_faction.name,
-                    &mut reactions,
-                    &map,
-                );
-            }
-            if pos.y < h - 1 && pos.x < w - 1 {
-                evaluate(
-                    (idx + w as usize) + 1,
-                    &factions,
-                    &my_faction.name,
-                    &mut reactions,
-                    &map,
-                );
+            for other in map.index.entities_in_radius(pos.into_point(), 1.5) {
+                if other == entity {
+                    continue;
+                }
+                if let Some(faction) = factions.get(other) {
+                    reactions.push((
+                        other,
+                        reaction_to(&my_faction.name, faction, &RAWS.lock().unwrap()),
+                    ));
+                }
             }
 
             let mut done = false;
@@ -96,6 +64,18 @@ impl<'a> System<'a> for AdjacentAI {
                     want_melee
                         .insert(entity, WantsToMelee { target: reaction.0 })
                         .expect("Error inserting melee");
+                    if let Some(target_pos) = positions.get(reaction.0) {
+                        chasing
+                            .insert(
+                                entity,
+                                Chasing::new(
+                                    reaction.0,
+                                    target_pos.into_point(),
+                                    turn_counter.turn,
+                                ),
+                            )
+                            .expect("Unable to insert");
+                    }
                     done = true;
                 }
             }
@@ -110,20 +90,3 @@ impl<'a> System<'a> for AdjacentAI {
         }
     }
 }
-
-fn evaluate(
-    idx: usize,
-    factions: &ReadStorage<Faction>,
-    my_faction: &str,
-    reactions: &mut Vec<(Entity, Reaction)>,
-    map: &Map,
-) {
-    for other_entity in map.tiles[idx].content.iter() {
-        if let Some(faction) = factions.get(*other_entity) {
-            reactions.push((
-                *other_entity,
-                faction_reaction(my_faction, &faction.name, &RAWS.lock().unwrap()),
-            ));
-        }
-    }
-}
@@ -1,7 +1,11 @@
-use crate::pathfinding::a_star::a_star_search;
+use crate::pathfinding::flow_map::{next_step, FlowGoal, FlowMaps};
 use specs::prelude::*;
 
-use super::{EntityMoved, Map, MyTurn, Position, Viewshed, WantsToApproach};
+use super::{
+    clear_step, faction_sources, squad::flank_step, EntityMoved, Faction, Map, MyTurn, OpensDoors,
+    Position, Reaction, SquadLeader, SquadMember, TurnCounter, Viewshed, Walker, WantsToApproach,
+    WantsToOpenDoor,
+};
 
 pub struct ApproachAI {}
 
@@ -15,6 +19,14 @@ impl<'a> System<'a> for ApproachAI {
         WriteStorage<'a, Viewshed>,
         WriteStorage<'a, EntityMoved>,
         Entities<'a>,
+        ReadStorage<'a, Faction>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, FlowMaps>,
+        ReadExpect<'a, TurnCounter>,
+        ReadStorage<'a, SquadMember>,
+        ReadStorage<'a, SquadLeader>,
+        ReadStorage<'a, OpensDoors>,
+        WriteStorage<'a, WantsToOpenDoor>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -26,41 +38,74 @@ impl<'a> System<'a> for ApproachAI {
             mut viewsheds,
             mut entity_moved,
             entities,
+            factions,
+            player,
+            mut flow_maps,
+            turn_counter,
+            members,
+            leaders,
+            opens_doors,
+            mut want_open,
         ) = data;
 
-        let mut turn_done = Vec::<Entity>::new();
-        for (entity, mut pos, approach, mut viewshed, _) in (
-            &entities,
-            &mut positions,
-            &want_approach,
-            &mut viewsheds,
-            &turns,
-        )
-            .join()
-        {
-            turn_done.push(entity);
-            let path = a_star_search(
-                map.coord_to_index(pos.x, pos.y),
-                map.coord_to_index(approach.idx % map.width, approach.idx / map.width),
-                &*map,
-            );
-            if path.success && path.steps.len() > 1 {
-                let idx = map.coord_to_index(pos.x, pos.y);
-                pos.x = path.steps[1] as i32 % map.width;
-                pos.y = path.steps[1] as i32 / map.width;
-                entity_moved
-                    .insert(entity, EntityMoved {})
-                    .expect("Unable to insert marker");
-                let new_idx = map.coord_to_index(pos.x, pos.y);
-                map.move_entity(entity, idx, new_idx);
-                viewshed.dirty = true;
+        let player_idx = positions
+            .get(*player)
+            .map(|pos| map.coord_to_index(pos.x, pos.y));
+
+        let mut moves = Vec::<(Entity, usize)>::new();
+        let mut claimed = Vec::<usize>::new();
+        for (entity, pos, approach, _) in (&entities, &positions, &want_approach, &turns).join() {
+            let idx = map.coord_to_index(pos.x, pos.y);
+            let walker = Walker::new(&map, opens_doors.contains(entity));
+            if members.contains(entity) || leaders.contains(entity) {
+                if let Some(step) = flank_step(idx, approach.idx as usize, &walker, &mut claimed) {
+                    moves.push((entity, step));
+                    continue;
+                }
+            }
+
+            let goal = match factions.get(entity) {
+                Some(faction) if Some(approach.idx as usize) != player_idx => {
+                    FlowGoal::Hostiles(faction.name.clone())
+                }
+                _ => FlowGoal::TowardPlayer,
+            };
+            let flow = flow_maps.get(&goal, &map, turn_counter.turn, || match &goal {
+                FlowGoal::Hostiles(name) => faction_sources(
+                    name,
+                    Reaction::Attack,
+                    &entities,
+                    &factions,
+                    &positions,
+                    &map,
+                ),
+                _ => player_idx.into_iter().collect(),
+            });
+
+            if let Some(step) = next_step(flow, idx, &walker) {
+                moves.push((entity, step));
             }
         }
 
-        want_approach.clear();
+        for (entity, dest_idx) in moves {
+            if !clear_step(entity, dest_idx, &map, &opens_doors, &mut want_open) {
+                continue;
+            }
+            let pos = positions.get_mut(entity).unwrap();
+            pos.x = dest_idx as i32 % map.width;
+            pos.y = dest_idx as i32 / map.width;
+            entity_moved
+                .insert(entity, EntityMoved {})
+                .expect("Unable to insert marker");
+            map.move_entity(entity, dest_idx);
+            if let Some(viewshed) = viewsheds.get_mut(entity) {
+                viewshed.dirty = true;
+            }
+        }
 
-        for done in turn_done.iter() {
-            turns.remove(*done);
+        for (entity, _) in (&entities, &want_approach).join() {
+            turns.remove(entity);
         }
+        want_approach.clear();
     }
 }
@@ -1,8 +1,17 @@
 use crate::pathfinding::a_star::a_star_search;
+use bracket_lib::{
+    prelude::Algorithm2D,
+    terminal::{DistanceAlg, Point},
+};
 use specs::prelude::*;
 
-use super::{Chasing, EntityMoved, Map, MyTurn, Position, Viewshed};
-use std::collections::HashMap;
+use super::{
+    clear_step, Chasing, EntityMoved, Map, MyTurn, OpensDoors, Position, RandomGen, TurnCounter,
+    Viewshed, Walker, WantsToOpenDoor, CHASE_SEARCH_TURNS,
+};
+
+const CHASE_MEMORY: i32 = 150; // ticks before a lost target is forgotten
+const SEARCH_RADIUS: f32 = 4.0;
 
 pub struct ChaseAI {}
 
@@ -16,6 +25,10 @@ impl<'a> System<'a> for ChaseAI {
         WriteStorage<'a, Viewshed>,
         WriteStorage<'a, EntityMoved>,
         Entities<'a>,
+        ReadExpect<'a, TurnCounter>,
+        WriteExpect<'a, RandomGen>,
+        ReadStorage<'a, OpensDoors>,
+        WriteStorage<'a, WantsToOpenDoor>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -27,16 +40,18 @@ impl<'a> System<'a> for ChaseAI {
             mut viewsheds,
             mut entity_moved,
             entities,
+            turn_counter,
+            mut rng,
+            opens_doors,
+            mut want_open,
         ) = data;
 
-        let mut targets: HashMap<Entity, (i32, i32)> = HashMap::new();
         let mut end_chase = Vec::<Entity>::new();
-        for (entity, _, chasing) in (&entities, &turns, &chasing).join() {
-            let target_pos = positions.get(chasing.target);
-
-            if let Some(target_pos) = target_pos {
-                targets.insert(entity, (target_pos.x, target_pos.y));
-            } else {
+        for (entity, _, chase) in (&entities, &turns, &chasing).join() {
+            if positions.get(chase.target).is_none()
+                || turn_counter.turn - chase.seen_turn > CHASE_MEMORY
+                || chase.search_turns <= 0
+            {
                 end_chase.push(entity);
             }
         }
@@ -47,31 +62,53 @@ impl<'a> System<'a> for ChaseAI {
         end_chase.clear();
 
         let mut turn_done = Vec::<Entity>::new();
-        for (entity, mut pos, _chase, mut viewshed, _) in
-            (&entities, &mut positions, &chasing, &mut viewsheds, &turns).join()
+        let mut moves = Vec::<(Entity, usize)>::new();
+        for (entity, pos, chase, _, _) in
+            (&entities, &positions, &mut chasing, &viewsheds, &turns).join()
         {
             turn_done.push(entity);
-            let target_pos = targets[&entity];
-            let path = a_star_search(
-                map.coord_to_index(pos.x, pos.y) as i32,
-                map.coord_to_index(target_pos.0, target_pos.1) as i32,
-                &*map,
-            );
-            if path.success && path.steps.len() > 1 && path.steps.len() < 15 {
-                let idx = map.coord_to_index(pos.x, pos.y);
-                pos.x = path.steps[1] as i32 % map.width;
-                pos.y = path.steps[1] as i32 / map.width;
-                entity_moved
-                    .insert(entity, EntityMoved {})
-                    .expect("Unable to insert marker");
-                let dest_idx = map.coord_to_index(pos.x, pos.y);
+            let idx = map.point2d_to_index(pos.into_point());
 
-                map.move_entity(entity, idx, dest_idx);
-                viewshed.dirty = true;
-                turn_done.push(entity);
+            let mut destination = None;
+            if chase.search_turns == CHASE_SEARCH_TURNS && pos.into_point() != chase.last_seen {
+                let walker = Walker::new(&map, opens_doors.contains(entity));
+                let path = a_star_search(idx, map.point2d_to_index(chase.last_seen), &walker);
+                if path.success && path.steps.len() > 1 {
+                    destination = Some(path.steps[1]);
+                } else {
+                    chase.search_turns -= 1;
+                }
             } else {
+                destination = search_step(pos.into_point(), chase.last_seen, &map, &mut rng);
+                chase.search_turns -= 1;
+            }
+
+            if chase.search_turns <= 0 {
                 end_chase.push(entity);
             }
+
+            if let Some(dest_idx) = destination {
+                moves.push((entity, dest_idx));
+            }
+        }
+
+        // Only the mobs that actually step get their position written, so the
+        // rest are not flagged as moved.
+        for (entity, dest_idx) in moves {
+            if !clear_step(entity, dest_idx, &map, &opens_doors, &mut want_open) {
+                continue;
+            }
+            let dest = map.index_to_point2d(dest_idx);
+            let pos = positions.get_mut(entity).unwrap();
+            pos.x = dest.x;
+            pos.y = dest.y;
+            entity_moved
+                .insert(entity, EntityMoved {})
+                .expect("Unable to insert marker");
+            map.move_entity(entity, dest_idx);
+            if let Some(viewshed) = viewsheds.get_mut(entity) {
+                viewshed.dirty = true;
+            }
         }
 
         for done in end_chase.iter() {
@@ -82,3 +119,25 @@ impl<'a> System<'a> for ChaseAI {
         }
     }
 }
+
+/// Picks a random open neighbour that stays close to where the target was last seen.
+fn search_step(from: Point, last_seen: Point, map: &Map, rng: &mut RandomGen) -> Option<usize> {
+    let mut candidates = Vec::<usize>::new();
+    for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+        let pt = Point::new(from.x + dx, from.y + dy);
+        if !map.in_bounds(pt) {
+            continue;
+        }
+        let idx = map.point2d_to_index(pt);
+        if !map.is_blocked(idx)
+            && DistanceAlg::Pythagoras.distance2d(pt, last_seen) <= SEARCH_RADIUS
+        {
+            candidates.push(idx);
+        }
+    }
+
+    if candidates.is_empty() {
+        return None;
+    }
+    Some(candidates[rng.range(0, candidates.len())])
+}
@@ -1,6 +1,9 @@
 use crate::{map::tiles::is_tile_walkable, pathfinding::a_star::a_star_search};
 
-use super::{EntityMoved, Map, Movement, MovementMode, MyTurn, Position, RandomGen, Viewshed};
+use super::{
+    clear_step, EntityMoved, Map, Movement, MovementMode, MyTurn, OpensDoors, Position, RandomGen,
+    Viewshed, Walker, WantsToOpenDoor,
+};
 use bracket_lib::prelude::Algorithm2D;
 use specs::prelude::*;
 
@@ -16,6 +19,9 @@ impl<'a> System<'a> for DefaultMoveAI {
         WriteStorage<'a, Viewshed>,
         WriteStorage<'a, EntityMoved>,
         Entities<'a>,
+        WriteExpect<'a, RandomGen>,
+        ReadStorage<'a, OpensDoors>,
+        WriteStorage<'a, WantsToOpenDoor>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -27,13 +33,17 @@ impl<'a> System<'a> for DefaultMoveAI {
             mut viewsheds,
             mut entity_moved,
             entities,
+            mut rng,
+            opens_doors,
+            mut want_open,
         ) = data;
 
+        // Joined over a copy of the mask so that positions are only fetched
+        // mutably, and flagged as modified, for mobs that actually step.
         let mut turn_done = Vec::<Entity>::new();
-        let mut rng = RandomGen::default();
-        for (entity, mut pos, mut movement, mut viewshed, _) in (
+        for (entity, _, movement, viewshed, _) in (
             &entities,
-            &mut positions,
+            &positions.mask().clone(),
             &mut move_mode,
             &mut viewsheds,
             &turns,
@@ -41,6 +51,7 @@ impl<'a> System<'a> for DefaultMoveAI {
             .join()
         {
             turn_done.push(entity);
+            let pos = *positions.get(entity).unwrap();
 
             match &mut movement.mode {
                 Movement::Static => {}
@@ -60,28 +71,27 @@ impl<'a> System<'a> for DefaultMoveAI {
                         return;
                     }
 
-                    let idx = map.point2d_to_index(pos.into_point());
+                    let pos = positions.get_mut(entity).unwrap();
                     pos.x = pt.x;
                     pos.y = pt.y;
 
                     entity_moved
                         .insert(entity, EntityMoved {})
                         .expect("Unable to insert marker");
-                    map.move_entity(entity, idx, dest_idx);
+                    map.move_entity(entity, dest_idx);
                     viewshed.dirty = true;
                 }
                 Movement::Waypoint { path } => {
                     if let Some(path) = path {
-                        let idx = map.coord_to_index(pos.x, pos.y);
                         if path.len() > 1 {
-                            if !map.is_blocked(path[1]) {
+                            if clear_step(entity, path[1], &map, &opens_doors, &mut want_open) {
+                                let pos = positions.get_mut(entity).unwrap();
                                 pos.x = path[1] as i32 % map.width;
                                 pos.y = path[1] as i32 / map.width;
                                 entity_moved
                                     .insert(entity, EntityMoved {})
                                     .expect("Unable to insert marker");
-                                let dest_idx = map.coord_to_index(pos.x, pos.y);
-                                map.move_entity(entity, idx, dest_idx);
+                                map.move_entity(entity, path[1]);
                                 viewshed.dirty = true;
                                 path.remove(0);
                             }
@@ -97,7 +107,7 @@ impl<'a> System<'a> for DefaultMoveAI {
                             let path = a_star_search(
                                 map.coord_to_index(pos.x, pos.y) as i32,
                                 map.coord_to_index(target_x, target_y) as i32,
-                                &*map,
+                                &Walker::new(&map, opens_doors.contains(entity)),
                             );
                             if path.success && path.steps.len() > 1 {
                                 movement.mode = Movement::Waypoint {
@@ -1,10 +1,14 @@
-use super::{Attributes, EquipmentChanged, Equipped, InBackpack, Item, Log};
+use super::{
+    AttributeBonus, Attributes, EquipmentChanged, Equipped, InBackpack, Item, Log, Stackable,
+    StatusEffect, StatusKind,
+};
 use specs::prelude::*;
 use std::collections::HashMap;
 
 pub struct EncumbranceSystem {}
 
 impl<'a> System<'a> for EncumbranceSystem {
+    #[allow(clippy::type_complexity)]
     type SystemData = (
         WriteStorage<'a, EquipmentChanged>,
         Entities<'a>,
@@ -13,10 +17,24 @@ impl<'a> System<'a> for EncumbranceSystem {
         ReadStorage<'a, Equipped>,
         WriteStorage<'a, Attributes>,
         ReadExpect<'a, Entity>,
+        ReadStorage<'a, StatusEffect>,
+        ReadStorage<'a, AttributeBonus>,
+        ReadStorage<'a, Stackable>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut equip_dirty, entities, items, backpacks, wielded, mut attributes, player) = data;
+        let (
+            mut equip_dirty,
+            entities,
+            items,
+            backpacks,
+            wielded,
+            mut attributes,
+            player,
+            statuses,
+            attribute_bonuses,
+            stacks,
+        ) = data;
 
         if equip_dirty.is_empty() {
             return;
@@ -29,24 +47,46 @@ impl<'a> System<'a> for EncumbranceSystem {
 
         equip_dirty.clear();
 
-        for (item, equipped) in (&items, &wielded).join() {
+        for (entity, item, equipped) in (&entities, &items, &wielded).join() {
             if to_update.contains_key(&equipped.owner) {
+                let count = stacks.get(entity).map_or(1, |stack| stack.quantity) as f32;
                 let totals = to_update.get_mut(&equipped.owner).unwrap();
-                totals.0 += item.weight;
-                totals.1 += item.initiative_penalty;
+                totals.0 += item.weight * count;
+                totals.1 += item.initiative_penalty * count;
             }
         }
 
-        for (item, carried) in (&items, &backpacks).join() {
+        for (entity, item, carried) in (&entities, &items, &backpacks).join() {
             if to_update.contains_key(&carried.owner) {
+                let count = stacks.get(entity).map_or(1, |stack| stack.quantity) as f32;
                 let totals = to_update.get_mut(&carried.owner).unwrap();
-                totals.0 += item.weight;
-                totals.1 += item.initiative_penalty;
+                totals.0 += item.weight * count;
+                totals.1 += item.initiative_penalty * count;
             }
         }
 
         for (entity, (weight, initiative)) in to_update.iter() {
             if let Some(attr) = attributes.get_mut(*entity) {
+                attr.might.modifiers = 0;
+                attr.fitness.modifiers = 0;
+                attr.quickness.modifiers = 0;
+                attr.intelligence.modifiers = 0;
+                for status in statuses.join() {
+                    if let (true, StatusKind::AttributeModifier { attribute, amount }) =
+                        (status.target == *entity, status.kind)
+                    {
+                        attr.attribute_mut(attribute).modifiers += amount;
+                    }
+                }
+                for (bonus, equipped) in (&attribute_bonuses, &wielded).join() {
+                    if equipped.owner != *entity {
+                        continue;
+                    }
+                    for (attribute, amount) in bonus.modifiers.iter() {
+                        attr.attribute_mut(*attribute).modifiers += amount;
+                    }
+                }
+
                 if *entity != *player {
                     continue;
                 }
@@ -1,7 +1,12 @@
-use super::{EntityMoved, Map, MyTurn, Position, Viewshed, WantsToFlee};
-use bracket_lib::prelude::DijkstraMap;
+use crate::pathfinding::flow_map::{next_step, FlowGoal, FlowMaps};
 use specs::prelude::*;
 
+use super::{
+    clear_step, faction_sources, reaction_to, EntityMoved, Faction, Map, Morale, MyTurn,
+    OpensDoors, Position, Reaction, TurnCounter, Viewshed, Walker, WantsToFlee, WantsToOpenDoor,
+    RAWS,
+};
+
 pub struct FleeAI {}
 
 impl<'a> System<'a> for FleeAI {
@@ -14,6 +19,13 @@ impl<'a> System<'a> for FleeAI {
         WriteStorage<'a, Viewshed>,
         WriteStorage<'a, EntityMoved>,
         Entities<'a>,
+        ReadStorage<'a, Faction>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, FlowMaps>,
+        ReadExpect<'a, TurnCounter>,
+        ReadStorage<'a, Morale>,
+        ReadStorage<'a, OpensDoors>,
+        WriteStorage<'a, WantsToOpenDoor>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -25,46 +37,82 @@ impl<'a> System<'a> for FleeAI {
             mut viewsheds,
             mut entity_moved,
             entities,
+            factions,
+            player,
+            mut flow_maps,
+            turn_counter,
+            morale,
+            opens_doors,
+            mut want_open,
         ) = data;
 
-        let mut turn_done = Vec::<Entity>::new();
-        for (entity, mut pos, flee, mut viewshed, _myturn) in (
-            &entities,
-            &mut positions,
-            &want_flee,
-            &mut viewsheds,
-            &turns,
-        )
-            .join()
-        {
-            turn_done.push(entity);
-            let my_idx = map.coord_to_index(pos.x, pos.y);
-            map.populate_blocked();
-            let flee_map = DijkstraMap::new(
-                map.width as usize,
-                map.height as usize,
-                &flee.indices,
-                &*map,
-                100.0,
-            );
-            if let Some(flee_target) = DijkstraMap::find_highest_exit(&flee_map, my_idx, &*map) {
-                if !map.is_blocked(flee_target) {
-                    map.move_entity(entity, my_idx, flee_target);
-                    viewshed.dirty = true;
-                    pos.x = flee_target as i32 % map.width;
-                    pos.y = flee_target as i32 / map.width;
-                    entity_moved
-                        .insert(entity, EntityMoved {})
-                        .expect("Unable to insert marker");
+        let player_idx = positions
+            .get(*player)
+            .map(|pos| map.coord_to_index(pos.x, pos.y));
+
+        let mut moves = Vec::<(Entity, usize)>::new();
+        for (entity, pos, flee, _) in (&entities, &positions, &want_flee, &turns).join() {
+            let goal = match factions.get(entity) {
+                Some(faction) if flee.indices.iter().any(|idx| Some(*idx) != player_idx) => {
+                    // Broken mobs, and kiters backing off, run from what they would attack.
+                    let from_enemies = flee.indices.iter().any(|idx| {
+                        map.index.entities_at(*idx).any(|other| {
+                            factions.get(other).is_some_and(|other_faction| {
+                                reaction_to(&faction.name, other_faction, &RAWS.lock().unwrap())
+                                    == Reaction::Attack
+                            })
+                        })
+                    });
+                    if from_enemies || morale.get(entity).is_some_and(|m| m.broken) {
+                        FlowGoal::Enemies(faction.name.clone())
+                    } else {
+                        FlowGoal::Threats(faction.name.clone())
+                    }
+                }
+                _ => FlowGoal::AwayFromPlayer,
+            };
+            let flow = flow_maps.get(&goal, &map, turn_counter.turn, || match &goal {
+                FlowGoal::Threats(name) => {
+                    faction_sources(name, Reaction::Flee, &entities, &factions, &positions, &map)
                 }
+                FlowGoal::Enemies(name) => faction_sources(
+                    name,
+                    Reaction::Attack,
+                    &entities,
+                    &factions,
+                    &positions,
+                    &map,
+                ),
+                _ => player_idx.into_iter().collect(),
+            });
+
+            let idx = map.coord_to_index(pos.x, pos.y);
+            let walker = Walker::new(&map, opens_doors.contains(entity));
+            if let Some(step) = next_step(flow, idx, &walker) {
+                moves.push((entity, step));
             }
         }
 
-        want_flee.clear();
+        for (entity, dest_idx) in moves {
+            if !clear_step(entity, dest_idx, &map, &opens_doors, &mut want_open) {
+                continue;
+            }
+            let pos = positions.get_mut(entity).unwrap();
+            pos.x = dest_idx as i32 % map.width;
+            pos.y = dest_idx as i32 / map.width;
+            entity_moved
+                .insert(entity, EntityMoved {})
+                .expect("Unable to insert marker");
+            map.move_entity(entity, dest_idx);
+            if let Some(viewshed) = viewsheds.get_mut(entity) {
+                viewshed.dirty = true;
+            }
+        }
 
         // Remove turn marker for those that are done
-        for done in turn_done.iter() {
-            turns.remove(*done);
+        for (entity, _) in (&entities, &want_flee).join() {
+            turns.remove(entity);
         }
+        want_flee.clear();
     }
 }
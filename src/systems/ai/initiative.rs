@@ -1,10 +1,17 @@
-use super::super::{Attributes, Initiative, MyTurn, Position, RandomGen, RunState};
+use super::super::{
+    Attributes, HungerClock, HungerState, Initiative, Log, MyTurn, Pools, Position, RandomGen,
+    RunState, StatusEffect, StatusKind, SufferDamage, TurnCounter,
+};
 use bracket_lib::terminal::{DistanceAlg, Point};
 use specs::prelude::*;
+use std::collections::HashMap;
+
+const MANA_REGEN_CHANCE: i32 = 4;
 
 pub struct InitiativeSystem {}
 
 impl<'a> System<'a> for InitiativeSystem {
+    #[allow(clippy::type_complexity)]
     type SystemData = (
         WriteStorage<'a, Initiative>,
         ReadStorage<'a, Position>,
@@ -14,6 +21,12 @@ impl<'a> System<'a> for InitiativeSystem {
         WriteExpect<'a, RunState>,
         ReadExpect<'a, Entity>,
         ReadExpect<'a, Point>,
+        WriteExpect<'a, RandomGen>,
+        WriteStorage<'a, Pools>,
+        ReadStorage<'a, StatusEffect>,
+        WriteStorage<'a, HungerClock>,
+        WriteStorage<'a, SufferDamage>,
+        WriteExpect<'a, TurnCounter>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -26,13 +39,28 @@ impl<'a> System<'a> for InitiativeSystem {
             mut runstate,
             player,
             player_pos,
+            mut rng,
+            mut pools,
+            statuses,
+            mut hunger_clocks,
+            mut suffer_damage,
+            mut turn_counter,
         ) = data;
-        let mut rng = RandomGen::default();
-
         if *runstate != RunState::Ticking {
             return;
         }
         turns.clear();
+        turn_counter.turn += 1;
+
+        let mut speed_modifiers: HashMap<Entity, i32> = HashMap::new();
+        for status in statuses.join() {
+            let modifier = match status.kind {
+                StatusKind::Slow { initiative_penalty } => initiative_penalty,
+                StatusKind::Haste { initiative_bonus } => -initiative_bonus,
+                _ => continue,
+            };
+            *speed_modifiers.entry(status.target).or_insert(0) += modifier;
+        }
 
         for (entity, initiative, pos) in (&entities, &mut initiatives, &positions).join() {
             initiative.current -= 1;
@@ -47,7 +75,35 @@ impl<'a> System<'a> for InitiativeSystem {
             initiative.current = 6 + rng.range(0, 6);
 
             if let Some(attr) = attributes.get(entity) {
-                initiative.current += attr.initiative_bonus();
+                initiative.current += attr.initiative_penalty();
+            }
+
+            if let Some(modifier) = speed_modifiers.get(&entity) {
+                initiative.current += modifier;
+            }
+
+            if let Some(clock) = hunger_clocks.get_mut(entity) {
+                if let Some(state) = clock.tick() {
+                    if entity == *player {
+                        let log = Log::new().append("You are");
+                        let log = if state == HungerState::Normal {
+                            log.append("no longer well fed")
+                        } else {
+                            log.bad(&state.name().to_lowercase())
+                        };
+                        log.append(".").build();
+                    }
+                }
+                if clock.state == HungerState::Starving {
+                    SufferDamage::new_damage(&mut suffer_damage, entity, 1, false);
+                }
+                initiative.current += clock.state.initiative_penalty();
+            }
+
+            if let Some(pools) = pools.get_mut(entity) {
+                if pools.mana.current < pools.mana.max && rng.roll_dice(1, MANA_REGEN_CHANCE) == 1 {
+                    pools.mana.current += 1;
+                }
             }
 
             if entity == *player {
@@ -1,18 +1,32 @@
+use specs::prelude::*;
+
 use super::super::{
-    action::{WantsToApproach, WantsToFlee, WantsToMelee},
-    effect::Confusion,
-    item::{EquipmentChanged, Equipped, InBackpack, Item},
-    map::Map,
+    action::{
+        WantsToApproach, WantsToCastSpell, WantsToFlee, WantsToMelee, WantsToOpenDoor,
+        WantsToShoot, WantsToUseItem,
+    },
+    effect::{
+        DamageType, ProvidesHealing, Ranged, Resistances, SpellTemplate, StatusEffect, StatusKind,
+    },
+    item::{
+        Ammunition, AttributeBonus, EquipmentChanged, Equipped, Identification, InBackpack, Item,
+        RangedWeapon, Stackable,
+    },
+    map::{Map, Walker},
+    props::OpensDoors,
     raws::{
-        factions::{faction_reaction, Reaction},
+        behaviors::{find_behavior, BehaviorRaw},
+        factions::{reaction_to, Reaction},
         RAWS,
     },
     rng::RandomGen,
     state::RunState,
     unit::{
-        Attributes, Chasing, EntityMoved, Faction, Movement, MovementMode, MyTurn, Quips, Viewshed,
+        Attributes, Behavior, Chasing, EntityMoved, Faction, KnownSpells, Morale, Movement,
+        MovementMode, MyTurn, Pools, Quips, Scattering, SquadLeader, SquadMember, SufferDamage,
+        Viewshed, CHASE_SEARCH_TURNS,
     },
-    Log, Name, Position,
+    Log, Name, Position, TurnCounter,
 };
 
 pub mod adjacent;
@@ -22,6 +36,74 @@ pub mod default;
 pub mod encumbrance;
 pub mod fleeing;
 pub mod initiative;
+pub mod morale;
 pub mod quipping;
+pub mod squad;
 pub mod turn_status;
+pub mod utility;
 pub mod visible;
+
+/// Whether `entity` can step onto `idx` right now. A mob that can open doors
+/// spends the move opening a closed one that stands in its way.
+pub fn clear_step(
+    entity: Entity,
+    idx: usize,
+    map: &Map,
+    opens_doors: &ReadStorage<OpensDoors>,
+    want_open: &mut WriteStorage<WantsToOpenDoor>,
+) -> bool {
+    if !map.is_blocked(idx) {
+        return true;
+    }
+    if let (true, Some(door)) = (opens_doors.contains(entity), map.index.closed_door(idx)) {
+        want_open
+            .insert(entity, WantsToOpenDoor { door })
+            .expect("Unable to insert");
+    }
+    false
+}
+
+/// Spells `entity` knows and has the mana for, with their range.
+pub fn castable_spells(
+    entity: Entity,
+    entities: &Entities,
+    known_spells: &ReadStorage<KnownSpells>,
+    pools: &ReadStorage<Pools>,
+    names: &ReadStorage<Name>,
+    spell_templates: &ReadStorage<SpellTemplate>,
+    ranged: &ReadStorage<Ranged>,
+) -> Vec<(Entity, i32)> {
+    let mut castable = Vec::<(Entity, i32)>::new();
+    if let (Some(known), Some(my_pools)) = (known_spells.get(entity), pools.get(entity)) {
+        for spell in known.spells.iter() {
+            if spell.mana_cost > my_pools.mana.current {
+                continue;
+            }
+            for (spell_entity, name, _, spell_range) in
+                (entities, names, spell_templates, ranged).join()
+            {
+                if name.name == spell.display_name {
+                    castable.push((spell_entity, spell_range.range));
+                }
+            }
+        }
+    }
+    castable
+}
+
+/// Tiles of everything the faction reacts to with `reaction`.
+pub fn faction_sources(
+    faction: &str,
+    reaction: Reaction,
+    entities: &Entities,
+    factions: &ReadStorage<Faction>,
+    positions: &WriteStorage<Position>,
+    map: &Map,
+) -> Vec<usize> {
+    let raws = RAWS.lock().unwrap();
+    (entities, factions, positions)
+        .join()
+        .filter(|(_, other, _)| reaction_to(faction, other, &raws) == reaction)
+        .map(|(_, _, pos)| map.coord_to_index(pos.x, pos.y))
+        .collect()
+}
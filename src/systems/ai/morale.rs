@@ -0,0 +1,154 @@
+use bracket_lib::terminal::DistanceAlg;
+use specs::prelude::*;
+
+use super::{
+    Attributes, Chasing, Faction, Identification, InBackpack, Log, Map, Morale, MyTurn, Name,
+    Pools, Position, ProvidesHealing, Scattering, SquadMember, WantsToUseItem,
+};
+
+const ALLY_RADIUS: f32 = 6.0;
+const MAX_ALLIES: i32 = 3;
+const LEADER_RADIUS: f32 = 8.0;
+const LEADER_BONUS: i32 = 4;
+/// How far above breaking point morale has to climb before a broken mob fights again.
+const RECOVERY: i32 = 3;
+
+pub struct MoraleSystem {}
+
+impl<'a> System<'a> for MoraleSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        WriteStorage<'a, MyTurn>,
+        WriteStorage<'a, Morale>,
+        ReadStorage<'a, Pools>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, Faction>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, SquadMember>,
+        ReadStorage<'a, Scattering>,
+        WriteStorage<'a, Chasing>,
+        ReadStorage<'a, InBackpack>,
+        ReadStorage<'a, ProvidesHealing>,
+        WriteStorage<'a, WantsToUseItem>,
+        ReadStorage<'a, Name>,
+        ReadExpect<'a, Identification>,
+        ReadExpect<'a, Map>,
+        ReadExpect<'a, Entity>,
+        Entities<'a>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            mut turns,
+            mut morale,
+            pools,
+            attributes,
+            factions,
+            positions,
+            members,
+            scattering,
+            mut chasing,
+            backpack,
+            healing,
+            mut wants_use,
+            names,
+            identification,
+            map,
+            player,
+            entities,
+        ) = data;
+
+        let player_level = attributes.get(*player).map_or(1, |attr| attr.level);
+
+        let mut turn_done = Vec::<Entity>::new();
+        for (entity, morale, stats, attr, faction, pos, _) in (
+            &entities,
+            &mut morale,
+            &pools,
+            &attributes,
+            &factions,
+            &positions,
+            &turns,
+        )
+            .join()
+        {
+            let allies = map
+                .index
+                .entities_in_radius(pos.into_point(), ALLY_RADIUS)
+                .into_iter()
+                .filter(|other| *other != entity)
+                .filter(|other| factions.get(*other).is_some_and(|f| f.name == faction.name))
+                .filter(|other| pools.get(*other).is_some_and(|p| p.hit_points.current > 0))
+                .count() as i32;
+
+            let leader_near = members
+                .get(entity)
+                .and_then(|member| positions.get(member.leader))
+                .is_some_and(|leader_pos| {
+                    DistanceAlg::Pythagoras.distance2d(pos.into_point(), leader_pos.into_point())
+                        <= LEADER_RADIUS
+                });
+
+            let wounds = (stats.hit_points.max - stats.hit_points.current) * 10
+                / stats.hit_points.max.max(1);
+
+            let value = morale.courage + allies.min(MAX_ALLIES) * 2
+                - (player_level - attr.level).max(0) * 2
+                - wounds
+                + if leader_near { LEADER_BONUS } else { 0 };
+
+            let was_broken = morale.broken;
+            morale.broken =
+                scattering.contains(entity) || value < 0 || (was_broken && value < RECOVERY);
+
+            let visible = map.tiles[map.coord_to_index(pos.x, pos.y)].visible;
+            let name = names
+                .get(entity)
+                .map(|n| n.name.clone())
+                .unwrap_or_default();
+            if morale.broken && !was_broken {
+                chasing.remove(entity);
+                if visible {
+                    Log::new().npc(&name).append("panics and flees!").build();
+                }
+            } else if !morale.broken && was_broken && visible {
+                Log::new().npc(&name).append("regains its courage.").build();
+            }
+
+            // A broken mob with a healing potion on hand drinks it instead of running.
+            if !morale.broken || stats.hit_points.current >= stats.hit_points.max {
+                continue;
+            }
+            let potion = (&entities, &backpack, &healing)
+                .join()
+                .find(|(_, pack, _)| pack.owner == entity)
+                .map(|(item, _, _)| item);
+            if let Some(potion) = potion {
+                wants_use
+                    .insert(
+                        entity,
+                        WantsToUseItem {
+                            item: potion,
+                            target: None,
+                        },
+                    )
+                    .expect("Unable to insert");
+                if visible {
+                    let potion_name = names
+                        .get(potion)
+                        .map(|n| identification.display_name(&n.name));
+                    Log::new()
+                        .npc(&name)
+                        .append("drinks a")
+                        .item(&potion_name.unwrap_or_default())
+                        .build();
+                }
+                turn_done.push(entity);
+            }
+        }
+
+        for done in turn_done.iter() {
+            turns.remove(*done);
+        }
+    }
+}
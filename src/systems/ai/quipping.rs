@@ -12,11 +12,11 @@ impl<'a> System<'a> for QuipSystem {
         ReadStorage<'a, MyTurn>,
         ReadExpect<'a, Point>,
         ReadStorage<'a, Viewshed>,
+        WriteExpect<'a, RandomGen>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut quips, names, turns, player_pos, viewsheds) = data;
-        let mut rng = RandomGen::default();
+        let (mut quips, names, turns, player_pos, viewsheds, mut rng) = data;
 
         for (quip, source, viewshed, _) in (&mut quips, &names, &viewsheds, &turns).join() {
             if !viewshed.visible_tiles.contains(&player_pos) || rng.range(0, 6) > 0 {
@@ -0,0 +1,274 @@
+use crate::pathfinding::a_star::a_star_search;
+use bracket_lib::{
+    prelude::Algorithm2D,
+    terminal::{DistanceAlg, Point},
+};
+use specs::prelude::*;
+use std::collections::HashMap;
+
+use super::{
+    clear_step, Chasing, EntityMoved, Faction, Log, Map, Morale, MyTurn, Name, OpensDoors,
+    Position, Scattering, SquadLeader, SquadMember, TurnCounter, Viewshed, Walker, WantsToOpenDoor,
+};
+
+const SCATTER_TURNS: i32 = 20;
+
+pub struct SquadAI {}
+
+impl<'a> System<'a> for SquadAI {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        WriteStorage<'a, MyTurn>,
+        WriteStorage<'a, SquadLeader>,
+        WriteStorage<'a, SquadMember>,
+        WriteStorage<'a, Scattering>,
+        WriteStorage<'a, Chasing>,
+        WriteStorage<'a, Position>,
+        WriteExpect<'a, Map>,
+        WriteStorage<'a, Viewshed>,
+        WriteStorage<'a, EntityMoved>,
+        Entities<'a>,
+        ReadStorage<'a, Faction>,
+        ReadStorage<'a, Name>,
+        ReadExpect<'a, TurnCounter>,
+        ReadStorage<'a, Morale>,
+        ReadStorage<'a, OpensDoors>,
+        WriteStorage<'a, WantsToOpenDoor>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            mut turns,
+            mut leaders,
+            mut members,
+            mut scattering,
+            mut chasing,
+            mut positions,
+            mut map,
+            mut viewsheds,
+            mut entity_moved,
+            entities,
+            factions,
+            names,
+            turn_counter,
+            morale,
+            opens_doors,
+            mut want_open,
+        ) = data;
+        let turn = turn_counter.turn;
+
+        // New leaders pick up the listed mobs of their faction standing around them.
+        let mut forming = Vec::<(Entity, Point, String, Vec<String>, i32)>::new();
+        for (entity, leader, pos, faction) in
+            (&entities, &mut leaders, &positions, &factions).join()
+        {
+            if !leader.formed {
+                leader.formed = true;
+                forming.push((
+                    entity,
+                    pos.into_point(),
+                    faction.name.clone(),
+                    leader.followers.clone(),
+                    leader.radius,
+                ));
+            }
+        }
+        for (leader, leader_pos, leader_faction, followers, radius) in forming.iter() {
+            let mut recruits = Vec::<(Entity, Point)>::new();
+            for (entity, name, pos, faction, _, _) in (
+                &entities, &names, &positions, &factions, !&members, !&leaders,
+            )
+                .join()
+            {
+                if faction.name == *leader_faction
+                    && followers.contains(&name.name)
+                    && DistanceAlg::Pythagoras.distance2d(*leader_pos, pos.into_point())
+                        <= *radius as f32
+                {
+                    recruits.push((entity, pos.into_point() - *leader_pos));
+                }
+            }
+            for (entity, slot) in recruits {
+                members
+                    .insert(
+                        entity,
+                        SquadMember {
+                            leader: *leader,
+                            slot,
+                        },
+                    )
+                    .expect("Unable to insert");
+            }
+        }
+
+        // Followers scatter once their leader is gone.
+        let mut leaderless = Vec::<Entity>::new();
+        for (entity, member) in (&entities, &members).join() {
+            if !entities.is_alive(member.leader) {
+                leaderless.push(entity);
+            }
+        }
+        for entity in leaderless {
+            members.remove(entity);
+            chasing.remove(entity);
+            scattering
+                .insert(
+                    entity,
+                    Scattering {
+                        until_turn: turn + SCATTER_TURNS,
+                    },
+                )
+                .expect("Unable to insert");
+            if let (Some(pos), Some(name)) = (positions.get(entity), names.get(entity)) {
+                if map.tiles[map.coord_to_index(pos.x, pos.y)].visible {
+                    Log::new()
+                        .npc(&name.name)
+                        .append("scatters in panic!")
+                        .build();
+                }
+            }
+        }
+
+        let mut regrouped = Vec::<Entity>::new();
+        for (entity, scatter) in (&entities, &scattering).join() {
+            if scatter.until_turn <= turn {
+                regrouped.push(entity);
+            }
+        }
+        for entity in regrouped {
+            scattering.remove(entity);
+        }
+
+        // Whoever spots an enemy this turn passes it on to the rest of the squad.
+        let squad_of = |entity: Entity| match members.get(entity) {
+            Some(member) => Some(member.leader),
+            None if leaders.contains(entity) => Some(entity),
+            None => None,
+        };
+        let mut sightings = HashMap::<Entity, (Entity, Point)>::new();
+        for (entity, chase) in (&entities, &chasing).join() {
+            if chase.seen_turn != turn {
+                continue;
+            }
+            if let Some(squad) = squad_of(entity) {
+                sightings
+                    .entry(squad)
+                    .or_insert((chase.target, chase.last_seen));
+            }
+        }
+        let mut alerted = Vec::<(Entity, Entity, Point)>::new();
+        for (entity, _) in (&entities, &positions).join() {
+            if morale.get(entity).is_some_and(|m| m.broken) {
+                continue;
+            }
+            let sighting = squad_of(entity).and_then(|squad| sightings.get(&squad));
+            if let Some((target, last_seen)) = sighting {
+                if chasing
+                    .get(entity)
+                    .is_none_or(|chase| chase.seen_turn != turn)
+                {
+                    alerted.push((entity, *target, *last_seen));
+                }
+            }
+        }
+        for (entity, target, last_seen) in alerted {
+            chasing
+                .insert(entity, Chasing::new(target, last_seen, turn))
+                .expect("Unable to insert");
+        }
+
+        // Idle followers fall back into formation around their leader.
+        let mut turn_done = Vec::<Entity>::new();
+        let mut moves = Vec::<(Entity, usize)>::new();
+        for (entity, member, pos, _) in (&entities, &members, &positions, &turns).join() {
+            turn_done.push(entity);
+            let leader_pos = match positions.get(member.leader) {
+                Some(leader_pos) => leader_pos.into_point(),
+                None => continue,
+            };
+            let slot = leader_pos + member.slot;
+            if pos.into_point() == slot || !map.in_bounds(slot) {
+                continue;
+            }
+            let slot_idx = map.point2d_to_index(slot);
+            if map.is_blocked(slot_idx) {
+                continue;
+            }
+            let walker = Walker::new(&map, opens_doors.contains(entity));
+            let path = a_star_search(map.coord_to_index(pos.x, pos.y), slot_idx, &walker);
+            if path.success && path.steps.len() > 1 {
+                moves.push((entity, path.steps[1]));
+            }
+        }
+
+        for (entity, dest_idx) in moves {
+            if !clear_step(entity, dest_idx, &map, &opens_doors, &mut want_open) {
+                continue;
+            }
+            let pos = positions.get_mut(entity).unwrap();
+            pos.x = dest_idx as i32 % map.width;
+            pos.y = dest_idx as i32 / map.width;
+            entity_moved
+                .insert(entity, EntityMoved {})
+                .expect("Unable to insert marker");
+            map.move_entity(entity, dest_idx);
+            if let Some(viewshed) = viewsheds.get_mut(entity) {
+                viewshed.dirty = true;
+            }
+        }
+
+        for done in turn_done.iter() {
+            turns.remove(*done);
+        }
+    }
+}
+
+/// Sends a squad member toward the nearest free tile around its target that no
+/// squadmate has claimed yet, so a group spreads out instead of queueing.
+pub fn flank_step(
+    idx: usize,
+    target: usize,
+    walker: &Walker,
+    claimed: &mut Vec<usize>,
+) -> Option<usize> {
+    let map = walker.map;
+    let from = map.index_to_point2d(idx);
+    let target_pt = map.index_to_point2d(target);
+    if DistanceAlg::Pythagoras.distance2d(from, target_pt) < 1.5 {
+        return None;
+    }
+
+    let mut flanks = Vec::<usize>::new();
+    for (dx, dy) in [
+        (-1, 0),
+        (1, 0),
+        (0, -1),
+        (0, 1),
+        (-1, -1),
+        (1, -1),
+        (-1, 1),
+        (1, 1),
+    ] {
+        let pt = Point::new(target_pt.x + dx, target_pt.y + dy);
+        if !map.in_bounds(pt) {
+            continue;
+        }
+        let flank = map.point2d_to_index(pt);
+        if !map.is_blocked(flank) && !claimed.contains(&flank) {
+            flanks.push(flank);
+        }
+    }
+
+    let distance = |flank: &usize| {
+        DistanceAlg::PythagorasSquared.distance2d(from, map.index_to_point2d(*flank))
+    };
+    let flank = flanks
+        .into_iter()
+        .min_by(|a, b| distance(a).total_cmp(&distance(b)))?;
+    let path = a_star_search(idx, flank, walker);
+    if path.success && path.steps.len() > 1 {
+        claimed.push(flank);
+        return Some(path.steps[1]);
+    }
+    None
+}
@@ -1,42 +1,105 @@
-use super::{Confusion, MyTurn, RunState};
+use super::{
+    super::melee_combat::damage_resistance, DamageType, EquipmentChanged, Equipped, Log, MyTurn,
+    Pools, Resistances, RunState, StatusEffect, StatusKind, SufferDamage,
+};
 use specs::prelude::*;
 
 pub struct TurnStatusSystem {}
 
 impl<'a> System<'a> for TurnStatusSystem {
+    #[allow(clippy::type_complexity)]
     type SystemData = (
         WriteStorage<'a, MyTurn>,
-        WriteStorage<'a, Confusion>,
+        WriteStorage<'a, StatusEffect>,
         Entities<'a>,
-        ReadExpect<'a, RunState>,
+        WriteExpect<'a, RunState>,
+        ReadExpect<'a, Entity>,
+        WriteStorage<'a, SufferDamage>,
+        WriteStorage<'a, Pools>,
+        Read<'a, LazyUpdate>,
+        ReadStorage<'a, Resistances>,
+        ReadStorage<'a, Equipped>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut turns, mut confusion, entities, runstate) = data;
+        let (
+            mut turns,
+            mut statuses,
+            entities,
+            mut runstate,
+            player_entity,
+            mut suffer_damage,
+            mut pools,
+            lazy,
+            resistances,
+            equipped,
+        ) = data;
 
-        if *runstate != RunState::Ticking {
+        if *runstate != RunState::Ticking && *runstate != RunState::AwaitingInput {
             return;
         }
 
         let mut not_my_turn = Vec::<Entity>::new();
-        let mut not_confused = Vec::<Entity>::new();
+        let mut expired = Vec::<Entity>::new();
 
-        for (entity, _, confused) in (&entities, &mut turns, &mut confusion).join() {
-            confused.duration -= 1;
+        for (effect_entity, status) in (&entities, &mut statuses).join() {
+            if !entities.is_alive(status.target) {
+                expired.push(effect_entity);
+                continue;
+            }
+            if turns.get(status.target).is_none() {
+                continue;
+            }
 
-            if confused.duration < 1 {
-                not_confused.push(entity);
-            } else {
-                not_my_turn.push(entity);
+            match status.kind {
+                StatusKind::Confusion | StatusKind::Stun => not_my_turn.push(status.target),
+                StatusKind::Poison { damage } => {
+                    let resistance = damage_resistance(
+                        status.target,
+                        DamageType::Poison,
+                        &resistances,
+                        &equipped,
+                    );
+                    let damage = resistance.apply(damage);
+                    SufferDamage::new_damage(&mut suffer_damage, status.target, damage, false);
+                }
+                StatusKind::Bleed { damage } => {
+                    SufferDamage::new_damage(&mut suffer_damage, status.target, damage, false);
+                }
+                StatusKind::Regeneration { heal } => {
+                    if let Some(pools) = pools.get_mut(status.target) {
+                        pools.hit_points.current =
+                            i32::min(pools.hit_points.max, pools.hit_points.current + heal);
+                    }
+                }
+                _ => {}
+            }
+
+            status.duration -= 1;
+            if status.duration < 1 {
+                expired.push(effect_entity);
+                if let StatusKind::AttributeModifier { .. } = status.kind {
+                    lazy.insert(status.target, EquipmentChanged {});
+                }
+                if status.target == *player_entity {
+                    Log::new()
+                        .append("Your")
+                        .item(&status.kind.name())
+                        .append("status wears off.")
+                        .build();
+                }
             }
         }
 
         for e in not_my_turn {
             turns.remove(e);
+            if e == *player_entity {
+                *runstate = RunState::Ticking;
+            }
         }
 
-        for e in not_confused {
-            confusion.remove(e);
+        for e in expired {
+            entities.delete(e).expect("Unable to delete status effect");
         }
     }
 }
@@ -0,0 +1,490 @@
+use crate::pathfinding::a_star::a_star_search;
+use bracket_lib::{
+    prelude::Algorithm2D,
+    terminal::{DistanceAlg, Point},
+};
+use specs::prelude::*;
+
+use super::{
+    super::ranged_combat::{has_line_of_fire, in_range, loaded_ranged_weapon},
+    castable_spells, clear_step, find_behavior, reaction_to, Ammunition, Behavior, BehaviorRaw,
+    Chasing, EntityMoved, Equipped, Faction, Identification, InBackpack, KnownSpells, Log, Map,
+    Morale, MyTurn, Name, OpensDoors, Pools, Position, ProvidesHealing, Ranged, RangedWeapon,
+    Reaction, SpellTemplate, TurnCounter, Viewshed, Walker, WantsToApproach, WantsToCastSpell,
+    WantsToFlee, WantsToMelee, WantsToOpenDoor, WantsToShoot, WantsToUseItem, RAWS,
+};
+
+const HELP_RADIUS: f32 = 8.0;
+const HELP_COOLDOWN: i32 = 100;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Action {
+    Attack,
+    Approach,
+    Flee,
+    UseItem,
+    Guard,
+    Wander,
+    CallForHelp,
+}
+
+const ACTIONS: [Action; 7] = [
+    Action::Attack,
+    Action::Approach,
+    Action::Flee,
+    Action::UseItem,
+    Action::Guard,
+    Action::Wander,
+    Action::CallForHelp,
+];
+
+enum Shot {
+    Spell(Entity),
+    Weapon,
+}
+
+/// What a mob knows when it weighs its options.
+struct Situation {
+    pos: Point,
+    /// Visible enemies, nearest first, with their distance.
+    hostiles: Vec<(Entity, Point, f32)>,
+    threats: Vec<usize>,
+    health: f32,
+    broken: bool,
+    potion: Option<Entity>,
+    shot: Option<(Shot, Entity, Point)>,
+    can_shoot: bool,
+    idle_allies: Vec<Entity>,
+    home: Option<Point>,
+}
+
+impl Situation {
+    fn nearest(&self) -> Option<&(Entity, Point, f32)> {
+        self.hostiles.first()
+    }
+
+    fn distance_home(&self, pt: Point) -> f32 {
+        self.home
+            .map_or(0.0, |home| DistanceAlg::Pythagoras.distance2d(home, pt))
+    }
+}
+
+fn weight(profile: &BehaviorRaw, action: Action) -> f32 {
+    let weights = &profile.weights;
+    match action {
+        Action::Attack => weights.attack,
+        Action::Approach => weights.approach,
+        Action::Flee => weights.flee,
+        Action::UseItem => weights.use_item,
+        Action::Guard => weights.guard,
+        Action::Wander => weights.wander,
+        Action::CallForHelp => weights.call_for_help,
+    }
+    .unwrap_or(0.0)
+}
+
+/// How appealing `action` is right now, from 0 to 1, before the profile weighs it.
+fn consider(action: Action, profile: &BehaviorRaw, situation: &Situation) -> f32 {
+    let kiting = situation.can_shoot && profile.preferred_range.is_some();
+    let too_close = |distance: f32| {
+        profile
+            .preferred_range
+            .is_some_and(|range| distance < range as f32)
+    };
+    let leash = |pt: Point| match (profile.guard_radius, situation.home) {
+        (Some(radius), Some(_)) => situation.distance_home(pt) <= radius as f32,
+        _ => true,
+    };
+
+    match action {
+        Action::Attack => match situation.nearest() {
+            _ if situation.broken => 0.0,
+            Some((_, _, distance)) if *distance < 1.5 => {
+                if kiting {
+                    0.5
+                } else {
+                    1.0
+                }
+            }
+            Some(_) if situation.shot.is_some() => 0.9,
+            _ => 0.0,
+        },
+        Action::Approach => match situation.nearest() {
+            Some((_, pt, _)) if !situation.broken && leash(*pt) => 0.6,
+            _ => 0.0,
+        },
+        Action::Flee => match situation.nearest() {
+            _ if situation.broken => 1.0,
+            Some((_, _, distance)) if kiting && too_close(*distance) => 0.95,
+            _ if !situation.threats.is_empty() => 0.8,
+            _ => 0.0,
+        },
+        Action::UseItem if situation.potion.is_some() => 1.0 - situation.health,
+        Action::UseItem => 0.0,
+        Action::Guard => match profile.guard_radius {
+            Some(radius) if situation.distance_home(situation.pos) > radius as f32 => 1.0,
+            Some(_) => 0.3,
+            None => 0.0,
+        },
+        Action::Wander => 0.2,
+        Action::CallForHelp => {
+            if situation.broken || situation.hostiles.is_empty() || situation.idle_allies.is_empty()
+            {
+                0.0
+            } else {
+                0.7 + 0.3 * (1.0 - situation.health)
+            }
+        }
+    }
+}
+
+/// Scores every action a mob with a `Behavior` could take against its raws
+/// profile and emits the intent of the best one. Movement is left to the
+/// approach and flee systems; wandering leaves the turn to chase and default
+/// movement.
+pub struct UtilityAI {}
+
+impl<'a> System<'a> for UtilityAI {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        WriteStorage<'a, MyTurn>,
+        WriteStorage<'a, Behavior>,
+        ReadStorage<'a, Faction>,
+        WriteStorage<'a, Position>,
+        WriteExpect<'a, Map>,
+        WriteStorage<'a, Viewshed>,
+        WriteStorage<'a, EntityMoved>,
+        Entities<'a>,
+        ReadExpect<'a, TurnCounter>,
+        ReadStorage<'a, Morale>,
+        ReadStorage<'a, Pools>,
+        WriteStorage<'a, Chasing>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, InBackpack>,
+        ReadStorage<'a, ProvidesHealing>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, RangedWeapon>,
+        ReadStorage<'a, Ammunition>,
+        ReadStorage<'a, KnownSpells>,
+        ReadStorage<'a, SpellTemplate>,
+        ReadStorage<'a, Ranged>,
+        (
+            WriteStorage<'a, WantsToMelee>,
+            WriteStorage<'a, WantsToShoot>,
+            WriteStorage<'a, WantsToCastSpell>,
+            WriteStorage<'a, WantsToApproach>,
+            WriteStorage<'a, WantsToFlee>,
+            WriteStorage<'a, WantsToUseItem>,
+            WriteStorage<'a, WantsToOpenDoor>,
+        ),
+        ReadStorage<'a, OpensDoors>,
+        ReadExpect<'a, Identification>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            mut turns,
+            mut behaviors,
+            factions,
+            mut positions,
+            mut map,
+            mut viewsheds,
+            mut entity_moved,
+            entities,
+            turn_counter,
+            morale,
+            pools,
+            mut chasing,
+            names,
+            backpack,
+            healing,
+            equipped_items,
+            ranged_weapons,
+            ammunition,
+            known_spells,
+            spell_templates,
+            ranged,
+            (
+                mut want_melee,
+                mut want_shoot,
+                mut want_cast,
+                mut want_approach,
+                mut want_flee,
+                mut want_use,
+                mut want_open,
+            ),
+            opens_doors,
+            identification,
+        ) = data;
+        let raws = RAWS.lock().unwrap();
+        let turn = turn_counter.turn;
+
+        let mut turn_done = Vec::<Entity>::new();
+        let mut moves = Vec::<(Entity, usize)>::new();
+        let mut alerts = Vec::<(Entity, Entity, Point)>::new();
+        for (entity, behavior, faction, pos, viewshed, stats, _) in (
+            &entities,
+            &mut behaviors,
+            &factions,
+            &positions,
+            &viewsheds,
+            &pools,
+            &turns,
+        )
+            .join()
+        {
+            let profile = match find_behavior(&raws, &behavior.profile) {
+                Some(profile) => profile,
+                None => continue,
+            };
+            let my_pt = pos.into_point();
+            if behavior.home.is_none() {
+                behavior.home = Some(my_pt);
+            }
+
+            let mut hostiles = Vec::<(Entity, Point, f32)>::new();
+            let mut threats = Vec::<usize>::new();
+            for tile in viewshed.visible_tiles.iter() {
+                let idx = map.point2d_to_index(*tile);
+                for other in map.index.entities_at(idx) {
+                    if other == entity {
+                        continue;
+                    }
+                    match factions
+                        .get(other)
+                        .map(|other_faction| reaction_to(&faction.name, other_faction, &raws))
+                    {
+                        Some(Reaction::Attack) => hostiles.push((
+                            other,
+                            *tile,
+                            DistanceAlg::Pythagoras.distance2d(my_pt, *tile),
+                        )),
+                        Some(Reaction::Flee) => threats.push(idx),
+                        _ => {}
+                    }
+                }
+            }
+            hostiles.sort_by(|a, b| a.2.total_cmp(&b.2));
+
+            let weapon = loaded_ranged_weapon(
+                &entities,
+                &equipped_items,
+                &ranged_weapons,
+                &backpack,
+                &ammunition,
+                entity,
+            );
+            let spells = castable_spells(
+                entity,
+                &entities,
+                &known_spells,
+                &pools,
+                &names,
+                &spell_templates,
+                &ranged,
+            );
+            let shot = hostiles.iter().find_map(|(target, pt, _)| {
+                let in_line_of_fire =
+                    |range: i32| in_range(my_pt, *pt, range) && has_line_of_fire(&map, my_pt, *pt);
+                if let Some((spell, _)) = spells.iter().find(|(_, range)| in_line_of_fire(*range)) {
+                    Some((Shot::Spell(*spell), *target, *pt))
+                } else {
+                    match &weapon {
+                        Some((_, weapon)) if in_line_of_fire(weapon.range) => {
+                            Some((Shot::Weapon, *target, *pt))
+                        }
+                        _ => None,
+                    }
+                }
+            });
+
+            let potion = (&entities, &backpack, &healing)
+                .join()
+                .find(|(_, pack, _)| pack.owner == entity)
+                .map(|(item, _, _)| item);
+
+            let help_ready = behavior
+                .called_turn
+                .is_none_or(|called| turn - called >= HELP_COOLDOWN);
+            let idle_allies = if help_ready && !hostiles.is_empty() {
+                map.index
+                    .entities_in_radius(my_pt, HELP_RADIUS)
+                    .into_iter()
+                    .filter(|other| *other != entity && !chasing.contains(*other))
+                    .filter(|other| factions.get(*other).is_some_and(|f| f.name == faction.name))
+                    .filter(|other| pools.get(*other).is_some_and(|p| p.hit_points.current > 0))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+
+            let situation = Situation {
+                pos: my_pt,
+                hostiles,
+                threats,
+                health: stats.hit_points.current as f32 / stats.hit_points.max.max(1) as f32,
+                broken: morale.get(entity).is_some_and(|m| m.broken),
+                potion,
+                can_shoot: weapon.is_some() || !spells.is_empty(),
+                shot,
+                idle_allies,
+                home: behavior.home,
+            };
+
+            let mut best = (Action::Wander, 0.0);
+            for action in ACTIONS {
+                let score = weight(profile, action) * consider(action, profile, &situation);
+                if score > best.1 {
+                    best = (action, score);
+                }
+            }
+
+            let visible = map.tiles[map.coord_to_index(pos.x, pos.y)].visible;
+            let name = names
+                .get(entity)
+                .map(|n| n.name.clone())
+                .unwrap_or_default();
+            let nearest = situation.nearest().map(|(target, pt, _)| (*target, *pt));
+            match best.0 {
+                Action::Attack => {
+                    let (target, target_pt) = match (&situation.shot, nearest) {
+                        (_, Some((target, pt))) if situation.hostiles[0].2 < 1.5 => {
+                            want_melee
+                                .insert(entity, WantsToMelee { target })
+                                .expect("Unable to insert");
+                            (target, pt)
+                        }
+                        (Some((Shot::Spell(spell), target, pt)), _) => {
+                            want_cast
+                                .insert(
+                                    entity,
+                                    WantsToCastSpell {
+                                        spell: *spell,
+                                        target: Some(*pt),
+                                    },
+                                )
+                                .expect("Unable to insert");
+                            (*target, *pt)
+                        }
+                        (Some((Shot::Weapon, target, pt)), _) => {
+                            want_shoot
+                                .insert(entity, WantsToShoot { target: *target })
+                                .expect("Unable to insert");
+                            (*target, *pt)
+                        }
+                        _ => continue,
+                    };
+                    chasing
+                        .insert(entity, Chasing::new(target, target_pt, turn))
+                        .expect("Unable to insert");
+                    turn_done.push(entity);
+                }
+                Action::Approach => {
+                    if let Some((target, pt)) = nearest {
+                        want_approach
+                            .insert(
+                                entity,
+                                WantsToApproach {
+                                    idx: map.point2d_to_index(pt) as i32,
+                                },
+                            )
+                            .expect("Unable to insert");
+                        chasing
+                            .insert(entity, Chasing::new(target, pt, turn))
+                            .expect("Unable to insert");
+                    }
+                }
+                Action::Flee => {
+                    let mut indices = situation.threats.clone();
+                    indices.extend(
+                        situation
+                            .hostiles
+                            .iter()
+                            .map(|(_, pt, _)| map.point2d_to_index(*pt)),
+                    );
+                    want_flee
+                        .insert(entity, WantsToFlee { indices })
+                        .expect("Unable to insert");
+                }
+                Action::UseItem => {
+                    if let Some(potion) = situation.potion {
+                        want_use
+                            .insert(
+                                entity,
+                                WantsToUseItem {
+                                    item: potion,
+                                    target: None,
+                                },
+                            )
+                            .expect("Unable to insert");
+                        if visible {
+                            let potion_name = names
+                                .get(potion)
+                                .map(|n| identification.display_name(&n.name));
+                            Log::new()
+                                .npc(&name)
+                                .append("drinks a")
+                                .item(&potion_name.unwrap_or_default())
+                                .build();
+                        }
+                    }
+                    turn_done.push(entity);
+                }
+                Action::Guard => {
+                    if let Some(home) = situation.home {
+                        let idx = map.coord_to_index(pos.x, pos.y);
+                        let walker = Walker::new(&map, opens_doors.contains(entity));
+                        let path = a_star_search(idx, map.point2d_to_index(home), &walker);
+                        if situation.distance_home(my_pt) > 0.0
+                            && path.success
+                            && path.steps.len() > 1
+                        {
+                            moves.push((entity, path.steps[1]));
+                        }
+                    }
+                    turn_done.push(entity);
+                }
+                Action::Wander => {}
+                Action::CallForHelp => {
+                    if let Some((target, pt)) = nearest {
+                        for ally in situation.idle_allies.iter() {
+                            alerts.push((*ally, target, pt));
+                        }
+                        alerts.push((entity, target, pt));
+                        behavior.called_turn = Some(turn);
+                        if visible {
+                            Log::new().npc(&name).append("calls for help!").build();
+                        }
+                    }
+                    turn_done.push(entity);
+                }
+            }
+        }
+
+        for (entity, target, pt) in alerts {
+            chasing
+                .insert(entity, Chasing::new(target, pt, turn))
+                .expect("Unable to insert");
+        }
+
+        for (entity, dest_idx) in moves {
+            if !clear_step(entity, dest_idx, &map, &opens_doors, &mut want_open) {
+                continue;
+            }
+            let pos = positions.get_mut(entity).unwrap();
+            pos.x = dest_idx as i32 % map.width;
+            pos.y = dest_idx as i32 / map.width;
+            entity_moved
+                .insert(entity, EntityMoved {})
+                .expect("Unable to insert marker");
+            map.move_entity(entity, dest_idx);
+            if let Some(viewshed) = viewsheds.get_mut(entity) {
+                viewshed.dirty = true;
+            }
+        }
+
+        for done in turn_done.iter() {
+            turns.remove(*done);
+        }
+    }
+}
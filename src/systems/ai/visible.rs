@@ -2,8 +2,11 @@ use bracket_lib::prelude::Algorithm2D;
 use specs::prelude::*;
 
 use super::{
-    faction_reaction, Chasing, Faction, Map, MyTurn, Position, Reaction, Viewshed, WantsToApproach,
-    WantsToFlee, RAWS,
+    super::ranged_combat::{has_line_of_fire, in_range, loaded_ranged_weapon},
+    castable_spells, reaction_to, Ammunition, Behavior, Chasing, Equipped, Faction, InBackpack,
+    KnownSpells, Map, Morale, MyTurn, Name, Pools, Position, Ranged, RangedWeapon, Reaction,
+    SpellTemplate, TurnCounter, Viewshed, WantsToApproach, WantsToCastSpell, WantsToFlee,
+    WantsToShoot, RAWS,
 };
 
 pub struct VisibleAI {}
@@ -11,7 +14,7 @@ pub struct VisibleAI {}
 impl<'a> System<'a> for VisibleAI {
     #[allow(clippy::type_complexity)]
     type SystemData = (
-        ReadStorage<'a, MyTurn>,
+        WriteStorage<'a, MyTurn>,
         ReadStorage<'a, Faction>,
         ReadStorage<'a, Position>,
         ReadExpect<'a, Map>,
@@ -21,11 +24,25 @@ impl<'a> System<'a> for VisibleAI {
         ReadExpect<'a, Entity>,
         ReadStorage<'a, Viewshed>,
         WriteStorage<'a, Chasing>,
+        WriteStorage<'a, WantsToShoot>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, RangedWeapon>,
+        ReadStorage<'a, InBackpack>,
+        ReadStorage<'a, Ammunition>,
+        WriteStorage<'a, WantsToCastSpell>,
+        ReadStorage<'a, KnownSpells>,
+        ReadStorage<'a, Pools>,
+        ReadStorage<'a, SpellTemplate>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Ranged>,
+        ReadExpect<'a, TurnCounter>,
+        ReadStorage<'a, Morale>,
+        ReadStorage<'a, Behavior>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
         let (
-            turns,
+            mut turns,
             factions,
             positions,
             map,
@@ -35,10 +52,32 @@ impl<'a> System<'a> for VisibleAI {
             player,
             viewsheds,
             mut chasing,
+            mut want_shoot,
+            equipped_items,
+            ranged_weapons,
+            backpack,
+            ammunition,
+            mut want_cast,
+            known_spells,
+            pools,
+            spell_templates,
+            names,
+            ranged,
+            turn_counter,
+            morale,
+            behaviors,
         ) = data;
 
-        for (entity, _, my_faction, pos, viewshed) in
-            (&entities, &turns, &factions, &positions, &viewsheds).join()
+        let mut turn_done = Vec::<Entity>::new();
+        for (entity, _, my_faction, pos, viewshed, _) in (
+            &entities,
+            &turns,
+            &factions,
+            &positions,
+            &viewsheds,
+            !&behaviors,
+        )
+            .join()
         {
             if entity == *player {
                 continue;
@@ -53,10 +92,75 @@ impl<'a> System<'a> for VisibleAI {
                 }
             }
 
+            let ranged_weapon = loaded_ranged_weapon(
+                &entities,
+                &equipped_items,
+                &ranged_weapons,
+                &backpack,
+                &ammunition,
+                entity,
+            );
+            let castable_spells = castable_spells(
+                entity,
+                &entities,
+                &known_spells,
+                &pools,
+                &names,
+                &spell_templates,
+                &ranged,
+            );
+
             let mut done = false;
+            let mut shooting = false;
             for reaction in reactions.iter() {
                 match reaction.1 {
+                    Reaction::Attack if morale.get(entity).is_some_and(|m| m.broken) => {
+                        flee.push(reaction.0);
+                    }
+                    Reaction::Attack if shooting => {}
                     Reaction::Attack => {
+                        let target_pos = map.index_to_point2d(reaction.0);
+                        let in_line_of_fire = |range: i32| {
+                            in_range(pos.into_point(), target_pos, range)
+                                && has_line_of_fire(&map, pos.into_point(), target_pos)
+                        };
+
+                        if let Some((spell, _)) = castable_spells
+                            .iter()
+                            .find(|(_, range)| in_line_of_fire(*range))
+                        {
+                            want_cast
+                                .insert(
+                                    entity,
+                                    WantsToCastSpell {
+                                        spell: *spell,
+                                        target: Some(target_pos),
+                                    },
+                                )
+                                .expect("Unable to insert");
+                            shooting = true;
+                        } else if let Some((_, weapon)) = &ranged_weapon {
+                            if in_line_of_fire(weapon.range) {
+                                want_shoot
+                                    .insert(entity, WantsToShoot { target: reaction.2 })
+                                    .expect("Unable to insert");
+                                shooting = true;
+                            }
+                        }
+
+                        if shooting {
+                            want_approach.remove(entity);
+                            chasing
+                                .insert(
+                                    entity,
+                                    Chasing::new(reaction.2, target_pos, turn_counter.turn),
+                                )
+                                .expect("Unable to insert");
+                            turn_done.push(entity);
+                            done = true;
+                            continue;
+                        }
+
                         want_approach
                             .insert(
                                 entity,
@@ -66,7 +170,10 @@ impl<'a> System<'a> for VisibleAI {
                             )
                             .expect("Unable to insert");
                         chasing
-                            .insert(entity, Chasing { target: reaction.2 })
+                            .insert(
+                                entity,
+                                Chasing::new(reaction.2, target_pos, turn_counter.turn),
+                            )
                             .expect("Unable to insert");
                         done = true;
                     }
@@ -83,6 +190,10 @@ impl<'a> System<'a> for VisibleAI {
                     .expect("Unable to insert");
             }
         }
+
+        for done in turn_done.iter() {
+            turns.remove(*done);
+        }
     }
 }
 
@@ -93,12 +204,12 @@ fn evaluate(
     reactions: &mut Vec<(usize, Reaction, Entity)>,
     map: &Map,
 ) {
-    for other_entity in map.tiles[idx].content.iter() {
-        if let Some(faction) = factions.get(*other_entity) {
+    for other_entity in map.index.entities_at(idx) {
+        if let Some(faction) = factions.get(other_entity) {
             reactions.push((
                 idx,
-                faction_reaction(my_faction, &faction.name, &RAWS.lock().unwrap()),
-                *other_entity,
+                reaction_to(my_faction, faction, &RAWS.lock().unwrap()),
+                other_entity,
             ));
         }
     }
@@ -0,0 +1,106 @@
+use super::{
+    super::colors::*,
+    door::try_unlock,
+    melee_combat::{damage_resistance, log_damage},
+    particle::ParticleBuilder,
+    Attributes, Container, Equipped, InBackpack, InflictsDamage, Log, Name, Position, RandomGen,
+    Resistances, SufferDamage, Trapped, WantsToOpenContainer,
+};
+use bracket_lib::terminal::to_cp437;
+use specs::prelude::*;
+
+/// Works the lock and springs the trap of containers the player tries to open.
+/// Once neither is left, bumping the container shows what is inside.
+pub struct ContainerSystem {}
+
+impl<'a> System<'a> for ContainerSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, WantsToOpenContainer>,
+        WriteStorage<'a, Container>,
+        WriteStorage<'a, Trapped>,
+        WriteStorage<'a, InflictsDamage>,
+        WriteStorage<'a, SufferDamage>,
+        WriteExpect<'a, ParticleBuilder>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, InBackpack>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, Resistances>,
+        ReadStorage<'a, Equipped>,
+        WriteExpect<'a, RandomGen>,
+        ReadExpect<'a, Entity>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut want_open,
+            mut containers,
+            mut trapped,
+            mut inflicts_damage,
+            mut suffer_damage,
+            mut particle_builder,
+            positions,
+            backpack,
+            names,
+            attributes,
+            resistances,
+            equipped,
+            mut rng,
+            player,
+        ) = data;
+
+        for (entity, open) in (&entities, &want_open).join() {
+            let container = match containers.get_mut(open.container) {
+                Some(container) => container,
+                None => continue,
+            };
+            let name = names
+                .get(open.container)
+                .map_or(String::new(), |n| n.name.clone());
+
+            if container.locked {
+                if !try_unlock(
+                    entity,
+                    entity == *player,
+                    &name,
+                    container.key.as_ref(),
+                    container.lock,
+                    &backpack,
+                    &names,
+                    &attributes,
+                    &mut rng,
+                ) {
+                    continue;
+                }
+                container.locked = false;
+            }
+
+            if trapped.remove(open.container).is_none() {
+                continue;
+            }
+            Log::new()
+                .append("The")
+                .item(&name)
+                .append("was trapped!")
+                .build();
+            if let Some(damage) = inflicts_damage.remove(open.container) {
+                if let Some(pos) = positions.get(entity) {
+                    particle_builder.request(pos.x, pos.y, c(RED3), c(BLACK), to_cp437('‼'), 200.0);
+                }
+                let resistance =
+                    damage_resistance(entity, damage.damage_type, &resistances, &equipped);
+                let amount = resistance.apply(damage.damage);
+                SufferDamage::new_damage(&mut suffer_damage, entity, amount, false);
+                if let Some(victim) = names.get(entity) {
+                    let log = Log::new().append("The trap hits").npc(&victim.name);
+                    log_damage(log, amount, damage.damage_type, resistance).build();
+                }
+            }
+        }
+
+        want_open.clear();
+    }
+}
@@ -1,6 +1,16 @@
 use super::{
-    super::colors::*, get_loots, particle::ParticleBuilder, spawn_named_item, Attributes, Log,
-    LootTable, Map, Name, Player, Pools, Position, RunState, SpawnType, SufferDamage, RAWS,
+    super::{
+        colors::*,
+        raws::{
+            affixes::enchant_item,
+            progression::{level_up_points, xp_to_next_level},
+        },
+    },
+    get_loots,
+    inventory::set_quantity,
+    particle::ParticleBuilder,
+    spawn_named_item, Attributes, LevelUpPoints, Log, LootTable, Map, Name, Player, Pools,
+    Position, RandomGen, RunState, SpawnType, SufferDamage, RAWS,
 };
 use bracket_lib::terminal::{to_cp437, Point};
 use specs::prelude::*;
@@ -8,6 +18,7 @@ use specs::prelude::*;
 pub struct DamageSystem {}
 
 impl<'a> System<'a> for DamageSystem {
+    #[allow(clippy::type_complexity)]
     type SystemData = (
         WriteStorage<'a, Pools>,
         WriteStorage<'a, SufferDamage>,
@@ -18,6 +29,7 @@ impl<'a> System<'a> for DamageSystem {
         ReadExpect<'a, Entity>,
         ReadExpect<'a, Point>,
         WriteExpect<'a, ParticleBuilder>,
+        WriteStorage<'a, LevelUpPoints>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -31,12 +43,13 @@ impl<'a> System<'a> for DamageSystem {
             player,
             ppos,
             mut particles,
+            mut level_up_points_store,
         ) = data;
 
         let mut xp_gain = 0;
         let mut money_gain = 0;
 
-        for (entity, mut pools, damage) in (&entities, &mut pools, &damage).join() {
+        for (entity, pools, damage) in (&entities, &mut pools, &damage).join() {
             if pools.god_mode {
                 continue;
             }
@@ -58,28 +71,55 @@ impl<'a> System<'a> for DamageSystem {
         }
 
         if xp_gain > 0 || money_gain > 0 {
-            let mut player_attr = attr.get_mut(*player).unwrap();
-            let mut player_pools = pools.get_mut(*player).unwrap();
+            let player_attr = attr.get_mut(*player).unwrap();
+            let player_pools = pools.get_mut(*player).unwrap();
 
             player_pools.xp += xp_gain;
             player_pools.money += money_gain;
 
-            let to_level = player_attr.level * 1000;
+            let raws = &RAWS.lock().unwrap();
+            let mut leveled_up = false;
+            loop {
+                let to_level = xp_to_next_level(raws, player_attr.level);
+                if player_pools.xp < to_level {
+                    break;
+                }
 
-            if player_pools.xp >= to_level {
                 // We've gone up a level!
+                let (attribute_points, skill_points) = level_up_points(raws, player_attr.level);
+                player_pools.xp -= to_level;
                 player_attr.level += 1;
+                leveled_up = true;
+
+                if let Some(points) = level_up_points_store.get_mut(*player) {
+                    points.attribute_points += attribute_points;
+                    points.skill_points += skill_points;
+                } else {
+                    level_up_points_store
+                        .insert(
+                            *player,
+                            LevelUpPoints {
+                                attribute_points,
+                                skill_points,
+                            },
+                        )
+                        .expect("Unable to insert LevelUpPoints");
+                }
 
-                //log.entries.push(format!("Congratulations, you are now level {}", player_stats.level));
+                Log::new()
+                    .append("Congratulations, you are now level")
+                    .good(&player_attr.level)
+                    .append("!")
+                    .build();
+            }
 
+            if leveled_up {
                 player_pools.hit_points.max = player_attr.player_max_hp();
                 player_pools.hit_points.current = player_pools.hit_points.max;
 
                 player_pools.mana.max = player_attr.max_mana();
                 player_pools.mana.current = player_pools.mana.max;
 
-                player_pools.xp -= to_level;
-
                 for i in 0..10 {
                     if ppos.y - i > 1 {
                         particles.request(
@@ -124,16 +164,17 @@ pub fn delete_the_deads(ecs: &mut World) {
         }
     }
 
-    let mut to_spawn: Vec<(String, Position)> = Vec::new();
+    let mut to_spawn: Vec<(String, i32, Position)> = Vec::new();
 
     for victim in dead.iter() {
         if let Some(loot) = ecs.read_storage::<LootTable>().get(*victim) {
-            if let Some(tag) = get_loots(&RAWS.lock().unwrap(), &loot.table) {
-                if let Some(pos) = ecs.write_storage::<Position>().get(*victim) {
-                    to_spawn.push((tag, *pos));
-                    let mut map = ecs.fetch_mut::<Map>();
-                    let idx = map.coord_to_index(pos.x, pos.y);
-                    map.blocks_movement(idx, false);
+            if let Some((tag, quantity)) = get_loots(
+                &RAWS.lock().unwrap(),
+                &loot.table,
+                &mut ecs.write_resource::<RandomGen>(),
+            ) {
+                if let Some(pos) = ecs.read_storage::<Position>().get(*victim) {
+                    to_spawn.push((tag, quantity, *pos));
                 }
             }
         }
@@ -141,15 +182,21 @@ pub fn delete_the_deads(ecs: &mut World) {
         ecs.delete_entity(*victim).expect("Unable to delete");
     }
 
+    let depth = ecs.fetch::<Map>().depth;
+    let raws = RAWS.lock().unwrap();
     for drop in to_spawn.iter() {
-        spawn_named_item(
-            &RAWS.lock().unwrap(),
+        let item = spawn_named_item(
+            &raws,
             ecs,
             &drop.0,
             SpawnType::AtPosition {
-                x: drop.1.x,
-                y: drop.1.y,
+                x: drop.2.x,
+                y: drop.2.y,
             },
         );
+        if let Some(item) = item {
+            set_quantity(ecs, item, drop.1);
+            enchant_item(&raws, ecs, item, depth);
+        }
     }
 }
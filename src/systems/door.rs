@@ -0,0 +1,239 @@
+use super::{
+    Attributes, BlocksTile, BlocksVisibility, Door, InBackpack, Log, Map, Name, Position,
+    RandomGen, Renderable, Viewshed, WantsToBashDoor, WantsToCloseDoor, WantsToOpenDoor,
+};
+use specs::prelude::*;
+
+/// Opens, closes and breaks doors for whoever asked to this turn.
+pub struct DoorSystem {}
+
+impl<'a> System<'a> for DoorSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, WantsToOpenDoor>,
+        WriteStorage<'a, WantsToCloseDoor>,
+        WriteStorage<'a, WantsToBashDoor>,
+        WriteStorage<'a, Door>,
+        WriteStorage<'a, BlocksTile>,
+        WriteStorage<'a, BlocksVisibility>,
+        WriteStorage<'a, Renderable>,
+        WriteStorage<'a, Viewshed>,
+        WriteExpect<'a, Map>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, InBackpack>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Attributes>,
+        WriteExpect<'a, RandomGen>,
+        ReadExpect<'a, Entity>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut want_open,
+            mut want_close,
+            mut want_bash,
+            mut doors,
+            mut blocks_tile,
+            mut blocks_visibility,
+            mut renderables,
+            mut viewsheds,
+            mut map,
+            positions,
+            backpack,
+            names,
+            attributes,
+            mut rng,
+            player,
+        ) = data;
+
+        let mut changed = false;
+
+        for (entity, open) in (&entities, &want_open).join() {
+            let door = match doors.get_mut(open.door) {
+                Some(door) if !door.open => door,
+                _ => continue,
+            };
+            let is_player = entity == *player;
+            let name = names.get(entity).map_or(String::new(), |n| n.name.clone());
+            let seen = seen(&map, &positions, open.door);
+
+            if door.locked {
+                if !try_unlock(
+                    entity,
+                    is_player,
+                    "door",
+                    door.key.as_ref(),
+                    door.lock,
+                    &backpack,
+                    &names,
+                    &attributes,
+                    &mut rng,
+                ) {
+                    continue;
+                }
+                door.locked = false;
+            }
+
+            door.open = true;
+            blocks_tile.remove(open.door);
+            blocks_visibility.remove(open.door);
+            map.index.set_door(open.door, false, false);
+            if let Some(renderable) = renderables.get_mut(open.door) {
+                renderable.glyph = door.open_glyph;
+            }
+            if !is_player && seen {
+                Log::new().npc(&name).append("opens a door.").build();
+            }
+            changed = true;
+        }
+
+        for (entity, close) in (&entities, &want_close).join() {
+            let door = match doors.get_mut(close.door) {
+                Some(door) if door.open => door,
+                _ => continue,
+            };
+            let idx = match positions.get(close.door) {
+                Some(pos) => map.coord_to_index(pos.x, pos.y),
+                None => continue,
+            };
+            if map.index.entities_at(idx).any(|other| other != close.door) {
+                if entity == *player {
+                    Log::new()
+                        .append("Something is in the way of the door.")
+                        .build();
+                }
+                continue;
+            }
+
+            door.open = false;
+            blocks_tile
+                .insert(close.door, BlocksTile {})
+                .expect("Unable to insert");
+            blocks_visibility
+                .insert(close.door, BlocksVisibility {})
+                .expect("Unable to insert");
+            map.index.set_door(close.door, true, !door.locked);
+            if let Some(renderable) = renderables.get_mut(close.door) {
+                renderable.glyph = door.closed_glyph;
+            }
+            if entity == *player {
+                Log::new().append("You close the door.").build();
+            }
+            changed = true;
+        }
+
+        let mut smashed = Vec::<Entity>::new();
+        for (entity, bash) in (&entities, &want_bash).join() {
+            let door = match doors.get(bash.door) {
+                Some(door) if !door.open => door,
+                _ => continue,
+            };
+            let name = names.get(entity).map_or(String::new(), |n| n.name.clone());
+            let natural_roll = rng.roll_dice(1, 20);
+            let roll = natural_roll + attributes.get(entity).map_or(0, |attr| attr.might.bonus());
+            let seen = seen(&map, &positions, bash.door);
+
+            if roll >= door.strength {
+                smashed.push(bash.door);
+                if seen {
+                    Log::new()
+                        .append("(Roll")
+                        .roll(&format!("{:02}", roll))
+                        .append("vs")
+                        .roll(&format!("{:02}", door.strength))
+                        .append("strength|")
+                        .npc(&name)
+                        .append("smashes the door to pieces!")
+                        .build();
+                }
+            } else if seen {
+                Log::new()
+                    .append("(Roll")
+                    .roll(&format!("{:02}", roll))
+                    .append("vs")
+                    .roll(&format!("{:02}", door.strength))
+                    .append("strength|")
+                    .npc(&name)
+                    .append("slams into the door, but it holds.")
+                    .build();
+            }
+        }
+        for door in smashed {
+            map.index.remove(door.id());
+            entities.delete(door).expect("Unable to delete");
+            changed = true;
+        }
+
+        // Doors change what everyone around them can see.
+        if changed {
+            for viewshed in (&mut viewsheds).join() {
+                viewshed.dirty = true;
+            }
+        }
+
+        want_open.clear();
+        want_close.clear();
+        want_bash.clear();
+    }
+}
+
+fn seen(map: &Map, positions: &ReadStorage<Position>, door: Entity) -> bool {
+    positions
+        .get(door)
+        .is_some_and(|pos| map.tiles[map.coord_to_index(pos.x, pos.y)].visible)
+}
+
+/// Works a lock with the key if `entity` carries it. Only the player knows how
+/// to pick one, rolling quickness against `lock`.
+#[allow(clippy::too_many_arguments)]
+pub fn try_unlock(
+    entity: Entity,
+    is_player: bool,
+    what: &str,
+    key: Option<&String>,
+    lock: i32,
+    backpack: &ReadStorage<InBackpack>,
+    names: &ReadStorage<Name>,
+    attributes: &ReadStorage<Attributes>,
+    rng: &mut RandomGen,
+) -> bool {
+    let key = key.and_then(|key| {
+        (backpack, names)
+            .join()
+            .find(|(pack, item)| pack.owner == entity && item.name == *key)
+            .map(|(_, item)| item.name.clone())
+    });
+    if let Some(key) = key {
+        if is_player {
+            Log::new()
+                .append(format!("You unlock the {} with the", what))
+                .item(&key)
+                .append(".")
+                .build();
+        }
+        return true;
+    }
+    if !is_player {
+        return false;
+    }
+
+    let natural_roll = rng.roll_dice(1, 20);
+    let pick = natural_roll
+        + attributes
+            .get(entity)
+            .map_or(0, |attr| attr.quickness.bonus());
+    let log = Log::new()
+        .append("(Roll")
+        .roll(&format!("{:02}", pick))
+        .append("vs")
+        .roll(&format!("{:02}", lock))
+        .append("lock|");
+    if pick < lock {
+        log.append("You fail to pick the lock.").build();
+        return false;
+    }
+    log.good(&"You pick the lock.").build();
+    true
+}
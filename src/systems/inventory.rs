@@ -1,8 +1,15 @@
 use super::{
-    super::colors::*, particle::ParticleBuilder, AreaOfEffect, Confusion, Consumable,
-    EquipmentChanged, Equippable, Equipped, InBackpack, InflictsDamage, Log, Map, Name, Pools,
-    Position, ProvidesHealing, SufferDamage, WantsToDropItem, WantsToPickupItem, WantsToRemoveItem,
-    WantsToUseItem,
+    super::colors::*,
+    item_label,
+    melee_combat::{damage_resistance, log_damage},
+    particle::ParticleBuilder,
+    spawn_named_item,
+    status::apply_status,
+    AreaOfEffect, Consumable, Cursed, EquipmentChanged, EquipmentSlot, Equippable, Equipped,
+    GrantsStatus, HungerClock, Identification, Identifies, InBackpack, InflictsDamage, Log, Map,
+    MeleeWeapon, Name, Pools, Position, ProvidesFood, ProvidesHealing, RemovesCurse, Resistances,
+    SpawnType, Stackable, StatusEffect, SufferDamage, WantsToDropItem, WantsToPickupItem,
+    WantsToRemoveItem, WantsToUseItem, RAWS,
 };
 use bracket_lib::{
     prelude::{field_of_view, Algorithm2D},
@@ -21,12 +28,53 @@ impl<'a> System<'a> for ItemCollectionSystem {
         ReadStorage<'a, Name>,
         WriteStorage<'a, InBackpack>,
         WriteStorage<'a, EquipmentChanged>,
+        ReadExpect<'a, Identification>,
+        Entities<'a>,
+        WriteStorage<'a, Stackable>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (player_entity, mut wants_pickup, mut positions, names, mut backpack, mut dirty) = data;
+        let (
+            player_entity,
+            mut wants_pickup,
+            mut positions,
+            names,
+            mut backpack,
+            mut dirty,
+            identification,
+            entities,
+            mut stacks,
+        ) = data;
 
         for pickup in wants_pickup.join() {
+            let name = &names.get(pickup.item).unwrap().name;
+            if pickup.collected_by == *player_entity {
+                Log::new()
+                    .append("You pick up the")
+                    .item(&item_label(
+                        identification.display_name(name),
+                        stacks.get(pickup.item),
+                    ))
+                    .build();
+            }
+            dirty
+                .insert(pickup.collected_by, EquipmentChanged {})
+                .expect("Unable to insert EquipmentChanged");
+
+            // A stack joins the one already in the pack rather than taking a new line.
+            let quantity = stacks.get(pickup.item).map(|stack| stack.quantity);
+            let existing = (&entities, &backpack, &names, &stacks)
+                .join()
+                .find(|(_, carried, other, _)| {
+                    carried.owner == pickup.collected_by && other.name == *name
+                })
+                .map(|(item, _, _, _)| item);
+            if let (Some(quantity), Some(existing)) = (quantity, existing) {
+                stacks.get_mut(existing).unwrap().quantity += quantity;
+                entities.delete(pickup.item).expect("Unable to delete");
+                continue;
+            }
+
             positions.remove(pickup.item);
             backpack
                 .insert(
@@ -36,15 +84,6 @@ impl<'a> System<'a> for ItemCollectionSystem {
                     },
                 )
                 .expect("Unable to insert backpack");
-            dirty
-                .insert(pickup.collected_by, EquipmentChanged {})
-                .expect("Unable to insert EquipmentChanged");
-            if pickup.collected_by == *player_entity {
-                Log::new()
-                    .append("You pick up the")
-                    .item(&names.get(pickup.item).unwrap().name)
-                    .build();
-            }
         }
         wants_pickup.clear();
     }
@@ -63,16 +102,30 @@ impl<'a> System<'a> for ItemUseSystem {
         WriteStorage<'a, Pools>,
         ReadStorage<'a, Consumable>,
         ReadStorage<'a, ProvidesHealing>,
+        ReadStorage<'a, ProvidesFood>,
+        WriteStorage<'a, HungerClock>,
         ReadStorage<'a, InflictsDamage>,
         WriteStorage<'a, SufferDamage>,
         ReadStorage<'a, AreaOfEffect>,
-        WriteStorage<'a, Confusion>,
+        ReadStorage<'a, GrantsStatus>,
+        WriteStorage<'a, StatusEffect>,
         ReadStorage<'a, Equippable>,
         WriteStorage<'a, Equipped>,
         WriteStorage<'a, InBackpack>,
         WriteExpect<'a, ParticleBuilder>,
         ReadStorage<'a, Position>,
         WriteStorage<'a, EquipmentChanged>,
+        Read<'a, LazyUpdate>,
+        ReadStorage<'a, Resistances>,
+        ReadStorage<'a, MeleeWeapon>,
+        WriteExpect<'a, Identification>,
+        // Grouped to stay within the tuple sizes specs implements SystemData for.
+        (
+            ReadStorage<'a, Identifies>,
+            ReadStorage<'a, RemovesCurse>,
+            WriteStorage<'a, Cursed>,
+            WriteStorage<'a, Stackable>,
+        ),
     );
 
     #[allow(clippy::cognitive_complexity)]
@@ -86,19 +139,40 @@ impl<'a> System<'a> for ItemUseSystem {
             mut combat_stats,
             consumables,
             healing,
+            food,
+            mut hunger_clocks,
             inflicts_damage,
             mut suffer_damage,
             area_of_effect,
-            mut confused,
+            grants_status,
+            mut statuses,
             equippable,
             mut equipped,
             mut backpack,
             mut particle_builder,
             positions,
             mut dirty,
+            lazy,
+            resistances,
+            melee_weapons,
+            mut identification,
+            (identifies, removes_curse, mut cursed, mut stacks),
         ) = data;
 
         for (entity, useitem) in (&entities, &wants_use).join() {
+            if entity == *player_entity {
+                let name = &names.get(useitem.item).unwrap().name;
+                if let Some(disguise) = identification.identify(name) {
+                    Log::new()
+                        .append("The")
+                        .item(&disguise)
+                        .append("was a")
+                        .item(name)
+                        .append("!")
+                        .build();
+                }
+            }
+
             let mut used_item = true;
             let mut targets = Vec::<Entity>::new();
             dirty
@@ -109,11 +183,10 @@ impl<'a> System<'a> for ItemUseSystem {
                 if let Some(aoe) = area_of_effect.get(useitem.item) {
                     let mut blast_tiles = field_of_view(target, aoe.radius, &*map);
                     blast_tiles.retain(|p| map.in_bounds(*p));
+                    blast_tiles.sort_by_key(|p| (p.y, p.x));
                     for tile_pt in blast_tiles.iter() {
                         let idx = map.point2d_to_index(*tile_pt);
-                        for mob in map.tiles[idx].content.iter() {
-                            targets.push(*mob);
-                        }
+                        targets.extend(map.index.first_blocker(idx));
                         particle_builder.request(
                             tile_pt.x,
                             tile_pt.y,
@@ -126,52 +199,108 @@ impl<'a> System<'a> for ItemUseSystem {
                 } else {
                     //Mono
                     let idx = map.point2d_to_index(target);
-
-                    for mob in map.tiles[idx].content.iter() {
-                        targets.push(*mob);
-                    }
+                    targets.extend(map.index.first_blocker(idx));
                 }
             } else {
                 //Self cast
-                targets.push(*player_entity);
+                targets.push(entity);
             }
 
             if let Some(can_equip) = equippable.get(useitem.item) {
-                let target_slot = can_equip.slot;
                 let target = targets[0];
+                let two_handed = |item: Entity| {
+                    melee_weapons
+                        .get(item)
+                        .is_some_and(|weapon| weapon.two_handed)
+                };
+
+                // A light weapon goes to the off hand when the main hand already
+                // holds a one-handed weapon.
+                let mut target_slot = can_equip.slot;
+                if melee_weapons
+                    .get(useitem.item)
+                    .is_some_and(|weapon| weapon.off_hand)
+                    && (&entities, &equipped).join().any(|(item, wielded)| {
+                        wielded.owner == target
+                            && wielded.slot == EquipmentSlot::Melee
+                            && item != useitem.item
+                            && melee_weapons
+                                .get(item)
+                                .is_some_and(|weapon| !weapon.two_handed)
+                    })
+                {
+                    target_slot = EquipmentSlot::Shield;
+                }
 
                 let mut to_unequip = Vec::<Entity>::new();
-                for (item_entity, already_equipped, item) in (&entities, &equipped, &names).join() {
-                    if already_equipped.owner == target && already_equipped.slot == target_slot {
+                for (item_entity, already_equipped) in (&entities, &equipped).join() {
+                    if already_equipped.owner != target {
+                        continue;
+                    }
+                    // Two-handed weapons and the off hand rule each other out.
+                    let clashes = already_equipped.slot == target_slot
+                        || (already_equipped.slot == EquipmentSlot::Shield
+                            && two_handed(useitem.item))
+                        || (target_slot == EquipmentSlot::Shield
+                            && already_equipped.slot == EquipmentSlot::Melee
+                            && two_handed(item_entity));
+                    if clashes {
                         to_unequip.push(item_entity);
-                        if target == *player_entity {
-                            Log::new().append("You unequip").item(&item.name).build();
-                        }
                     }
                 }
 
-                for item in to_unequip.iter() {
-                    equipped.remove(*item);
-                    backpack
-                        .insert(*item, InBackpack { owner: target })
-                        .expect("Unable to insert into backpack");
-                }
+                if let Some(bound) = to_unequip.iter().find(|item| cursed.contains(**item)) {
+                    if target == *player_entity {
+                        Log::new()
+                            .append("You can't take off the")
+                            .item(&names.get(*bound).unwrap().name)
+                            .append(", it is")
+                            .bad(&"cursed!")
+                            .build();
+                    }
+                } else {
+                    for item in to_unequip.iter() {
+                        equipped.remove(*item);
+                        backpack
+                            .insert(*item, InBackpack { owner: target })
+                            .expect("Unable to insert into backpack");
+                        if target == *player_entity {
+                            Log::new()
+                                .append("You unequip")
+                                .item(&names.get(*item).unwrap().name)
+                                .build();
+                        }
+                    }
 
-                equipped
-                    .insert(
-                        useitem.item,
-                        Equipped {
-                            owner: target,
-                            slot: target_slot,
-                        },
-                    )
-                    .expect("Unable to equipped component");
-                backpack.remove(useitem.item);
-                if target == *player_entity {
-                    Log::new()
-                        .append("You equip")
-                        .item(&names.get(useitem.item).unwrap().name)
-                        .build();
+                    equipped
+                        .insert(
+                            useitem.item,
+                            Equipped {
+                                owner: target,
+                                slot: target_slot,
+                            },
+                        )
+                        .expect("Unable to equipped component");
+                    backpack.remove(useitem.item);
+                    if target == *player_entity {
+                        let log = Log::new()
+                            .append("You equip")
+                            .item(&names.get(useitem.item).unwrap().name);
+                        if target_slot == EquipmentSlot::Shield
+                            && melee_weapons.get(useitem.item).is_some()
+                        {
+                            log.append("in your off hand").build();
+                        } else {
+                            log.build();
+                        }
+                        if cursed.contains(useitem.item) {
+                            Log::new()
+                                .append("It is")
+                                .bad(&"cursed!")
+                                .append("You can't let go of it.")
+                                .build();
+                        }
+                    }
                 }
             }
 
@@ -206,11 +335,84 @@ impl<'a> System<'a> for ItemUseSystem {
                 }
             }
 
+            if identifies.get(useitem.item).is_some() && entity == *player_entity {
+                let unknown: Vec<String> = (&backpack, &names)
+                    .join()
+                    .filter(|(pack, item)| {
+                        pack.owner == entity && !identification.is_known(&item.name)
+                    })
+                    .map(|(_, item)| item.name.clone())
+                    .collect();
+                if unknown.is_empty() {
+                    Log::new().append("You learn nothing new.").build();
+                }
+                for name in unknown.iter() {
+                    if let Some(disguise) = identification.identify(name) {
+                        Log::new()
+                            .append("You identify the")
+                            .item(&disguise)
+                            .append("as a")
+                            .item(name)
+                            .append(".")
+                            .build();
+                    }
+                }
+            }
+
+            if removes_curse.get(useitem.item).is_some() {
+                let lifted: Vec<Entity> = (&entities, &equipped, &cursed)
+                    .join()
+                    .filter(|(_, wielded, _)| wielded.owner == entity)
+                    .map(|(item, _, _)| item)
+                    .collect();
+                for item in lifted.iter() {
+                    cursed.remove(*item);
+                    if entity == *player_entity {
+                        Log::new()
+                            .append("The curse on the")
+                            .item(&names.get(*item).unwrap().name)
+                            .good(&"lifts")
+                            .append(".")
+                            .build();
+                    }
+                }
+                if lifted.is_empty() && entity == *player_entity {
+                    Log::new().append("You feel a brief warmth.").build();
+                }
+            }
+
+            if food.get(useitem.item).is_some() {
+                used_item = false;
+                for target in targets.iter() {
+                    if let Some(clock) = hunger_clocks.get_mut(*target) {
+                        clock.eat();
+                        used_item = true;
+                        if *target == *player_entity {
+                            Log::new()
+                                .append("You eat the")
+                                .item(&names.get(useitem.item).unwrap().name)
+                                .append(", you are")
+                                .good(&"well fed")
+                                .append(".")
+                                .build();
+                        }
+                    }
+                }
+            }
+
             //damaging items
             if let Some(damage) = inflicts_damage.get(useitem.item) {
                 used_item = false;
                 for mob in targets.iter() {
-                    SufferDamage::new_damage(&mut suffer_damage, *mob, damage.damage, true);
+                    let resistance =
+                        damage_resistance(*mob, damage.damage_type, &resistances, &equipped);
+                    let amount = resistance.apply(damage.damage);
+                    SufferDamage::new_damage(
+                        &mut suffer_damage,
+                        *mob,
+                        amount,
+                        entity == *player_entity,
+                    );
                     used_item = true;
                     if entity != *player_entity {
                         continue;
@@ -218,15 +420,12 @@ impl<'a> System<'a> for ItemUseSystem {
 
                     let mob_name = names.get(*mob).unwrap();
                     let item_name = names.get(useitem.item).unwrap();
-                    Log::new()
+                    let log = Log::new()
                         .append("You use")
                         .item(&item_name.name)
                         .append("on")
-                        .npc(&mob_name.name)
-                        .append(", infilicting")
-                        .bad(&damage.damage)
-                        .append("damages")
-                        .build();
+                        .npc(&mob_name.name);
+                    log_damage(log, amount, damage.damage_type, resistance).build();
 
                     let pos = positions.get(*mob);
                     if let Some(pos) = pos {
@@ -242,26 +441,32 @@ impl<'a> System<'a> for ItemUseSystem {
                 }
             }
 
-            let mut add_confusion = Vec::new();
-
-            if let Some(confusion) = confused.get(useitem.item) {
+            if let Some(grants) = grants_status.get(useitem.item) {
                 used_item = false;
                 for mob in targets.iter() {
+                    if combat_stats.get(*mob).is_none() {
+                        continue;
+                    }
                     used_item = true;
-                    add_confusion.push((*mob, confusion.duration));
+                    for status in grants.effects.iter() {
+                        apply_status(&entities, &lazy, &mut statuses, *mob, status);
+                        if let Some(mob_name) = names.get(*mob) {
+                            Log::new()
+                                .npc(&mob_name.name)
+                                .append(if status.kind.is_harmful() {
+                                    "suffers from"
+                                } else {
+                                    "gains"
+                                })
+                                .item(&status.kind.name())
+                                .append(format!("for {} turns", status.duration))
+                                .build();
+                        }
+                    }
                     if entity != *player_entity {
                         continue;
                     }
 
-                    let mob_name = names.get(*mob).unwrap();
-                    let item_name = names.get(useitem.item).unwrap();
-                    Log::new()
-                        .append("You use")
-                        .item(&item_name.name)
-                        .append("on")
-                        .npc(&mob_name.name)
-                        .build();
-
                     if let Some(pos) = positions.get(*mob) {
                         particle_builder.request(
                             pos.x,
@@ -275,15 +480,9 @@ impl<'a> System<'a> for ItemUseSystem {
                 }
             }
 
-            for mob in add_confusion.iter() {
-                confused
-                    .insert(mob.0, Confusion { duration: mob.1 })
-                    .expect("Unable to insert confusion");
-            }
-
             // remove used consumables
             if used_item && consumables.get(useitem.item).is_some() {
-                entities.delete(useitem.item).expect("Delete failed");
+                use_up_one(&entities, &mut stacks, useitem.item);
             }
         }
 
@@ -302,6 +501,9 @@ impl<'a> System<'a> for ItemDropSystem {
         WriteStorage<'a, Position>,
         WriteStorage<'a, InBackpack>,
         WriteStorage<'a, EquipmentChanged>,
+        ReadExpect<'a, Identification>,
+        WriteStorage<'a, Stackable>,
+        Read<'a, LazyUpdate>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -313,31 +515,61 @@ impl<'a> System<'a> for ItemDropSystem {
             mut positions,
             mut backpack,
             mut dirty,
+            identification,
+            mut stacks,
+            lazy,
         ) = data;
 
         for (entity, to_drop) in (&entities, &want_drops).join() {
-            let dropped_pos = positions.get(entity).unwrap();
+            let dropped_pos = *positions.get(entity).unwrap();
+            let name = names.get(to_drop.item).unwrap().name.clone();
 
-            positions
-                .insert(
-                    to_drop.item,
-                    Position {
-                        x: dropped_pos.x,
-                        y: dropped_pos.y,
-                    },
-                )
-                .expect("Unable to insert positions");
-            backpack.remove(to_drop.item);
+            let split = match (stacks.get_mut(to_drop.item), to_drop.quantity) {
+                (Some(stack), Some(quantity)) if quantity < stack.quantity => {
+                    stack.quantity -= quantity;
+                    Some(quantity)
+                }
+                _ => None,
+            };
+
+            let dropped = match split {
+                Some(quantity) => {
+                    // The rest stays in the pack; what is dropped becomes a stack of its own.
+                    let key = name.clone();
+                    lazy.exec_mut(move |ecs| {
+                        let item = spawn_named_item(
+                            &RAWS.lock().unwrap(),
+                            ecs,
+                            &key,
+                            SpawnType::AtPosition {
+                                x: dropped_pos.x,
+                                y: dropped_pos.y,
+                            },
+                        );
+                        if let Some(item) = item {
+                            set_quantity(ecs, item, quantity);
+                        }
+                    });
+                    item_label(
+                        identification.display_name(&name),
+                        Some(&Stackable { quantity }),
+                    )
+                }
+                None => {
+                    positions
+                        .insert(to_drop.item, dropped_pos)
+                        .expect("Unable to insert positions");
+                    backpack.remove(to_drop.item);
+                    item_label(identification.display_name(&name), stacks.get(to_drop.item))
+                }
+            };
 
             dirty
                 .insert(entity, EquipmentChanged {})
                 .expect("Unable to insert EquipmentChanged");
 
             if entity == *player_entity {
-                Log::new()
-                    .append("You drop the")
-                    .item(&names.get(to_drop.item).unwrap().name)
-                    .build();
+                Log::new().append("You drop the").item(&dropped).build();
             }
         }
         want_drops.clear();
@@ -355,12 +587,38 @@ impl<'a> System<'a> for ItemRemoveSystem {
         ReadStorage<'a, Name>,
         WriteStorage<'a, Equipped>,
         WriteStorage<'a, InBackpack>,
+        ReadStorage<'a, Cursed>,
+        WriteStorage<'a, EquipmentChanged>,
     );
     fn run(&mut self, data: Self::SystemData) {
-        let (player_entity, entities, mut wants_remove, names, mut equipped, mut backpack) = data;
+        let (
+            player_entity,
+            entities,
+            mut wants_remove,
+            names,
+            mut equipped,
+            mut backpack,
+            cursed,
+            mut dirty,
+        ) = data;
 
         for (entity, to_remove) in (&entities, &wants_remove).join() {
+            if cursed.contains(to_remove.item) {
+                if entity == *player_entity {
+                    Log::new()
+                        .append("You can't remove the")
+                        .item(&names.get(to_remove.item).unwrap().name)
+                        .append(", it is")
+                        .bad(&"cursed!")
+                        .build();
+                }
+                continue;
+            }
+
             equipped.remove(to_remove.item);
+            dirty
+                .insert(entity, EquipmentChanged {})
+                .expect("Unable to insert EquipmentChanged");
             backpack
                 .insert(to_remove.item, InBackpack { owner: entity })
                 .expect("Unable to insert in backpack");
@@ -375,3 +633,254 @@ impl<'a> System<'a> for ItemRemoveSystem {
         wants_remove.clear();
     }
 }
+
+pub fn set_quantity(ecs: &mut World, item: Entity, quantity: i32) {
+    if let Some(stack) = ecs.write_storage::<Stackable>().get_mut(item) {
+        stack.quantity = quantity;
+    }
+}
+
+/// Uses up one of `item`, deleting it once the stack runs out.
+pub fn use_up_one(entities: &Entities, stacks: &mut WriteStorage<Stackable>, item: Entity) {
+    if let Some(stack) = stacks.get_mut(item) {
+        if stack.quantity > 1 {
+            stack.quantity -= 1;
+            return;
+        }
+    }
+    entities.delete(item).expect("Unable to delete");
+}
+
+/// How many items called `name` `owner` carries, counting whole stacks.
+/// Unidentified item types never count, so a recipe can't give away which
+/// disguise is which.
+pub fn count_carried(ecs: &World, owner: Entity, name: &str) -> i32 {
+    if !ecs.fetch::<Identification>().is_known(name) {
+        return 0;
+    }
+    let stacks = ecs.read_storage::<Stackable>();
+    (
+        &ecs.entities(),
+        &ecs.read_storage::<InBackpack>(),
+        &ecs.read_storage::<Name>(),
+    )
+        .join()
+        .filter(|(_, pack, item)| pack.owner == owner && item.name == name)
+        .map(|(item, _, _)| stacks.get(item).map_or(1, |stack| stack.quantity))
+        .sum()
+}
+
+/// Takes `quantity` items called `name` out of `owner`'s pack, eating into
+/// stacks before deleting them.
+pub fn consume_carried(ecs: &mut World, owner: Entity, name: &str, quantity: i32) {
+    let carried: Vec<Entity> = (
+        &ecs.entities(),
+        &ecs.read_storage::<InBackpack>(),
+        &ecs.read_storage::<Name>(),
+    )
+        .join()
+        .filter(|(_, pack, item)| pack.owner == owner && item.name == name)
+        .map(|(item, _, _)| item)
+        .collect();
+
+    let mut left = quantity;
+    for item in carried {
+        if left <= 0 {
+            break;
+        }
+        let mut stacks = ecs.write_storage::<Stackable>();
+        if let Some(stack) = stacks.get_mut(item) {
+            if stack.quantity > left {
+                stack.quantity -= left;
+                return;
+            }
+            left -= stack.quantity;
+        } else {
+            left -= 1;
+        }
+        std::mem::drop(stacks);
+        ecs.delete_entity(item).expect("Unable to delete");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::super::new_state, *};
+
+    fn equip(ecs: &mut World, owner: Entity, cursed: bool) -> Entity {
+        let mut builder = ecs.create_entity().with(Name::new("Helm")).with(Equipped {
+            owner,
+            slot: EquipmentSlot::Head,
+        });
+        if cursed {
+            builder = builder.with(Cursed {});
+        }
+        let item = builder.build();
+        ecs.write_storage::<WantsToRemoveItem>()
+            .insert(owner, WantsToRemoveItem { item })
+            .expect("Unable to insert");
+        item
+    }
+
+    fn carry_arrows(ecs: &mut World, owner: Entity, quantity: i32) -> Entity {
+        let arrows = spawn_named_item(
+            &RAWS.lock().unwrap(),
+            ecs,
+            "Arrow",
+            SpawnType::Carried { by: owner },
+        )
+        .unwrap();
+        set_quantity(ecs, arrows, quantity);
+        arrows
+    }
+
+    /// Arrow stacks in `owner`'s pack, or lying around when `owner` is `None`.
+    fn arrow_stacks(ecs: &World, owner: Option<Entity>) -> Vec<i32> {
+        let backpack = ecs.read_storage::<InBackpack>();
+        (
+            &ecs.entities(),
+            &ecs.read_storage::<Name>(),
+            &ecs.read_storage::<Stackable>(),
+        )
+            .join()
+            .filter(|(item, name, _)| {
+                name.name == "Arrow" && backpack.get(*item).map(|pack| pack.owner) == owner
+            })
+            .map(|(_, _, stack)| stack.quantity)
+            .collect()
+    }
+
+    fn drop_arrows(ecs: &mut World, dropper: Entity, item: Entity, quantity: Option<i32>) {
+        ecs.write_storage::<WantsToDropItem>()
+            .insert(dropper, WantsToDropItem { item, quantity })
+            .expect("Unable to insert");
+        ItemDropSystem {}.run_now(ecs);
+        ecs.maintain();
+    }
+
+    #[test]
+    fn dropping_part_of_a_stack_splits_it() {
+        let mut ecs = new_state(Some(1)).ecs;
+        let player = *ecs.fetch::<Entity>();
+        let arrows = carry_arrows(&mut ecs, player, 10);
+
+        drop_arrows(&mut ecs, player, arrows, Some(3));
+        assert_eq!(arrow_stacks(&ecs, Some(player)), vec![7]);
+        assert_eq!(arrow_stacks(&ecs, None), vec![3]);
+
+        // Dropping the rest moves the stack itself out of the pack.
+        drop_arrows(&mut ecs, player, arrows, None);
+        assert_eq!(arrow_stacks(&ecs, Some(player)), Vec::<i32>::new());
+        assert!(ecs.read_storage::<Position>().contains(arrows));
+    }
+
+    #[test]
+    fn picked_up_stacks_merge_into_the_pack() {
+        let mut ecs = new_state(Some(1)).ecs;
+        let player = *ecs.fetch::<Entity>();
+        let arrows = carry_arrows(&mut ecs, player, 10);
+        drop_arrows(&mut ecs, player, arrows, Some(4));
+        let dropped = (
+            &ecs.entities(),
+            &ecs.read_storage::<Name>(),
+            &ecs.read_storage::<Position>(),
+        )
+            .join()
+            .find(|(_, name, _)| name.name == "Arrow")
+            .map(|(item, _, _)| item)
+            .unwrap();
+
+        ecs.write_storage::<WantsToPickupItem>()
+            .insert(
+                player,
+                WantsToPickupItem {
+                    collected_by: player,
+                    item: dropped,
+                },
+            )
+            .expect("Unable to insert");
+        ItemCollectionSystem {}.run_now(&ecs);
+        ecs.maintain();
+
+        assert_eq!(arrow_stacks(&ecs, Some(player)), vec![10]);
+        assert!(!ecs.entities().is_alive(dropped));
+    }
+
+    #[test]
+    fn using_up_a_stack_deletes_it_last() {
+        let mut ecs = new_state(Some(1)).ecs;
+        let player = *ecs.fetch::<Entity>();
+        let arrows = carry_arrows(&mut ecs, player, 2);
+
+        for left in [Some(1), None] {
+            ecs.exec(
+                |(entities, mut stacks): (Entities, WriteStorage<Stackable>)| {
+                    use_up_one(&entities, &mut stacks, arrows);
+                },
+            );
+            ecs.maintain();
+            let quantity = ecs
+                .read_storage::<Stackable>()
+                .get(arrows)
+                .map(|s| s.quantity);
+            assert_eq!(quantity, left);
+        }
+        assert!(!ecs.entities().is_alive(arrows));
+    }
+
+    #[test]
+    fn consuming_eats_through_partial_stacks() {
+        let mut ecs = new_state(Some(1)).ecs;
+        let player = *ecs.fetch::<Entity>();
+        // Built by hand, as spawning into a pack would merge them.
+        for quantity in [2, 3, 4] {
+            ecs.create_entity()
+                .with(Name::new("Arrow"))
+                .with(Stackable { quantity })
+                .with(InBackpack { owner: player })
+                .build();
+        }
+        assert_eq!(count_carried(&ecs, player, "Arrow"), 9);
+
+        consume_carried(&mut ecs, player, "Arrow", 6);
+        ecs.maintain();
+
+        assert_eq!(count_carried(&ecs, player, "Arrow"), 3);
+        // Whole stacks are used up first and only the last one is cut down,
+        // whichever order the pack is walked in.
+        let mut left = arrow_stacks(&ecs, Some(player));
+        left.sort();
+        assert!(left == vec![3] || left == vec![1, 2], "{:?}", left);
+    }
+
+    #[test]
+    fn cursed_items_cannot_be_removed() {
+        let mut ecs = new_state(Some(1)).ecs;
+        let player = *ecs.fetch::<Entity>();
+        let helm = equip(&mut ecs, player, true);
+
+        ItemRemoveSystem {}.run_now(&ecs);
+        ecs.maintain();
+
+        assert!(ecs.read_storage::<Equipped>().contains(helm));
+        assert!(!ecs.read_storage::<InBackpack>().contains(helm));
+        assert!(!ecs.read_storage::<WantsToRemoveItem>().contains(player));
+    }
+
+    #[test]
+    fn uncursed_items_go_back_to_the_pack() {
+        let mut ecs = new_state(Some(1)).ecs;
+        let player = *ecs.fetch::<Entity>();
+        let helm = equip(&mut ecs, player, false);
+
+        ItemRemoveSystem {}.run_now(&ecs);
+        ecs.maintain();
+
+        assert!(!ecs.read_storage::<Equipped>().contains(helm));
+        let owner = ecs
+            .read_storage::<InBackpack>()
+            .get(helm)
+            .map(|pack| pack.owner);
+        assert_eq!(owner, Some(player));
+    }
+}
@@ -1,47 +1,73 @@
-use super::{tiles::is_tile_walkable, BlocksTile, Map, Pools, Position};
-use bracket_lib::{prelude::Algorithm2D, terminal::Point};
+use super::{BlocksTile, Door, Map, Position};
 use ::specs::prelude::*;
 
-pub struct MapIndexingSystem {}
+#[derive(Default)]
+pub struct MapIndexingSystem {
+    position_events: Option<ReaderId<ComponentEvent>>,
+    blocker_events: Option<ReaderId<ComponentEvent>>,
+}
 
 impl<'a> System<'a> for MapIndexingSystem {
     type SystemData = (
         WriteExpect<'a, Map>,
-        ReadStorage<'a, Position>,
-        ReadStorage<'a, Pools>,
+        WriteStorage<'a, Position>,
         Entities<'a>,
-        ReadStorage<'a, BlocksTile>,
-        ReadExpect<'a, Entity>,
-        ReadExpect<'a, Point>,
-
+        WriteStorage<'a, BlocksTile>,
+        ReadStorage<'a, Door>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut map, position, pools, entities, block, player_entity, ppos) = data;
-
-        for (i, tile) in map.tiles.clone().iter().enumerate() {
-            map.tiles[i].content.clear();
-            map.tiles[i].block_movement = !is_tile_walkable(tile.surface);
-        }
+        let (mut map, mut positions, entities, mut blocks, doors) = data;
+        let closed_door = |entity: Entity| doors.get(entity).is_some_and(|d| !d.open && !d.locked);
 
-        for (entity, position, _) in (&entities, &position, &block).join() {
-            let mut alive = true;
-            let idx = map.coord_to_index(position.x, position.y);
+        // The first run, and any run after the map itself was replaced, indexes
+        // everything. Pending events are then stale and only need draining.
+        let rebuild = !map.index.is_built()
+            || self.position_events.is_none()
+            || self.blocker_events.is_none();
+        let position_events = self
+            .position_events
+            .get_or_insert_with(|| positions.register_reader());
+        let blocker_events = self
+            .blocker_events
+            .get_or_insert_with(|| blocks.register_reader());
 
-            if let Some(pools) = pools.get(entity) {
-                if pools.hit_points.current < 1 {
-                    alive = false;
+        let mut dirty = BitSet::new();
+        for event in positions
+            .channel()
+            .read(position_events)
+            .chain(blocks.channel().read(blocker_events))
+        {
+            match event {
+                ComponentEvent::Inserted(id)
+                | ComponentEvent::Modified(id)
+                | ComponentEvent::Removed(id) => {
+                    dirty.add(*id);
                 }
             }
-            if alive {
-                map.tiles[idx].content.push(entity);
-            }
-
-            map.blocks_movement(idx, true);
         }
 
-        let idx = map.point2d_to_index(*ppos);
-        map.tiles[idx].content.push(*player_entity);
+        if rebuild {
+            let (width, height) = (map.width, map.height);
+            map.index.reset(width, height);
+            for (entity, pos) in (&entities, &positions).join() {
+                let idx = map.coord_to_index(pos.x, pos.y);
+                map.index.insert(entity, idx, blocks.contains(entity), closed_door(entity));
+            }
+            return;
+        }
 
+        // Entities are looked up again by id, so events arriving out of order
+        // across the two channels still leave the index matching the storages.
+        for id in (&dirty).join() {
+            let entity = entities.entity(id);
+            match positions.get(entity) {
+                Some(pos) if entities.is_alive(entity) => {
+                    let idx = map.coord_to_index(pos.x, pos.y);
+                    map.index.insert(entity, idx, blocks.contains(entity), closed_door(entity));
+                }
+                _ => map.index.remove(id),
+            }
+        }
     }
 }
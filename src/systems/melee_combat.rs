@@ -1,14 +1,18 @@
 use super::{
-    super::colors::*, particle::ParticleBuilder, Attributes, EquipmentSlot, Equipped, Log,
-    MeleeWeapon, Name, NaturalProperty, Pools, Position, RandomGen, Skills, SufferDamage,
-    WantsToMelee, Wearable,
+    super::colors::*, particle::ParticleBuilder, Attributes, DamageBonus, DamageType,
+    EquipmentSlot, Equipped, Log, MeleeWeapon, Name, NaturalProperty, Pools, Position, RandomGen,
+    Resistance, Resistances, Shield, Skills, SufferDamage, WantsToMelee, WeaponAttribute, Wearable,
 };
 use bracket_lib::terminal::to_cp437;
-use specs::prelude::*;
+use specs::{prelude::*, storage::MaskedStorage};
+use std::ops::Deref;
+
+const OFF_HAND_PENALTY: i32 = -4;
 
 pub struct MeleeCombatSystem {}
 
 impl<'a> System<'a> for MeleeCombatSystem {
+    #[allow(clippy::type_complexity)]
     type SystemData = (
         Entities<'a>,
         WriteStorage<'a, WantsToMelee>,
@@ -24,6 +28,10 @@ impl<'a> System<'a> for MeleeCombatSystem {
         ReadStorage<'a, Wearable>,
         ReadStorage<'a, NaturalProperty>,
         ReadExpect<'a, Entity>,
+        WriteExpect<'a, RandomGen>,
+        ReadStorage<'a, Resistances>,
+        ReadStorage<'a, Shield>,
+        ReadStorage<'a, DamageBonus>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -42,6 +50,10 @@ impl<'a> System<'a> for MeleeCombatSystem {
             wearables,
             natural_properties,
             player_entity,
+            mut rng,
+            resistances,
+            shields,
+            damage_bonuses,
         ) = data;
 
         for (entity, wants_melee, source, source_attributes, source_skills, source_pools) in (
@@ -69,8 +81,6 @@ impl<'a> System<'a> for MeleeCombatSystem {
             let target = sources.get(wants_melee.target).unwrap();
             let target_attributes = attributes.get(wants_melee.target).unwrap();
             let target_skills = skills.get(wants_melee.target).unwrap();
-            let mut rng = RandomGen::default();
-            let natural_roll = rng.roll_dice(1, 20);
 
             let mut attack = MeleeWeapon::base();
             if let Some(np) = natural_properties.get(entity) {
@@ -79,132 +89,193 @@ impl<'a> System<'a> for MeleeCombatSystem {
                     attack.damage_bonus = np.attacks[idx].damage_bonus;
                     attack.damage_die_type = np.attacks[idx].damage_die_type;
                     attack.damage_n_dice = np.attacks[idx].damage_n_dice;
+                    attack.damage_type = np.attacks[idx].damage_type;
                 }
             }
 
-            for (wielded, melee) in (&equipped_items, &melee_weapons).join() {
-                if wielded.owner == entity && wielded.slot == EquipmentSlot::Melee {
-                    attack = *melee;
+            let mut weapon = None;
+            let mut off_hand = None;
+            for (item, wielded, melee) in (&entities, &equipped_items, &melee_weapons).join() {
+                if wielded.owner != entity {
+                    continue;
+                }
+                match wielded.slot {
+                    EquipmentSlot::Melee => {
+                        attack = *melee;
+                        weapon = Some(item);
+                    }
+                    EquipmentSlot::Shield => off_hand = Some((*melee, item)),
+                    _ => {}
                 }
             }
 
-            let hit_bonus_from_attribute = source_attributes.might.bonus();
-            let hit_bonus_from_weapon = attack.hit_bonus;
-            let hit_bonus_from_skill = source_skills.melee;
-
-            let hit_roll = natural_roll
-                + hit_bonus_from_attribute
-                + hit_bonus_from_skill
-                + hit_bonus_from_weapon;
+            // A weapon in the off hand swings again after the main one, less accurately.
+            let mut swings = vec![(attack, weapon, 0)];
+            if let Some((off_hand, item)) = off_hand {
+                swings.push((off_hand, Some(item), OFF_HAND_PENALTY));
+            }
 
-            let base_armor_class = if let Some(np) = natural_properties.get(wants_melee.target) {
-                np.armor_class.unwrap_or(10)
-            } else {
-                10
-            };
+            for (attack, weapon, penalty) in swings {
+                let natural_roll = rng.roll_dice(1, 20);
+                let hit_bonus_from_attribute = match attack.attribute {
+                    WeaponAttribute::Might => source_attributes.might.bonus(),
+                    WeaponAttribute::Quickness => source_attributes.quickness.bonus(),
+                };
+                let hit_bonus_from_weapon = attack.hit_bonus;
+                let hit_bonus_from_skill = source_skills.melee;
 
-            let armor_bonus_from_quickness = target_attributes.quickness.bonus();
-            let armor_bonus_from_skill = target_skills.defense;
-            let mut armor_bonus_from_item = 0;
+                let hit_roll = natural_roll
+                    + hit_bonus_from_attribute
+                    + hit_bonus_from_skill
+                    + hit_bonus_from_weapon
+                    + penalty;
 
-            for (wielded, armor) in (&equipped_items, &wearables).join() {
-                if wielded.owner == wants_melee.target {
-                    armor_bonus_from_item += armor.armor_class;
-                }
-            }
-
-            let armor_class_total = base_armor_class
-                + armor_bonus_from_item
-                + armor_bonus_from_quickness
-                + armor_bonus_from_skill;
-
-            if natural_roll > 1 && (natural_roll == 20 || hit_roll >= armor_class_total) {
-                let base_damage = rng.roll_dice(attack.damage_n_dice, attack.damage_die_type);
-                let bonus_damage_from_attribute = source_attributes.might.bonus();
-                let bonus_damage_from_skill = source_skills.melee;
-                let bonus_damage_from_weapon = attack.damage_bonus;
-
-                let damage_total = i32::max(
-                    0,
-                    base_damage
-                        + bonus_damage_from_attribute
-                        + bonus_damage_from_skill
-                        + bonus_damage_from_weapon,
-                );
-                SufferDamage::new_damage(
-                    &mut inflict_damage,
+                let armor_class_total = armor_class(
                     wants_melee.target,
-                    damage_total,
-                    entity == *player_entity,
+                    target_attributes,
+                    target_skills,
+                    &natural_properties,
+                    &equipped_items,
+                    &wearables,
                 );
-                Log::new()
-                    .append("(Roll")
-                    .roll(&format!("{:02}", hit_roll))
-                    .append("vs")
-                    .roll(&format!("{:02}", armor_class_total))
-                    .append("AC|")
-                    .npc(&source.name)
-                    .append("hits")
-                    .npc(&target.name)
-                    .append(", for")
-                    .bad(&damage_total)
-                    .append("hp")
-                    .build();
-                if let Some(pos) = positions.get(wants_melee.target) {
-                    particle_builder.request(
-                        pos.x,
-                        pos.y,
-                        c(YELLOW1),
-                        c(BLACK),
-                        to_cp437('‼'),
-                        200.0,
+
+                if is_hit(natural_roll, hit_roll, armor_class_total) {
+                    if let Some(shield) = shield_block(
+                        wants_melee.target,
+                        &entities,
+                        &equipped_items,
+                        &shields,
+                        &mut rng,
+                    ) {
+                        log_block(
+                            &source.name,
+                            &target.name,
+                            &sources
+                                .get(shield)
+                                .map_or(String::new(), |n| n.name.clone()),
+                            hit_roll,
+                            armor_class_total,
+                        );
+                        continue;
+                    }
+
+                    let base_damage = rng.roll_dice(attack.damage_n_dice, attack.damage_die_type);
+                    let bonus_damage_from_attribute = hit_bonus_from_attribute;
+                    let bonus_damage_from_skill = source_skills.melee;
+                    let bonus_damage_from_weapon = attack.damage_bonus;
+
+                    let damage_total = i32::max(
+                        0,
+                        base_damage
+                            + bonus_damage_from_attribute
+                            + bonus_damage_from_skill
+                            + bonus_damage_from_weapon,
                     );
-                }
-            } else if natural_roll == 1 {
-                //fumble
-                Log::new()
-                    .append("(Roll")
-                    .bad(&format!("{:02}", natural_roll))
-                    .append("vs")
-                    .roll(&format!("{:02}", armor_class_total))
-                    .append("AC|")
-                    .npc(&source.name)
-                    .append("attacks")
-                    .npc(&target.name)
-                    .append(".")
-                    .bad(&"Fumble!")
-                    .build();
-                if let Some(pos) = positions.get(wants_melee.target) {
-                    particle_builder.request(
-                        pos.x,
-                        pos.y,
-                        c(BLUE5),
-                        c(BLACK),
-                        to_cp437('‼'),
-                        200.0,
+                    let resistance = damage_resistance(
+                        wants_melee.target,
+                        attack.damage_type,
+                        &resistances,
+                        &equipped_items,
                     );
-                }
-            } else {
-                Log::new()
-                    .append("(Roll")
-                    .roll(&format!("{:02}", hit_roll))
-                    .append("vs")
-                    .roll(&format!("{:02}", armor_class_total))
-                    .append("AC|")
-                    .npc(&source.name)
-                    .append("attacks")
-                    .npc(&target.name)
-                    .append(", but misses.")
-                    .build();
-                if let Some(pos) = positions.get(wants_melee.target) {
-                    particle_builder.request(
-                        pos.x,
-                        pos.y,
-                        c(SHALLOWWATERS5),
-                        c(BLACK),
-                        to_cp437('‼'),
-                        200.0,
+                    let damage_total = resistance.apply(damage_total);
+                    SufferDamage::new_damage(
+                        &mut inflict_damage,
+                        wants_melee.target,
+                        damage_total,
+                        entity == *player_entity,
                     );
+                    let log = Log::new()
+                        .append("(Roll")
+                        .roll(&format!("{:02}", hit_roll))
+                        .append("vs")
+                        .roll(&format!("{:02}", armor_class_total))
+                        .append("AC|")
+                        .npc(&source.name)
+                        .append("hits")
+                        .npc(&target.name);
+                    log_damage(log, damage_total, attack.damage_type, resistance).build();
+                    if let Some((weapon, bonus)) =
+                        weapon.and_then(|w| damage_bonuses.get(w).map(|bonus| (w, bonus)))
+                    {
+                        let (extra, resistance) = roll_damage_bonus(
+                            bonus,
+                            wants_melee.target,
+                            &resistances,
+                            &equipped_items,
+                            &mut rng,
+                        );
+                        SufferDamage::new_damage(
+                            &mut inflict_damage,
+                            wants_melee.target,
+                            extra,
+                            entity == *player_entity,
+                        );
+                        log_damage_bonus(
+                            &sources
+                                .get(weapon)
+                                .map_or(String::new(), |n| n.name.clone()),
+                            &target.name,
+                            extra,
+                            bonus.damage_type,
+                            resistance,
+                        );
+                    }
+                    if let Some(pos) = positions.get(wants_melee.target) {
+                        particle_builder.request(
+                            pos.x,
+                            pos.y,
+                            c(YELLOW1),
+                            c(BLACK),
+                            to_cp437('‼'),
+                            200.0,
+                        );
+                    }
+                } else if natural_roll == 1 {
+                    //fumble
+                    Log::new()
+                        .append("(Roll")
+                        .bad(&format!("{:02}", natural_roll))
+                        .append("vs")
+                        .roll(&format!("{:02}", armor_class_total))
+                        .append("AC|")
+                        .npc(&source.name)
+                        .append("attacks")
+                        .npc(&target.name)
+                        .append(".")
+                        .bad(&"Fumble!")
+                        .build();
+                    if let Some(pos) = positions.get(wants_melee.target) {
+                        particle_builder.request(
+                            pos.x,
+                            pos.y,
+                            c(BLUE5),
+                            c(BLACK),
+                            to_cp437('‼'),
+                            200.0,
+                        );
+                    }
+                } else {
+                    Log::new()
+                        .append("(Roll")
+                        .roll(&format!("{:02}", hit_roll))
+                        .append("vs")
+                        .roll(&format!("{:02}", armor_class_total))
+                        .append("AC|")
+                        .npc(&source.name)
+                        .append("attacks")
+                        .npc(&target.name)
+                        .append(", but misses.")
+                        .build();
+                    if let Some(pos) = positions.get(wants_melee.target) {
+                        particle_builder.request(
+                            pos.x,
+                            pos.y,
+                            c(SHALLOWWATERS5),
+                            c(BLACK),
+                            to_cp437('‼'),
+                            200.0,
+                        );
+                    }
                 }
             }
         }
@@ -212,3 +283,198 @@ impl<'a> System<'a> for MeleeCombatSystem {
         wants_melee.clear();
     }
 }
+
+pub fn armor_class(
+    target: Entity,
+    target_attributes: &Attributes,
+    target_skills: &Skills,
+    natural_properties: &ReadStorage<NaturalProperty>,
+    equipped_items: &ReadStorage<Equipped>,
+    wearables: &ReadStorage<Wearable>,
+) -> i32 {
+    let base_armor_class = if let Some(np) = natural_properties.get(target) {
+        np.armor_class.unwrap_or(10)
+    } else {
+        10
+    };
+
+    let armor_bonus_from_quickness = target_attributes.quickness.bonus();
+    let armor_bonus_from_skill = target_skills.defense;
+    let mut armor_bonus_from_item = 0;
+
+    for (wielded, armor) in (equipped_items, wearables).join() {
+        if wielded.owner == target {
+            armor_bonus_from_item += armor.armor_class;
+        }
+    }
+
+    base_armor_class + armor_bonus_from_item + armor_bonus_from_quickness + armor_bonus_from_skill
+}
+
+/// How `target` takes `damage_type`, from its own hide and from whatever it
+/// has equipped.
+pub fn damage_resistance<D: Deref<Target = MaskedStorage<Equipped>>>(
+    target: Entity,
+    damage_type: DamageType,
+    resistances: &ReadStorage<Resistances>,
+    equipped_items: &Storage<Equipped, D>,
+) -> Resistance {
+    let mut resistance = resistances
+        .get(target)
+        .map_or(Resistance::Normal, |r| r.get(damage_type));
+
+    for (wielded, item) in (equipped_items, resistances).join() {
+        if wielded.owner == target {
+            resistance = resistance.combine(item.get(damage_type));
+        }
+    }
+
+    resistance
+}
+
+/// Rolls the extra damage an enchanted weapon adds to a hit, as `target`
+/// takes it.
+pub fn roll_damage_bonus(
+    bonus: &DamageBonus,
+    target: Entity,
+    resistances: &ReadStorage<Resistances>,
+    equipped_items: &ReadStorage<Equipped>,
+    rng: &mut RandomGen,
+) -> (i32, Resistance) {
+    let damage = i32::max(0, rng.roll_dice(bonus.n_dice, bonus.die_type) + bonus.bonus);
+    let resistance = damage_resistance(target, bonus.damage_type, resistances, equipped_items);
+    (resistance.apply(damage), resistance)
+}
+
+pub fn log_damage_bonus(
+    weapon: &str,
+    target: &str,
+    damage: i32,
+    damage_type: DamageType,
+    resistance: Resistance,
+) {
+    let log = Log::new()
+        .append("The")
+        .item(&weapon)
+        .append("bites into")
+        .npc(&target);
+    log_damage(log, damage, damage_type, resistance).build();
+}
+
+/// The shield `target` raises in time to stop a blow that landed, if any.
+pub fn shield_block(
+    target: Entity,
+    entities: &Entities,
+    equipped_items: &ReadStorage<Equipped>,
+    shields: &ReadStorage<Shield>,
+    rng: &mut RandomGen,
+) -> Option<Entity> {
+    let (item, shield) = (entities, equipped_items, shields)
+        .join()
+        .find(|(_, wielded, _)| wielded.owner == target)
+        .map(|(item, _, shield)| (item, shield))?;
+
+    if rng.roll_dice(1, 100) <= shield.block_chance {
+        Some(item)
+    } else {
+        None
+    }
+}
+
+pub fn log_block(source: &str, target: &str, shield: &str, hit_roll: i32, armor_class: i32) {
+    Log::new()
+        .append("(Roll")
+        .roll(&format!("{:02}", hit_roll))
+        .append("vs")
+        .roll(&format!("{:02}", armor_class))
+        .append("AC|")
+        .npc(&source)
+        .append("hits, but")
+        .npc(&target)
+        .append("blocks with the")
+        .item(&shield)
+        .append(".")
+        .build();
+}
+
+/// Ends a hit line with the damage dealt, its type and how it was resisted.
+pub fn log_damage(log: Log, damage: i32, damage_type: DamageType, resistance: Resistance) -> Log {
+    let log = log
+        .append(", for")
+        .bad(&damage)
+        .append(format!("{} damage", damage_type.name()));
+    match resistance.note() {
+        Some(note) => log.append(note),
+        None => log,
+    }
+}
+
+pub fn is_hit(natural_roll: i32, hit_roll: i32, armor_class: i32) -> bool {
+    natural_roll > 1 && (natural_roll == 20 || hit_roll >= armor_class)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn resistant_to(damage_type: DamageType, resistance: Resistance) -> Resistances {
+        Resistances {
+            table: HashMap::from([(damage_type, resistance)]),
+        }
+    }
+
+    fn wear(ecs: &mut World, owner: Entity, resistances: Resistances) -> Entity {
+        ecs.create_entity()
+            .with(Equipped {
+                owner,
+                slot: EquipmentSlot::Torso,
+            })
+            .with(resistances)
+            .build()
+    }
+
+    fn resistance_of(ecs: &World, target: Entity, damage_type: DamageType) -> Resistance {
+        damage_resistance(
+            target,
+            damage_type,
+            &ecs.read_storage::<Resistances>(),
+            &ecs.read_storage::<Equipped>(),
+        )
+    }
+
+    #[test]
+    fn equipped_gear_adds_to_the_wearer() {
+        let mut ecs = World::new();
+        ecs.register::<Equipped>();
+        ecs.register::<Resistances>();
+        let wearer = ecs
+            .create_entity()
+            .with(resistant_to(DamageType::Fire, Resistance::Vulnerable))
+            .build();
+        let bystander = ecs.create_entity().build();
+        wear(
+            &mut ecs,
+            wearer,
+            resistant_to(DamageType::Fire, Resistance::Resistant),
+        );
+        wear(
+            &mut ecs,
+            wearer,
+            resistant_to(DamageType::Cold, Resistance::Immune),
+        );
+        wear(
+            &mut ecs,
+            bystander,
+            resistant_to(DamageType::Acid, Resistance::Immune),
+        );
+
+        // The armor's resistance cancels the wearer's own vulnerability.
+        assert!(resistance_of(&ecs, wearer, DamageType::Fire) == Resistance::Normal);
+        assert!(resistance_of(&ecs, wearer, DamageType::Cold) == Resistance::Immune);
+        // Someone else's gear does nothing for the wearer, and vice versa.
+        assert!(resistance_of(&ecs, wearer, DamageType::Acid) == Resistance::Normal);
+        assert!(resistance_of(&ecs, bystander, DamageType::Cold) == Resistance::Normal);
+        assert!(resistance_of(&ecs, bystander, DamageType::Acid) == Resistance::Immune);
+    }
+}
@@ -1,31 +1,50 @@
 use super::{
-    action::{WantsToDropItem, WantsToMelee, WantsToPickupItem, WantsToRemoveItem, WantsToUseItem},
-    effect::{AreaOfEffect, Confusion, EntryTrigger, InflictsDamage, ProvidesHealing},
+    action::{
+        WantsToBashDoor, WantsToCastSpell, WantsToCloseDoor, WantsToDropItem, WantsToMelee,
+        WantsToOpenContainer, WantsToOpenDoor, WantsToPickupItem, WantsToRemoveItem, WantsToShoot,
+        WantsToUseItem,
+    },
+    effect::{
+        AreaOfEffect, DamageType, EntryTrigger, GrantsStatus, Identifies, InflictsDamage,
+        ProvidesFood, ProvidesHealing, RemovesCurse, Resistance, Resistances, SpellTemplate,
+        Stacking, StatusEffect, StatusKind, StatusTemplate,
+    },
     item::{
-        Consumable, EquipmentChanged, EquipmentSlot, Equippable, Equipped, InBackpack, MeleeWeapon,
-        Wearable,
+        item_label, Ammunition, Consumable, Cursed, DamageBonus, EquipmentChanged, EquipmentSlot,
+        Equippable, Equipped, Identification, InBackpack, MeleeWeapon, RangedWeapon, Shield,
+        Stackable, WeaponAttribute, Wearable,
     },
-    map::{tiles, Map},
-    props::{LightSource, SingleActivation},
+    map::Map,
+    props::{Container, Door, LightSource, SingleActivation, Trapped},
     raws::{
+        classes::find_class,
+        factions::{faction_reaction, parse_reaction, reaction_to, Reaction},
         SpawnType, RAWS,
         {items::spawn_named_item, loot::get_loots},
     },
     rng::RandomGen,
     state::RunState,
     unit::{
-        Attributes, EntityMoved, Initiative, LootTable, MyTurn, NaturalProperty, Player, Pools,
-        Skills, SufferDamage, Viewshed,
+        Attributes, CharacterClass, EntityMoved, Faction, HungerClock, HungerState, Initiative,
+        LevelUpPoints, LootTable, MyTurn, NaturalProperty, Player, Pools, Reputation, Skills,
+        SufferDamage, Viewshed,
     },
     BlocksTile, BlocksVisibility, Hidden, Log, Name, ParticleLifetime, Position, Renderable,
+    SerializeMe, TurnCounter,
 };
 
 pub mod ai;
+pub mod container;
 pub mod damage;
+pub mod door;
 pub mod inventory;
 pub mod lighting;
 pub mod map_indexing;
 pub mod melee_combat;
 pub mod particle;
+pub mod ranged_combat;
+pub mod reputation;
+pub mod spell;
+pub mod status;
 pub mod trigger;
 pub mod visibility;
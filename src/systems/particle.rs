@@ -1,15 +1,15 @@
 use super::{ParticleLifetime, Position, Renderable};
-use bracket_lib::terminal::{BTerm, RGB};
+use bracket_lib::terminal::RGB;
 use bracket_terminal::FontCharType;
 use specs::prelude::*;
 
-pub fn cull_dead_particles(ecs: &mut World, ctx: &BTerm) {
+pub fn cull_dead_particles(ecs: &mut World, frame_time_ms: f32) {
     let mut dead_particles = Vec::<Entity>::new();
     {
         let mut particles = ecs.write_storage::<ParticleLifetime>();
         let entities = ecs.entities();
-        for (entity, mut particle) in (&entities, &mut particles).join() {
-            particle.lifetime_ms -= ctx.frame_time_ms;
+        for (entity, particle) in (&entities, &mut particles).join() {
+            particle.lifetime_ms -= frame_time_ms;
             if particle.lifetime_ms < 0.0 {
                 dead_particles.push(entity);
             }
@@ -0,0 +1,369 @@
+use super::{
+    super::colors::*,
+    inventory::use_up_one,
+    melee_combat::{
+        armor_class, damage_resistance, is_hit, log_block, log_damage, log_damage_bonus,
+        roll_damage_bonus, shield_block,
+    },
+    particle::ParticleBuilder,
+    Ammunition, Attributes, DamageBonus, EquipmentChanged, EquipmentSlot, Equipped, InBackpack,
+    Log, Map, Name, NaturalProperty, Pools, Position, RandomGen, RangedWeapon, Resistances, Shield,
+    Skills, Stackable, SufferDamage, WantsToShoot, WeaponAttribute, Wearable,
+};
+use bracket_lib::{
+    prelude::{Algorithm2D, BaseMap},
+    terminal::{line2d, to_cp437, DistanceAlg, LineAlg, Point},
+};
+use specs::prelude::*;
+
+pub struct RangedCombatSystem {}
+
+impl<'a> System<'a> for RangedCombatSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, WantsToShoot>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Pools>,
+        WriteStorage<'a, SufferDamage>,
+        WriteExpect<'a, ParticleBuilder>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, Skills>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, RangedWeapon>,
+        ReadStorage<'a, Wearable>,
+        ReadStorage<'a, NaturalProperty>,
+        ReadStorage<'a, InBackpack>,
+        ReadStorage<'a, Ammunition>,
+        WriteStorage<'a, EquipmentChanged>,
+        ReadExpect<'a, Map>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, RandomGen>,
+        ReadStorage<'a, Resistances>,
+        ReadStorage<'a, Shield>,
+        ReadStorage<'a, DamageBonus>,
+        WriteStorage<'a, Stackable>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut wants_shoot,
+            sources,
+            pools,
+            mut inflict_damage,
+            mut particle_builder,
+            positions,
+            attributes,
+            skills,
+            equipped_items,
+            ranged_weapons,
+            wearables,
+            natural_properties,
+            backpack,
+            ammunition,
+            mut dirty,
+            map,
+            player_entity,
+            mut rng,
+            resistances,
+            shields,
+            damage_bonuses,
+            mut stacks,
+        ) = data;
+
+        for (entity, wants_shoot, source, source_attributes, source_skills, source_pools, pos) in (
+            &entities,
+            &wants_shoot,
+            &sources,
+            &attributes,
+            &skills,
+            &pools,
+            &positions,
+        )
+            .join()
+        {
+            if let Some(target_pools) = pools.get(wants_shoot.target) {
+                if target_pools.hit_points.current <= 0 {
+                    continue;
+                }
+            } else {
+                continue;
+            }
+
+            if source_pools.hit_points.current <= 0 {
+                continue;
+            }
+
+            let (weapon_entity, weapon) =
+                match equipped_ranged_weapon(&entities, &equipped_items, &ranged_weapons, entity) {
+                    Some(weapon) => weapon,
+                    None => continue,
+                };
+
+            let target_pos = match positions.get(wants_shoot.target) {
+                Some(target_pos) => target_pos.into_point(),
+                None => continue,
+            };
+
+            if !in_range(pos.into_point(), target_pos, weapon.range)
+                || !has_line_of_fire(&map, pos.into_point(), target_pos)
+            {
+                if entity == *player_entity {
+                    Log::new().append("You have no line of fire.").build();
+                }
+                continue;
+            }
+
+            if let Some(kind) = &weapon.ammo {
+                match find_ammunition(
+                    &entities,
+                    &backpack,
+                    &ammunition,
+                    entity,
+                    weapon_entity,
+                    kind,
+                ) {
+                    Some(ammo) => {
+                        use_up_one(&entities, &mut stacks, ammo);
+                        dirty
+                            .insert(entity, EquipmentChanged {})
+                            .expect("Unable to insert EquipmentChanged");
+                    }
+                    None => {
+                        if entity == *player_entity {
+                            Log::new()
+                                .append("You are out of")
+                                .item(kind)
+                                .append(".")
+                                .build();
+                        }
+                        continue;
+                    }
+                }
+            }
+
+            for point in line2d(LineAlg::Bresenham, pos.into_point(), target_pos)
+                .iter()
+                .filter(|p| **p != pos.into_point() && **p != target_pos)
+            {
+                particle_builder.request(point.x, point.y, c(GREY), c(BLACK), to_cp437('∙'), 100.0);
+            }
+
+            let target = sources.get(wants_shoot.target).unwrap();
+            let target_attributes = attributes.get(wants_shoot.target).unwrap();
+            let target_skills = skills.get(wants_shoot.target).unwrap();
+            let natural_roll = rng.roll_dice(1, 20);
+
+            let attribute_bonus = match weapon.attribute {
+                WeaponAttribute::Might => source_attributes.might.bonus(),
+                WeaponAttribute::Quickness => source_attributes.quickness.bonus(),
+            };
+            let hit_roll = natural_roll + attribute_bonus + source_skills.ranged + weapon.hit_bonus;
+
+            let armor_class_total = armor_class(
+                wants_shoot.target,
+                target_attributes,
+                target_skills,
+                &natural_properties,
+                &equipped_items,
+                &wearables,
+            );
+
+            if is_hit(natural_roll, hit_roll, armor_class_total) {
+                if let Some(shield) = shield_block(
+                    wants_shoot.target,
+                    &entities,
+                    &equipped_items,
+                    &shields,
+                    &mut rng,
+                ) {
+                    log_block(
+                        &source.name,
+                        &target.name,
+                        &sources
+                            .get(shield)
+                            .map_or(String::new(), |n| n.name.clone()),
+                        hit_roll,
+                        armor_class_total,
+                    );
+                    continue;
+                }
+
+                let base_damage = rng.roll_dice(weapon.damage_n_dice, weapon.damage_die_type);
+                let damage_total = i32::max(
+                    0,
+                    base_damage + attribute_bonus + source_skills.ranged + weapon.damage_bonus,
+                );
+                let resistance = damage_resistance(
+                    wants_shoot.target,
+                    weapon.damage_type,
+                    &resistances,
+                    &equipped_items,
+                );
+                let damage_total = resistance.apply(damage_total);
+                SufferDamage::new_damage(
+                    &mut inflict_damage,
+                    wants_shoot.target,
+                    damage_total,
+                    entity == *player_entity,
+                );
+                let log = Log::new()
+                    .append("(Roll")
+                    .roll(&format!("{:02}", hit_roll))
+                    .append("vs")
+                    .roll(&format!("{:02}", armor_class_total))
+                    .append("AC|")
+                    .npc(&source.name)
+                    .append("shoots")
+                    .npc(&target.name);
+                log_damage(log, damage_total, weapon.damage_type, resistance).build();
+                if let Some(bonus) = damage_bonuses.get(weapon_entity) {
+                    let (extra, resistance) = roll_damage_bonus(
+                        bonus,
+                        wants_shoot.target,
+                        &resistances,
+                        &equipped_items,
+                        &mut rng,
+                    );
+                    SufferDamage::new_damage(
+                        &mut inflict_damage,
+                        wants_shoot.target,
+                        extra,
+                        entity == *player_entity,
+                    );
+                    log_damage_bonus(
+                        &sources
+                            .get(weapon_entity)
+                            .map_or(String::new(), |n| n.name.clone()),
+                        &target.name,
+                        extra,
+                        bonus.damage_type,
+                        resistance,
+                    );
+                }
+                particle_builder.request(
+                    target_pos.x,
+                    target_pos.y,
+                    c(YELLOW1),
+                    c(BLACK),
+                    to_cp437('‼'),
+                    200.0,
+                );
+            } else if natural_roll == 1 {
+                //fumble
+                Log::new()
+                    .append("(Roll")
+                    .bad(&format!("{:02}", natural_roll))
+                    .append("vs")
+                    .roll(&format!("{:02}", armor_class_total))
+                    .append("AC|")
+                    .npc(&source.name)
+                    .append("shoots at")
+                    .npc(&target.name)
+                    .append(".")
+                    .bad(&"Fumble!")
+                    .build();
+                particle_builder.request(
+                    target_pos.x,
+                    target_pos.y,
+                    c(BLUE5),
+                    c(BLACK),
+                    to_cp437('‼'),
+                    200.0,
+                );
+            } else {
+                Log::new()
+                    .append("(Roll")
+                    .roll(&format!("{:02}", hit_roll))
+                    .append("vs")
+                    .roll(&format!("{:02}", armor_class_total))
+                    .append("AC|")
+                    .npc(&source.name)
+                    .append("shoots at")
+                    .npc(&target.name)
+                    .append(", but misses.")
+                    .build();
+                particle_builder.request(
+                    target_pos.x,
+                    target_pos.y,
+                    c(SHALLOWWATERS5),
+                    c(BLACK),
+                    to_cp437('‼'),
+                    200.0,
+                );
+            }
+        }
+
+        wants_shoot.clear();
+    }
+}
+
+pub fn equipped_ranged_weapon(
+    entities: &Entities,
+    equipped_items: &ReadStorage<Equipped>,
+    ranged_weapons: &ReadStorage<RangedWeapon>,
+    owner: Entity,
+) -> Option<(Entity, RangedWeapon)> {
+    for (weapon_entity, wielded, weapon) in (entities, equipped_items, ranged_weapons).join() {
+        if wielded.owner == owner && wielded.slot == EquipmentSlot::Ranged {
+            return Some((weapon_entity, weapon.clone()));
+        }
+    }
+
+    None
+}
+
+/// The ranged weapon `owner` wields, as long as it has something to fire.
+pub fn loaded_ranged_weapon(
+    entities: &Entities,
+    equipped_items: &ReadStorage<Equipped>,
+    ranged_weapons: &ReadStorage<RangedWeapon>,
+    backpack: &ReadStorage<InBackpack>,
+    ammunition: &ReadStorage<Ammunition>,
+    owner: Entity,
+) -> Option<(Entity, RangedWeapon)> {
+    equipped_ranged_weapon(entities, equipped_items, ranged_weapons, owner).filter(
+        |(weapon_entity, weapon)| match &weapon.ammo {
+            Some(kind) => {
+                find_ammunition(entities, backpack, ammunition, owner, *weapon_entity, kind)
+                    .is_some()
+            }
+            None => true,
+        },
+    )
+}
+
+/// Ammunition is taken from the owner's backpack first; a thrown weapon that
+/// is its own ammunition is used up last.
+pub fn find_ammunition(
+    entities: &Entities,
+    backpack: &ReadStorage<InBackpack>,
+    ammunition: &ReadStorage<Ammunition>,
+    owner: Entity,
+    weapon: Entity,
+    kind: &str,
+) -> Option<Entity> {
+    for (ammo_entity, carried_by, ammo) in (entities, backpack, ammunition).join() {
+        if carried_by.owner == owner && ammo.kind == kind {
+            return Some(ammo_entity);
+        }
+    }
+
+    match ammunition.get(weapon) {
+        Some(ammo) if ammo.kind == kind => Some(weapon),
+        _ => None,
+    }
+}
+
+pub fn in_range(from: Point, to: Point, range: i32) -> bool {
+    DistanceAlg::PythagorasSquared.distance2d(from, to) <= (range * range) as f32
+}
+
+pub fn has_line_of_fire(map: &Map, from: Point, to: Point) -> bool {
+    line2d(LineAlg::Bresenham, from, to)
+        .iter()
+        .filter(|p| **p != from && **p != to)
+        .all(|p| map.in_bounds(*p) && !map.is_opaque(map.point2d_to_index(*p)))
+}
@@ -0,0 +1,158 @@
+use super::{
+    faction_reaction, find_class, parse_reaction, reaction_to, CharacterClass, Faction, Log, Pools,
+    Position, Reaction, Reputation, SufferDamage, TurnCounter, Viewshed, RAWS,
+};
+use specs::prelude::*;
+use std::collections::HashSet;
+
+const ATTACK_PENALTY: i32 = 6;
+const WITNESS_PENALTY: i32 = 3;
+const KILL_BONUS: i32 = 2;
+/// Ticks between each step of reputation fading back toward neutral.
+const DECAY_INTERVAL: i32 = 200;
+
+pub struct ReputationSystem {}
+
+impl<'a> System<'a> for ReputationSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        WriteExpect<'a, Reputation>,
+        ReadStorage<'a, SufferDamage>,
+        ReadStorage<'a, Pools>,
+        WriteStorage<'a, Faction>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Viewshed>,
+        ReadStorage<'a, CharacterClass>,
+        ReadExpect<'a, TurnCounter>,
+        ReadExpect<'a, Entity>,
+        Entities<'a>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            mut reputation,
+            damage,
+            pools,
+            mut factions,
+            positions,
+            viewsheds,
+            classes,
+            turn_counter,
+            player,
+            entities,
+        ) = data;
+        let raws = RAWS.lock().unwrap();
+        // Sorted so that the log reads the same on every run of a seed.
+        let mut faction_names: Vec<&String> = raws.faction_index.keys().collect();
+        faction_names.sort();
+
+        // Runs before the damage system, so this turn's blows are still pending.
+        let mut attacked = Vec::<(Entity, String, Position, bool)>::new();
+        for (victim, damage, stats, faction, pos) in
+            (&entities, &damage, &pools, &factions, &positions).join()
+        {
+            if victim == *player || !damage.amount.iter().any(|(_, by_player)| *by_player) {
+                continue;
+            }
+            let total: i32 = damage.amount.iter().map(|(amount, _)| *amount).sum();
+            let killed = stats.hit_points.current - total < 1;
+            attacked.push((victim, faction.name.clone(), *pos, killed));
+        }
+
+        let player_faction = factions.get(*player).cloned();
+        for (victim, faction, pos, killed) in attacked {
+            // Whoever the victim's faction was at odds with is grateful for the kill.
+            if killed {
+                for other in faction_names.iter().copied() {
+                    if *other != faction
+                        && *other != "Player"
+                        && faction_reaction(other, "Player", &raws) != Reaction::Attack
+                        && faction_reaction(other, &faction, &raws) != Reaction::Ignore
+                    {
+                        reputation.shift(other, KILL_BONUS);
+                    }
+                }
+            }
+
+            let already_hostile = player_faction
+                .as_ref()
+                .is_some_and(|pf| reaction_to(&faction, pf, &raws) == Reaction::Attack);
+            if already_hostile {
+                continue;
+            }
+
+            // A survivor tells its kin; a kill only gets out if someone saw it.
+            let mut known = !killed;
+            let mut witnesses = HashSet::<String>::new();
+            let pt = pos.into_point();
+            for (other, other_faction, viewshed) in (&entities, &factions, &viewsheds).join() {
+                if other == victim || other == *player || !viewshed.visible_tiles.contains(&pt) {
+                    continue;
+                }
+                if other_faction.name == faction {
+                    known = true;
+                } else if other_faction.name != "Player"
+                    && faction_reaction(&other_faction.name, &faction, &raws) == Reaction::Ignore
+                {
+                    witnesses.insert(other_faction.name.clone());
+                }
+            }
+            if known {
+                reputation.shift(&faction, -ATTACK_PENALTY);
+            }
+            for witness in witnesses.iter() {
+                reputation.shift(witness, -WITNESS_PENALTY);
+            }
+        }
+
+        if turn_counter.turn - reputation.last_decay >= DECAY_INTERVAL {
+            reputation.last_decay = turn_counter.turn;
+            reputation.dirty |= !reputation.scores.is_empty();
+            for score in reputation.scores.values_mut() {
+                *score -= score.signum();
+            }
+            reputation.scores.retain(|_, score| *score != 0);
+        }
+
+        if !reputation.dirty {
+            return;
+        }
+        reputation.dirty = false;
+
+        // Rebuild the player's standing on top of what their class starts with.
+        let baseline = classes
+            .get(*player)
+            .and_then(|class| find_class(&raws, &class.name))
+            .and_then(|class| class.faction_standing.as_ref());
+        let player_faction = match factions.get_mut(*player) {
+            Some(faction) => faction,
+            None => return,
+        };
+        for faction in faction_names.iter().copied() {
+            if *faction == "Player" {
+                continue;
+            }
+            let table = faction_reaction(faction, &player_faction.name, &raws);
+            let base = baseline
+                .and_then(|standing| standing.get(faction))
+                .map_or(table, |reaction| parse_reaction(reaction));
+            let before = reaction_to(faction, player_faction, &raws);
+            let after = reputation.reaction(faction, base);
+
+            if after == table {
+                player_faction.standing.remove(faction);
+            } else {
+                player_faction.standing.insert(faction.clone(), after);
+            }
+
+            if before != after {
+                let news = match after {
+                    Reaction::Attack => "now consider you an enemy!",
+                    Reaction::Ignore => "no longer bear you ill will.",
+                    Reaction::Flee => "now fear you.",
+                };
+                Log::new().append("The").npc(faction).append(news).build();
+            }
+        }
+    }
+}
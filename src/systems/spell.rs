@@ -0,0 +1,123 @@
+use super::{
+    super::colors::*, particle::ParticleBuilder, Attributes, Log, Name, Pools, Position, RandomGen,
+    Skills, SpellTemplate, WantsToCastSpell, WantsToUseItem,
+};
+use bracket_lib::terminal::to_cp437;
+use specs::prelude::*;
+
+pub struct SpellCastSystem {}
+
+impl<'a> System<'a> for SpellCastSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, WantsToCastSpell>,
+        WriteStorage<'a, WantsToUseItem>,
+        ReadStorage<'a, SpellTemplate>,
+        ReadStorage<'a, Name>,
+        WriteStorage<'a, Pools>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, Skills>,
+        ReadStorage<'a, Position>,
+        WriteExpect<'a, ParticleBuilder>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, RandomGen>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut wants_cast,
+            mut wants_use,
+            spell_templates,
+            names,
+            mut pools,
+            attributes,
+            skills,
+            positions,
+            mut particle_builder,
+            player_entity,
+            mut rng,
+        ) = data;
+
+        for (entity, cast, caster, caster_pools, caster_attributes, caster_skills) in (
+            &entities,
+            &wants_cast,
+            &names,
+            &mut pools,
+            &attributes,
+            &skills,
+        )
+            .join()
+        {
+            let spell = match spell_templates.get(cast.spell) {
+                Some(spell) => spell,
+                None => continue,
+            };
+            let spell_name = &names.get(cast.spell).unwrap().name;
+
+            if caster_pools.mana.current < spell.mana_cost {
+                if entity == *player_entity {
+                    Log::new()
+                        .append("You don't have enough mana to cast")
+                        .item(spell_name)
+                        .append(".")
+                        .build();
+                }
+                continue;
+            }
+            caster_pools.mana.current -= spell.mana_cost;
+
+            let natural_roll = rng.roll_dice(1, 20);
+            let cast_roll =
+                natural_roll + caster_attributes.intelligence.bonus() + caster_skills.magic;
+
+            if natural_roll == 1 || cast_roll < spell.difficulty {
+                Log::new()
+                    .append("(Roll")
+                    .roll(&format!("{:02}", cast_roll))
+                    .append("vs")
+                    .roll(&format!("{:02}", spell.difficulty))
+                    .append("DC|")
+                    .npc(&caster.name)
+                    .append("tries to cast")
+                    .item(spell_name)
+                    .append(", but it fizzles.")
+                    .build();
+                if let Some(pos) = positions.get(entity) {
+                    particle_builder.request(
+                        pos.x,
+                        pos.y,
+                        c(BLUE5),
+                        c(BLACK),
+                        to_cp437('*'),
+                        200.0,
+                    );
+                }
+                continue;
+            }
+
+            Log::new()
+                .append("(Roll")
+                .roll(&format!("{:02}", cast_roll))
+                .append("vs")
+                .roll(&format!("{:02}", spell.difficulty))
+                .append("DC|")
+                .npc(&caster.name)
+                .append("casts")
+                .item(spell_name)
+                .build();
+            wants_use
+                .insert(
+                    entity,
+                    WantsToUseItem {
+                        item: cast.spell,
+                        target: cast.target,
+                    },
+                )
+                .expect("Unable to insert intent");
+        }
+
+        wants_cast.clear();
+    }
+}
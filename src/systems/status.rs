@@ -0,0 +1,102 @@
+use super::{EquipmentChanged, SerializeMe, Stacking, StatusEffect, StatusKind, StatusTemplate};
+use specs::{
+    prelude::*,
+    saveload::{MarkerAllocator, SimpleMarker, SimpleMarkerAllocator},
+};
+
+/// Applies a status to `target`, honouring the stacking rule of its kind:
+/// refreshing statuses update the existing instance, stacking ones always
+/// add a new one.
+pub fn apply_status(
+    entities: &Entities,
+    lazy: &LazyUpdate,
+    statuses: &mut WriteStorage<StatusEffect>,
+    target: Entity,
+    template: &StatusTemplate,
+) {
+    if let StatusKind::AttributeModifier { .. } = template.kind {
+        lazy.insert(target, EquipmentChanged {});
+    }
+
+    for status in statuses.join() {
+        if status.target == target
+            && status.kind.same_kind(template.kind)
+            && status.kind.stacking() == Stacking::Refresh
+        {
+            status.kind = template.kind;
+            status.duration = i32::max(status.duration, template.duration);
+            return;
+        }
+    }
+
+    // Created right away rather than lazily, so that a second application in
+    // the same frame finds this one instead of adding a duplicate.
+    let status = entities.create();
+    statuses
+        .insert(
+            status,
+            StatusEffect {
+                target,
+                kind: template.kind,
+                duration: template.duration,
+            },
+        )
+        .expect("Unable to insert status");
+    lazy.exec_mut(move |ecs| {
+        let mut markers = ecs.write_storage::<SimpleMarker<SerializeMe>>();
+        ecs.write_resource::<SimpleMarkerAllocator<SerializeMe>>()
+            .mark(status, &mut markers);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn world() -> World {
+        let mut ecs = World::new();
+        ecs.register::<StatusEffect>();
+        ecs.register::<EquipmentChanged>();
+        ecs.register::<SimpleMarker<SerializeMe>>();
+        ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
+        ecs
+    }
+
+    fn apply_twice(ecs: &mut World, target: Entity, kind: StatusKind) {
+        ecs.exec(
+            |(entities, lazy, mut statuses): (
+                Entities,
+                Read<LazyUpdate>,
+                WriteStorage<StatusEffect>,
+            )| {
+                for duration in [3, 5] {
+                    let template = StatusTemplate { kind, duration };
+                    apply_status(&entities, &lazy, &mut statuses, target, &template);
+                }
+            },
+        );
+        ecs.maintain();
+    }
+
+    #[test]
+    fn refreshing_statuses_merge_within_a_frame() {
+        let mut ecs = world();
+        let target = ecs.create_entity().build();
+        apply_twice(&mut ecs, target, StatusKind::Stun);
+
+        let statuses = ecs.read_storage::<StatusEffect>();
+        let applied: Vec<&StatusEffect> = statuses.join().collect();
+        assert_eq!(applied.len(), 1);
+        assert_eq!(applied[0].duration, 5);
+        assert_eq!(ecs.read_storage::<SimpleMarker<SerializeMe>>().count(), 1);
+    }
+
+    #[test]
+    fn stacking_statuses_add_up_within_a_frame() {
+        let mut ecs = world();
+        let target = ecs.create_entity().build();
+        apply_twice(&mut ecs, target, StatusKind::Poison { damage: 1 });
+
+        assert_eq!(ecs.read_storage::<StatusEffect>().count(), 2);
+    }
+}
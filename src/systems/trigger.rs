@@ -1,6 +1,9 @@
 use super::{
-    super::colors::*, particle::ParticleBuilder, EntityMoved, EntryTrigger, Hidden, InflictsDamage,
-    Log, Map, Name, Position, SingleActivation, SufferDamage,
+    super::colors::*,
+    melee_combat::{damage_resistance, log_damage},
+    particle::ParticleBuilder,
+    EntityMoved, EntryTrigger, Equipped, Hidden, InflictsDamage, Log, Map, Name, Position,
+    Resistances, SingleActivation, SufferDamage,
 };
 use bracket_lib::terminal::to_cp437;
 use specs::prelude::*;
@@ -21,6 +24,8 @@ impl<'a> System<'a> for TriggerSystem {
         WriteStorage<'a, SufferDamage>,
         WriteExpect<'a, ParticleBuilder>,
         ReadStorage<'a, SingleActivation>,
+        ReadStorage<'a, Resistances>,
+        ReadStorage<'a, Equipped>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -36,29 +41,38 @@ impl<'a> System<'a> for TriggerSystem {
             mut inflict_damage,
             mut particle_builder,
             single_activation,
+            resistances,
+            equipped,
         ) = data;
 
         let mut remove_entities = Vec::<Entity>::new();
         for (entity, _, pos) in (&entities, &entity_moved, &position).join() {
             let idx = map.coord_to_index(pos.x, pos.y);
-            for entity_id in map.tiles[idx].content.iter() {
-                if entity == *entity_id || entry_trigger.get(*entity_id).is_none() {
+            for entity_id in map.index.entities_at(idx) {
+                if entity == entity_id || entry_trigger.get(entity_id).is_none() {
                     continue;
                 }
 
-                if let Some(e) = names.get(*entity_id) {
+                if let Some(e) = names.get(entity_id) {
                     Log::new().item(&e.name).append("triggers!").build();
                 }
 
-                hidden.remove(*entity_id);
+                hidden.remove(entity_id);
 
-                if let Some(damage) = inflicts_damage.get(*entity_id) {
+                if let Some(damage) = inflicts_damage.get(entity_id) {
                     particle_builder.request(pos.x, pos.y, c(RED3), c(BLACK), to_cp437('‼'), 200.0);
-                    SufferDamage::new_damage(&mut inflict_damage, entity, damage.damage, false);
+                    let resistance =
+                        damage_resistance(entity, damage.damage_type, &resistances, &equipped);
+                    let amount = resistance.apply(damage.damage);
+                    SufferDamage::new_damage(&mut inflict_damage, entity, amount, false);
+                    if let (Some(trap), Some(victim)) = (names.get(entity_id), names.get(entity)) {
+                        let log = Log::new().item(&trap.name).append("hits").npc(&victim.name);
+                        log_damage(log, amount, damage.damage_type, resistance).build();
+                    }
                 }
 
-                if single_activation.get(*entity_id).is_some() {
-                    remove_entities.push(*entity_id);
+                if single_activation.get(entity_id).is_some() {
+                    remove_entities.push(entity_id);
                 }
             }
         }
@@ -14,13 +14,21 @@ impl<'a> System<'a> for VisibilitySystem {
         WriteStorage<'a, Hidden>,
         ReadStorage<'a, Name>,
         ReadStorage<'a, BlocksVisibility>,
+        WriteExpect<'a, RandomGen>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut map, entities, mut viewshed, pos, player, mut hidden, names, blocks_visibility) =
-            data;
-
-        let mut rng = RandomGen::default();
+        let (
+            mut map,
+            entities,
+            mut viewshed,
+            pos,
+            player,
+            mut hidden,
+            names,
+            blocks_visibility,
+            mut rng,
+        ) = data;
 
         for i in 0..map.tiles.len() {
             map.tiles[i].block_visibility = false;
@@ -40,6 +48,9 @@ impl<'a> System<'a> for VisibilitySystem {
             viewshed.visible_tiles.clear();
             viewshed.visible_tiles = field_of_view(pos.into_point(), viewshed.range, &*map);
             viewshed.visible_tiles.retain(|p| map.in_bounds(*p));
+            // field_of_view comes out of a HashSet; sort so rolls made per tile
+            // happen in the same order on every run.
+            viewshed.visible_tiles.sort_by_key(|p| (p.y, p.x));
 
             if player.get(ent).is_none() {
                 continue;
@@ -53,16 +64,16 @@ impl<'a> System<'a> for VisibilitySystem {
                 map.tiles[idx].revealed = true;
                 map.tiles[idx].visible = true;
 
-                for e in map.tiles[idx].content.iter() {
-                    if hidden.get(*e).is_none() || rng.range(1, 24) > 1 {
+                for e in map.index.entities_at(idx) {
+                    if hidden.get(e).is_none() || rng.range(1, 24) > 1 {
                         continue;
                     }
 
-                    if let Some(e) = names.get(*e) {
+                    if let Some(e) = names.get(e) {
                         Log::new().append("You spotted a").item(&e.name).build();
                     }
 
-                    hidden.remove(*e);
+                    hidden.remove(e);
                 }
             }
         }